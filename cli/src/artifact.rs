@@ -0,0 +1,225 @@
+use anyhow::{Context, Result};
+use ethers::utils::hex;
+use serde_json::Value;
+use std::path::Path;
+
+/// A compiled contract read from a Hardhat or Foundry artifact
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Artifact {
+    pub abi: Value,
+    /// Creation code sent by `deploy`
+    pub bytecode: Vec<u8>,
+    /// Runtime code as hex, left undecoded since it may hold library placeholders
+    pub deployed_bytecode: Option<String>,
+}
+
+impl Artifact {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read contract artifact {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("Invalid contract artifact {}", path.display()))
+    }
+
+    /// Parse an artifact in either layout: Hardhat's, where `bytecode` and
+    /// `deployedBytecode` are hex strings, or Foundry's, where each is an
+    /// object with the hex under `object` (next to `sourceMap` and
+    /// `linkReferences`)
+    pub fn parse(contents: &str) -> Result<Self> {
+        let artifact: Value = serde_json::from_str(contents).context("Artifact is not JSON")?;
+        let bytecode = code_hex(&artifact["bytecode"]).context(
+            "No bytecode found: expected a \"bytecode\" string (Hardhat) or a \"bytecode.object\" string (Foundry)",
+        )?;
+        let bytecode = hex::decode(bytecode.strip_prefix("0x").unwrap_or(bytecode))
+            .context("Bytecode is not plain hex (does it need libraries linked?)")?;
+        if bytecode.is_empty() {
+            anyhow::bail!("Bytecode is empty; is this an interface or abstract contract?");
+        }
+
+        Ok(Self {
+            abi: artifact["abi"].clone(),
+            bytecode,
+            deployed_bytecode: code_hex(&artifact["deployedBytecode"]).map(str::to_string),
+        })
+    }
+
+    /// Number of arguments the constructor takes, per the ABI
+    pub fn constructor_inputs(&self) -> usize {
+        self.abi
+            .as_array()
+            .and_then(|items| items.iter().find(|item| item["type"] == "constructor"))
+            .and_then(|constructor| constructor["inputs"].as_array())
+            .map_or(0, Vec::len)
+    }
+}
+
+/// The hex of a bytecode field in either layout
+fn code_hex(field: &Value) -> Option<&str> {
+    field.as_str().or_else(|| field["object"].as_str())
+}
+
+/// One function from an ABI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbiMethod {
+    pub name: String,
+    /// Canonical input types, e.g. `["string", "uint256"]`
+    pub inputs: Vec<String>,
+    /// `pure`, `view`, `nonpayable` or `payable`
+    pub state_mutability: String,
+}
+
+impl AbiMethod {
+    /// The signature the selector is computed from, e.g. `addEndpoint(string,string)`
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.name, self.inputs.join(","))
+    }
+
+    /// Whether the method only reads state, so it can be run with `eth_call`
+    pub fn is_read_only(&self) -> bool {
+        matches!(self.state_mutability.as_str(), "view" | "pure")
+    }
+}
+
+/// The functions of a contract ABI, looked up by name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AbiRegistry {
+    methods: Vec<AbiMethod>,
+}
+
+impl AbiRegistry {
+    /// Read the `function` entries of an ABI array; events, errors and the
+    /// constructor are skipped
+    pub fn from_abi(abi: &Value) -> Result<Self> {
+        let items = abi.as_array().context("The artifact has no \"abi\" array")?;
+        let methods = items
+            .iter()
+            .filter(|item| item["type"] == "function")
+            .map(|item| {
+                let name = item["name"].as_str().context("ABI function without a name")?;
+                let inputs = item["inputs"]
+                    .as_array()
+                    .map(|inputs| inputs.iter().map(canonical_type).collect::<Result<Vec<_>>>())
+                    .transpose()?
+                    .unwrap_or_default();
+                Ok(AbiMethod {
+                    name: name.to_string(),
+                    inputs,
+                    // Old ABIs only have `constant`
+                    state_mutability: item["stateMutability"].as_str().map_or_else(
+                        || if item["constant"] == true { "view" } else { "nonpayable" }.to_string(),
+                        str::to_string,
+                    ),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { methods })
+    }
+
+    /// Find a method by full signature (`addEndpoint(string)`), or by name
+    /// and argument count when the name alone is ambiguous
+    pub fn method(&self, name: &str, arg_count: usize) -> Result<&AbiMethod> {
+        if name.contains('(') {
+            let signature: String = name.split_whitespace().collect();
+            return self.methods
+                .iter()
+                .find(|method| method.signature() == signature)
+                .with_context(|| format!("The ABI has no method {}", signature));
+        }
+
+        let named: Vec<&AbiMethod> = self.methods.iter().filter(|method| method.name == name).collect();
+        let signatures = |methods: &[&AbiMethod]| methods.iter().map(|m| m.signature()).collect::<Vec<_>>().join(", ");
+        match named.iter().filter(|method| method.inputs.len() == arg_count).collect::<Vec<_>>().as_slice() {
+            [method] => Ok(method),
+            [] if named.is_empty() => anyhow::bail!("The ABI has no method named {}", name),
+            [] => anyhow::bail!("{} doesn't take {} argument(s); the ABI has {}", name, arg_count, signatures(&named)),
+            _ => anyhow::bail!("{} is overloaded; pass the full signature, one of {}", name, signatures(&named)),
+        }
+    }
+}
+
+/// The canonical type of an ABI parameter, expanding tuples into their components
+fn canonical_type(param: &Value) -> Result<String> {
+    let kind = param["type"].as_str().context("ABI parameter without a type")?;
+    match kind.strip_prefix("tuple") {
+        Some(suffix) => {
+            let components = param["components"]
+                .as_array()
+                .context("ABI tuple without components")?
+                .iter()
+                .map(canonical_type)
+                .collect::<Result<Vec<_>>>()?;
+            Ok(format!("({}){}", components.join(","), suffix))
+        }
+        None => Ok(kind.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_artifact() {
+        let hardhat = Artifact::parse(r#"{
+            "contractName": "PolyEndpoint",
+            "abi": [{ "type": "constructor", "inputs": [] }],
+            "bytecode": "0x6080604052",
+            "deployedBytecode": "0x60806040"
+        }"#).unwrap();
+        assert_eq!(hardhat.bytecode, [0x60, 0x80, 0x60, 0x40, 0x52]);
+        assert_eq!(hardhat.deployed_bytecode.as_deref(), Some("0x60806040"));
+        assert_eq!(hardhat.constructor_inputs(), 0);
+
+        let foundry = Artifact::parse(r#"{
+            "abi": [{ "type": "constructor", "inputs": [{ "name": "owner", "type": "address" }] }],
+            "bytecode": { "object": "0x6080604052", "sourceMap": "", "linkReferences": {} },
+            "deployedBytecode": { "object": "0x60806040", "sourceMap": "", "linkReferences": {} }
+        }"#).unwrap();
+        assert_eq!(foundry.bytecode, hardhat.bytecode);
+        assert_eq!(foundry.deployed_bytecode, hardhat.deployed_bytecode);
+        assert_eq!(foundry.constructor_inputs(), 1);
+
+        // No ABI and no runtime code are tolerated; missing or unusable bytecode is not
+        let minimal = Artifact::parse(r#"{ "bytecode": "6080" }"#).unwrap();
+        assert_eq!(minimal.deployed_bytecode, None);
+        assert_eq!(minimal.constructor_inputs(), 0);
+        assert!(Artifact::parse(r#"{ "abi": [] }"#).is_err());
+        assert!(Artifact::parse(r#"{ "bytecode": { "sourceMap": "" } }"#).is_err());
+        assert!(Artifact::parse(r#"{ "bytecode": { "object": "0x" } }"#).is_err());
+        assert!(Artifact::parse(r#"{ "bytecode": "0x60__$abc$__" }"#).is_err());
+        assert!(Artifact::parse("not json").is_err());
+    }
+
+    #[test]
+    fn test_abi_registry() {
+        let abi: Value = serde_json::from_str(r#"[
+            { "type": "constructor", "inputs": [] },
+            { "type": "event", "name": "AdminAdded", "inputs": [{ "name": "admin", "type": "address", "indexed": true }] },
+            { "type": "function", "name": "addEndpoint", "stateMutability": "nonpayable",
+              "inputs": [{ "name": "url", "type": "string" }, { "name": "description", "type": "string" }], "outputs": [] },
+            { "type": "function", "name": "addEndpoint", "stateMutability": "nonpayable",
+              "inputs": [{ "name": "url", "type": "string" }], "outputs": [] },
+            { "type": "function", "name": "getEndpointCount", "stateMutability": "view", "inputs": [],
+              "outputs": [{ "name": "", "type": "uint256" }] },
+            { "type": "function", "name": "legacy", "constant": true, "inputs": [] },
+            { "type": "function", "name": "setPairs", "stateMutability": "nonpayable",
+              "inputs": [{ "name": "pairs", "type": "tuple[]", "components": [{ "type": "address" }, { "type": "uint256" }] }] }
+        ]"#).unwrap();
+        let registry = AbiRegistry::from_abi(&abi).unwrap();
+        assert_eq!(registry.methods.len(), 5);
+
+        assert_eq!(registry.method("addEndpoint", 1).unwrap().signature(), "addEndpoint(string)");
+        assert_eq!(registry.method("addEndpoint", 2).unwrap().signature(), "addEndpoint(string,string)");
+        assert_eq!(registry.method("addEndpoint(string, string)", 0).unwrap().inputs, ["string", "string"]);
+        assert!(registry.method("getEndpointCount", 0).unwrap().is_read_only());
+        assert!(registry.method("legacy", 0).unwrap().is_read_only());
+        assert!(!registry.method("addEndpoint", 1).unwrap().is_read_only());
+        assert_eq!(registry.method("setPairs", 1).unwrap().signature(), "setPairs((address,uint256)[])");
+
+        let err = registry.method("addEndpoint", 3).unwrap_err().to_string();
+        assert!(err.contains("addEndpoint(string,string), addEndpoint(string)"), "{}", err);
+        assert!(registry.method("removeEndpoint", 1).is_err());
+        assert!(registry.method("addEndpoint(bytes32)", 1).is_err());
+        assert!(AbiRegistry::from_abi(&Value::Null).is_err());
+    }
+}
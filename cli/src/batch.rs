@@ -0,0 +1,119 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+
+/// One endpoint read from a batch file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointEntry {
+    /// Line number (text files) or 1-based array position (JSON files)
+    pub line: usize,
+    pub url: String,
+    pub description: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonEntry {
+    Url(String),
+    Full {
+        url: String,
+        #[serde(default)]
+        description: String,
+    },
+}
+
+/// Parse a batch file.
+///
+/// A file starting with `[` is read as a JSON array of URL strings or
+/// `{"url": ..., "description": ...}` objects. Anything else is read as one
+/// endpoint per line, optionally followed by whitespace and a description.
+/// Blank lines and lines starting with `#` are skipped.
+pub fn parse_endpoints(contents: &str) -> Result<Vec<EndpointEntry>> {
+    if contents.trim_start().starts_with('[') {
+        let items: Vec<JsonEntry> = serde_json::from_str(contents)
+            .context("Failed to parse JSON endpoint list")?;
+        return Ok(items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let (url, description) = match item {
+                    JsonEntry::Url(url) => (url, String::new()),
+                    JsonEntry::Full { url, description } => (url, description),
+                };
+                EndpointEntry { line: i + 1, url, description }
+            })
+            .collect());
+    }
+
+    Ok(contents
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line_trimmed = line.trim();
+            if line_trimmed.is_empty() || line_trimmed.starts_with('#') {
+                return None;
+            }
+            let (url, description) = match line_trimmed.split_once(char::is_whitespace) {
+                Some((url, description)) => (url, description.trim()),
+                None => (line_trimmed, ""),
+            };
+            Some(EndpointEntry {
+                line: i + 1,
+                url: url.to_string(),
+                description: description.to_string(),
+            })
+        })
+        .collect())
+}
+
+pub fn read_endpoints_file(path: &Path) -> Result<Vec<EndpointEntry>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse_endpoints(&contents)
+}
+
+/// Write `(url, description)` pairs to `path` as a JSON array of
+/// `{"url": ..., "description": ...}` objects, the format [`parse_endpoints`] reads
+pub fn write_endpoints_file(path: &Path, endpoints: &[(String, String)]) -> Result<()> {
+    let items: Vec<_> = endpoints
+        .iter()
+        .map(|(url, description)| serde_json::json!({ "url": url, "description": description }))
+        .collect();
+    let json = serde_json::to_string_pretty(&items).context("Failed to serialize endpoints")?;
+    std::fs::write(path, json + "\n").with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_text_and_json() {
+        let text = "# seed list\nhttps://a.example.com\n\nhttps://b.example.com  Primary RPC\n";
+        let entries = parse_endpoints(text).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0], EndpointEntry { line: 2, url: "https://a.example.com".into(), description: "".into() });
+        assert_eq!(entries[1].line, 4);
+        assert_eq!(entries[1].description, "Primary RPC");
+
+        let json = r#"["https://a.example.com", {"url": "https://b.example.com", "description": "Primary RPC"}]"#;
+        let entries = parse_endpoints(json).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1], EndpointEntry { line: 2, url: "https://b.example.com".into(), description: "Primary RPC".into() });
+    }
+
+    #[test]
+    fn test_write_endpoints_round_trip() {
+        let path = std::env::temp_dir().join(format!("polyportal-export-{}.json", std::process::id()));
+        let endpoints = vec![
+            ("https://a.example.com".to_string(), String::new()),
+            ("https://b.example.com".to_string(), "Primary \"RPC\"".to_string()),
+        ];
+        write_endpoints_file(&path, &endpoints).unwrap();
+
+        let entries = read_endpoints_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let read: Vec<_> = entries.into_iter().map(|e| (e.url, e.description)).collect();
+        assert_eq!(read, endpoints);
+    }
+}
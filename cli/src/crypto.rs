@@ -5,33 +5,98 @@ use aes_gcm::{
 };
 use sha2::{Sha256, Digest};
 use rand::Rng;
+use std::fmt;
 
 const SALT_SIZE: usize = 16;
 const NONCE_SIZE: usize = 12;
+/// A secp256k1 private key
+const KEY_SIZE: usize = 32;
+/// AES-GCM authentication tag appended to the ciphertext
+const TAG_SIZE: usize = 16;
 
-pub fn encrypt_private_key(key: &str, password: &str) -> Result<String> {
+/// Starts a tagged blob: `MAGIC`, a KDF identifier and its parameters, then
+/// salt, nonce and ciphertext. Untagged blobs are the original
+/// salt ++ nonce ++ ciphertext, encrypted with [`Kdf::Sha256`].
+const MAGIC: &[u8; 3] = b"PPK";
+
+/// KDF identifier for [`Kdf::Pbkdf2`], followed by the iteration count as a
+/// big-endian `u32`
+const KDF_PBKDF2: u8 = 1;
+
+/// PBKDF2 iterations used unless another count is chosen
+pub const PBKDF2_DEFAULT_ITERATIONS: u32 = 262_144;
+
+/// How the encryption key is derived from the password
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kdf {
+    /// One SHA-256 of password ++ salt; stored untagged so older versions can read it
+    Sha256,
+    /// PBKDF2-HMAC-SHA256, as used by many other keystores
+    Pbkdf2 { iterations: u32 },
+}
+
+impl Kdf {
+    fn derive_key(&self, password: &str, salt: &[u8]) -> [u8; 32] {
+        match *self {
+            Kdf::Sha256 => {
+                let mut hasher = Sha256::new();
+                hasher.update(password.as_bytes());
+                hasher.update(salt);
+                hasher.finalize().into()
+            }
+            Kdf::Pbkdf2 { iterations } => {
+                pbkdf2::pbkdf2_hmac_array::<Sha256, 32>(password.as_bytes(), salt, iterations)
+            }
+        }
+    }
+
+    /// The tag written before the salt
+    fn header(&self) -> Vec<u8> {
+        match *self {
+            Kdf::Sha256 => Vec::new(),
+            Kdf::Pbkdf2 { iterations } => {
+                let mut header = MAGIC.to_vec();
+                header.push(KDF_PBKDF2);
+                header.extend_from_slice(&iterations.to_be_bytes());
+                header
+            }
+        }
+    }
+}
+
+/// Encrypt a hex private key (with or without `0x`); see [`encrypt_key_bytes`]
+pub fn encrypt_private_key(key: &str, password: &str, kdf: Kdf) -> Result<String> {
+    let private_key_bytes = hex::decode(key.strip_prefix("0x").unwrap_or(key))
+        .context("Failed to decode private key")?;
+    encrypt_key_bytes(&private_key_bytes, password, kdf)
+}
+
+/// Encrypt a raw 32-byte private key with a key derived from `password` by `kdf`
+pub fn encrypt_key_bytes(key: &[u8], password: &str, kdf: Kdf) -> Result<String> {
+    if key.len() != KEY_SIZE {
+        anyhow::bail!("Private key must be {} bytes, got {}", KEY_SIZE, key.len());
+    }
+    if kdf == (Kdf::Pbkdf2 { iterations: 0 }) {
+        anyhow::bail!("PBKDF2 needs at least one iteration");
+    }
+    
     // Generate random salt
     let mut salt = [0u8; SALT_SIZE];
     rand::thread_rng().fill(&mut salt[..]);
     
     // Derive encryption key from password + salt
-    let mut hasher = Sha256::new();
-    hasher.update(password.as_bytes());
-    hasher.update(salt);
-    let encryption_key = hasher.finalize();
+    let encryption_key = kdf.derive_key(password, &salt);
     
     // Encrypt the private key
     let cipher = Aes256Gcm::new_from_slice(&encryption_key)?;
     let nonce = Aes256Gcm::generate_nonce(&mut rand::thread_rng());
     
-    let private_key_bytes = hex::decode(key.strip_prefix("0x").unwrap_or(key))
-        .context("Failed to decode private key")?;
-    
-    let ciphertext = cipher.encrypt(&nonce, private_key_bytes.as_ref())
+    let ciphertext = cipher.encrypt(&nonce, key)
         .map_err(|e| anyhow::anyhow!("Encryption failed: {:?}", e))?;
     
-    // Combine: salt (16) + nonce (12) + ciphertext
-    let mut combined = salt.to_vec();
+    // Combine: header + salt (16) + nonce (12) + ciphertext
+    let mut combined = kdf.header();
+    combined.extend_from_slice(&salt);
     #[allow(deprecated)]
     combined.extend_from_slice(nonce.as_slice());
     combined.extend_from_slice(&ciphertext);
@@ -39,12 +104,63 @@ pub fn encrypt_private_key(key: &str, password: &str) -> Result<String> {
     Ok(hex::encode(&combined))
 }
 
-pub fn decrypt_private_key(encrypted: &str, password: &str) -> Result<String> {
+/// Why [`decrypt_private_key`] failed
+#[derive(Debug, PartialEq, Eq)]
+pub enum DecryptError {
+    /// The data isn't hex, has the wrong length or names an unknown KDF,
+    /// so it was damaged or edited; no password will open it
+    InvalidFormat(String),
+    /// The data is well-formed but failed authentication, which almost
+    /// always means a wrong password
+    WrongPassword,
+}
+
+impl fmt::Display for DecryptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecryptError::InvalidFormat(reason) => write!(f, "Invalid encrypted data format: {}", reason),
+            DecryptError::WrongPassword => f.write_str("Decryption failed - wrong password?"),
+        }
+    }
+}
+
+impl std::error::Error for DecryptError {}
+
+/// The KDF a tagged blob names, and the rest of the blob after the tag
+fn parse_header(data: &[u8]) -> Result<Option<(Kdf, &[u8])>, DecryptError> {
+    let Some(rest) = data.strip_prefix(MAGIC) else {
+        return Ok(None);
+    };
+    match rest {
+        [KDF_PBKDF2, a, b, c, d, body @ ..] => {
+            Ok(Some((Kdf::Pbkdf2 { iterations: u32::from_be_bytes([*a, *b, *c, *d]) }, body)))
+        }
+        [id, ..] => Err(DecryptError::InvalidFormat(format!("unsupported key derivation function {}", id))),
+        [] => Err(DecryptError::InvalidFormat("truncated header".to_string())),
+    }
+}
+
+pub fn decrypt_private_key(encrypted: &str, password: &str) -> Result<String, DecryptError> {
     // Decode the encrypted data
-    let data = hex::decode(encrypted)?;
+    let data = hex::decode(encrypted.trim())
+        .map_err(|e| DecryptError::InvalidFormat(format!("not hex: {}", e)))?;
+    
+    let plaintext = match parse_header(&data) {
+        // A random untagged salt can start with MAGIC too, so fall back to reading it untagged
+        Ok(Some((kdf, body))) => decrypt_with(kdf, body, password)
+            .or_else(|e| decrypt_with(Kdf::Sha256, &data, password).map_err(|_| e))?,
+        Ok(None) => decrypt_with(Kdf::Sha256, &data, password)?,
+        Err(e) => decrypt_with(Kdf::Sha256, &data, password).map_err(|_| e)?,
+    };
     
-    if data.len() < SALT_SIZE + NONCE_SIZE {
-        anyhow::bail!("Invalid encrypted data format");
+    Ok(format!("0x{}", hex::encode(plaintext)))
+}
+
+/// Decrypt salt ++ nonce ++ ciphertext with a key derived by `kdf`
+fn decrypt_with(kdf: Kdf, data: &[u8], password: &str) -> Result<Vec<u8>, DecryptError> {
+    let expected = SALT_SIZE + NONCE_SIZE + KEY_SIZE + TAG_SIZE;
+    if data.len() != expected {
+        return Err(DecryptError::InvalidFormat(format!("expected {} bytes, got {}", expected, data.len())));
     }
     
     // Extract components
@@ -53,32 +169,28 @@ pub fn decrypt_private_key(encrypted: &str, password: &str) -> Result<String> {
     let ciphertext = &data[SALT_SIZE + NONCE_SIZE..];
     
     // Derive the same encryption key from password + salt
-    let mut hasher = Sha256::new();
-    hasher.update(password.as_bytes());
-    hasher.update(salt);
-    let encryption_key = hasher.finalize();
+    let encryption_key = kdf.derive_key(password, salt);
     
     // Decrypt
-    let cipher = Aes256Gcm::new_from_slice(&encryption_key)?;
+    let cipher = Aes256Gcm::new_from_slice(&encryption_key)
+        .expect("derived keys are 32 bytes");
     #[allow(deprecated)]
     let nonce = Nonce::from_slice(nonce_bytes);
     
-    let plaintext = cipher.decrypt(nonce, ciphertext)
-        .map_err(|_| anyhow::anyhow!("Decryption failed - wrong password?"))?;
-    
-    Ok(format!("0x{}", hex::encode(plaintext)))
+    cipher.decrypt(nonce, ciphertext)
+        .map_err(|_| DecryptError::WrongPassword)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+
     #[test]
     fn test_encrypt_decrypt() {
         let key = "0x0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
         let password = "test_password123";
         
-        let encrypted = encrypt_private_key(key, password).unwrap();
+        let encrypted = encrypt_private_key(key, password, Kdf::Sha256).unwrap();
         assert_ne!(encrypted, key);
         
         let decrypted = decrypt_private_key(&encrypted, password).unwrap();
@@ -87,4 +199,56 @@ mod tests {
         // Test wrong password
         assert!(decrypt_private_key(&encrypted, "wrong_password").is_err());
     }
+
+    #[test]
+    fn test_encrypt_key_bytes() {
+        let key = [0xab; KEY_SIZE];
+        let encrypted = encrypt_key_bytes(&key, "test_password123", Kdf::Pbkdf2 { iterations: 1000 }).unwrap();
+        assert_eq!(decrypt_private_key(&encrypted, "test_password123").unwrap(), format!("0x{}", hex::encode(key)));
+
+        // Anything but a 32-byte key would be unreadable later
+        assert!(encrypt_key_bytes(&key[..31], "test_password123", Kdf::Sha256).is_err());
+        assert!(encrypt_private_key("0x0123", "test_password123", Kdf::Sha256).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_errors() {
+        let key = "0x0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+        let encrypted = encrypt_private_key(key, "test_password123", Kdf::Sha256).unwrap();
+        assert_eq!(decrypt_private_key(&encrypted, "wrong_password"), Err(DecryptError::WrongPassword));
+
+        // Damage is reported as such, whatever the password
+        let format_error = |encrypted: &str| {
+            matches!(decrypt_private_key(encrypted, "test_password123"), Err(DecryptError::InvalidFormat(_)))
+        };
+        assert!(format_error(&encrypted[..encrypted.len() - 2]));
+        assert!(format_error(&format!("{}00", encrypted)));
+        assert!(format_error(&encrypted.replacen(&encrypted[..2], "zz", 1)));
+        assert!(format_error(""));
+        assert!(format_error(&format!("{}ff", hex::encode(MAGIC))));
+
+        // A flipped ciphertext bit fails authentication like a wrong password
+        let mut data = hex::decode(&encrypted).unwrap();
+        *data.last_mut().unwrap() ^= 1;
+        assert_eq!(decrypt_private_key(&hex::encode(data), "test_password123"), Err(DecryptError::WrongPassword));
+    }
+
+    #[test]
+    fn test_pbkdf2() {
+        // RFC 7914 section 11 PBKDF2-HMAC-SHA256 vector, truncated to 32 bytes
+        assert_eq!(
+            hex::encode(Kdf::Pbkdf2 { iterations: 1 }.derive_key("passwd", b"salt")),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"
+        );
+        
+        let key = "0x0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+        let encrypted = encrypt_private_key(key, "test_password123", Kdf::Pbkdf2 { iterations: 1000 }).unwrap();
+        let data = hex::decode(&encrypted).unwrap();
+        assert_eq!(&data[..4], b"PPK\x01");
+        assert_eq!(u32::from_be_bytes(data[4..8].try_into().unwrap()), 1000);
+        
+        assert_eq!(decrypt_private_key(&encrypted, "test_password123").unwrap(), key);
+        assert!(decrypt_private_key(&encrypted, "wrong_password").is_err());
+        assert!(encrypt_private_key(key, "test_password123", Kdf::Pbkdf2 { iterations: 0 }).is_err());
+    }
 }
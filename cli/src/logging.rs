@@ -0,0 +1,259 @@
+//! Diagnostics on stderr via `tracing`.
+//!
+//! A small subscriber instead of `tracing-subscriber`: events are printed as
+//! one line each, prefixed with the spans they happened in, and spans print
+//! how long they took when they close. Filtering understands the usual
+//! `RUST_LOG` forms (`debug`, `polyendpoint_sdk=trace,warn`).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Write as _};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Crates whose events `--verbose` turns on
+const OWN_TARGETS: [&str; 2] = ["polyportal_cli", "polyendpoint_sdk"];
+
+/// Where ethers logs each request's params and raw result at trace level
+const ETHERS_RPC_TARGET: &str = "ethers_providers::rpc::provider";
+
+/// Install the stderr subscriber.
+///
+/// `RUST_LOG` wins if set. Otherwise `-v` shows debug events from this CLI
+/// and the SDK, `-vv` trace events, and by default only warnings are shown.
+/// `trace_rpc` (`--trace`) adds ethers' request and response events either way.
+pub fn init(verbose: u8, trace_rpc: bool) {
+    let mut filter = match std::env::var("RUST_LOG") {
+        Ok(spec) if !spec.trim().is_empty() => Filter::parse(&spec),
+        _ => Filter::verbose(verbose),
+    };
+    if trace_rpc {
+        filter.targets.push((ETHERS_RPC_TARGET.to_string(), LevelFilter::TRACE));
+    }
+    // Only fails if a subscriber is already installed
+    let _ = tracing::subscriber::set_global_default(StderrSubscriber::new(filter));
+}
+
+/// Per-target level directives; the longest matching target prefix wins
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    default: LevelFilter,
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    /// Parse a comma-separated list of `level` and `target=level` directives.
+    /// Unparseable directives are ignored.
+    pub fn parse(spec: &str) -> Self {
+        let mut filter = Filter { default: LevelFilter::OFF, targets: Vec::new() };
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    if let Ok(level) = level.trim().parse() {
+                        filter.targets.push((target.trim().to_string(), level));
+                    }
+                }
+                None => match directive.parse() {
+                    Ok(level) => filter.default = level,
+                    // A bare target enables everything from it
+                    Err(_) => filter.targets.push((directive.to_string(), LevelFilter::TRACE)),
+                },
+            }
+        }
+        filter
+    }
+
+    fn verbose(verbose: u8) -> Self {
+        let level = match verbose {
+            0 => return Filter { default: LevelFilter::WARN, targets: Vec::new() },
+            1 => LevelFilter::DEBUG,
+            _ => LevelFilter::TRACE,
+        };
+        Filter {
+            default: LevelFilter::WARN,
+            targets: OWN_TARGETS.iter().map(|t| (t.to_string(), level)).collect(),
+        }
+    }
+
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .filter(|(prefix, _)| {
+                target == prefix || target.strip_prefix(prefix.as_str()).is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() <= self.level_for(metadata.target())
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.targets.iter().map(|(_, level)| *level).fold(self.default, LevelFilter::max)
+    }
+}
+
+struct SpanData {
+    metadata: &'static Metadata<'static>,
+    fields: String,
+    started: Instant,
+    refs: usize,
+}
+
+thread_local! {
+    /// Spans entered on this thread, innermost last
+    static CURRENT: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+}
+
+struct StderrSubscriber {
+    filter: Filter,
+    next_id: AtomicU64,
+    spans: Mutex<HashMap<u64, SpanData>>,
+}
+
+impl StderrSubscriber {
+    fn new(filter: Filter) -> Self {
+        Self { filter, next_id: AtomicU64::new(1), spans: Mutex::new(HashMap::new()) }
+    }
+
+    /// `outer{a=1}:inner{b=2}` for the spans entered on this thread
+    fn span_prefix(&self) -> String {
+        let spans = self.spans.lock().unwrap();
+        CURRENT.with(|current| {
+            let mut prefix = String::new();
+            for span in current.borrow().iter().filter_map(|id| spans.get(id)) {
+                let _ = write!(prefix, "{}{{{}}}:", span.metadata.name(), span.fields.trim_start());
+            }
+            prefix
+        })
+    }
+}
+
+/// Writes fields as ` name=value`, and the message bare
+struct FieldWriter<'a>(&'a mut String);
+
+impl Visit for FieldWriter<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            let _ = write!(self.0, " {}", value);
+        } else {
+            let _ = write!(self.0, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, " {:?}", value);
+        } else {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+impl Subscriber for StderrSubscriber {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.filter.enabled(metadata)
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(self.filter.max_level())
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut fields = String::new();
+        attrs.record(&mut FieldWriter(&mut fields));
+        self.spans.lock().unwrap().insert(
+            id,
+            SpanData { metadata: attrs.metadata(), fields, started: Instant::now(), refs: 1 },
+        );
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        if let Some(span) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+            values.record(&mut FieldWriter(&mut span.fields));
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut line = String::new();
+        event.record(&mut FieldWriter(&mut line));
+        let metadata = event.metadata();
+        let text = format!("{}{}", self.span_prefix(), line);
+        eprintln!("{:>5} {}: {}", metadata.level(), metadata.target(), text.trim_start());
+    }
+
+    fn enter(&self, span: &Id) {
+        CURRENT.with(|current| current.borrow_mut().push(span.into_u64()));
+    }
+
+    fn exit(&self, span: &Id) {
+        CURRENT.with(|current| {
+            let mut current = current.borrow_mut();
+            if let Some(pos) = current.iter().rposition(|id| *id == span.into_u64()) {
+                current.remove(pos);
+            }
+        });
+    }
+
+    fn clone_span(&self, span: &Id) -> Id {
+        if let Some(span) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+            span.refs += 1;
+        }
+        span.clone()
+    }
+
+    fn try_close(&self, span: Id) -> bool {
+        let mut spans = self.spans.lock().unwrap();
+        let Some(data) = spans.get_mut(&span.into_u64()) else {
+            return false;
+        };
+        data.refs -= 1;
+        if data.refs > 0 {
+            return false;
+        }
+
+        let data = spans.remove(&span.into_u64()).expect("span was just found");
+        drop(spans);
+        eprintln!(
+            "{:>5} {}: {}{{{}}}: closed after {} ms",
+            data.metadata.level(),
+            data.metadata.target(),
+            data.metadata.name(),
+            data.fields.trim_start(),
+            data.started.elapsed().as_millis()
+        );
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter() {
+        let filter = Filter::parse("warn,polyendpoint_sdk=trace,polyendpoint_sdk::ens=off,ethers");
+        assert_eq!(filter.level_for("polyportal_cli"), LevelFilter::WARN);
+        assert_eq!(filter.level_for("polyendpoint_sdk::simple_client"), LevelFilter::TRACE);
+        assert_eq!(filter.level_for("polyendpoint_sdk::ens"), LevelFilter::OFF);
+        assert_eq!(filter.level_for("polyendpoint_sdk_extra"), LevelFilter::WARN);
+        assert_eq!(filter.level_for("ethers::providers"), LevelFilter::TRACE);
+        assert_eq!(filter.max_level(), LevelFilter::TRACE);
+
+        assert_eq!(Filter::parse("debug"), Filter { default: LevelFilter::DEBUG, targets: Vec::new() });
+        assert_eq!(Filter::verbose(0).max_level(), LevelFilter::WARN);
+        assert_eq!(Filter::verbose(1).level_for("polyportal_cli::tx"), LevelFilter::DEBUG);
+        assert_eq!(Filter::verbose(1).level_for("hyper"), LevelFilter::WARN);
+    }
+}
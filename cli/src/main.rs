@@ -1,145 +1,665 @@
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use ethers::{
     middleware::SignerMiddleware,
-    providers::{Http, Provider},
-    signers::{LocalWallet, Signer},
-    types::{Address, Bytes, TransactionRequest},
-    utils::hex,
+    providers::{Http, Provider, RpcError},
+    signers::{coins_bip39::English, LocalWallet, MnemonicBuilder, Signer},
+    types::{Address, Bytes, Signature, TransactionReceipt, TransactionRequest, H256, U256},
+    utils::{format_ether, get_create2_address, hex, to_checksum},
 };
 use ethers_middleware::Middleware;
-use std::io::{self, Write};
+use serde_json::json;
+use std::io;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
 use rpassword::prompt_password;
+use tracing::Instrument;
 
+#[cfg(all(feature = "rustls", feature = "native-tls"))]
+compile_error!("features `rustls` and `native-tls` are mutually exclusive; use --no-default-features --features native-tls");
+
+mod artifact;
+mod batch;
 mod config;
 mod crypto;
+mod logging;
+#[cfg(any(test, feature = "test-utils"))]
+mod mock;
+mod output;
+mod signer;
+mod tx;
+mod watch;
+
+use polyendpoint_sdk::contract::{
+    abi::AbiToken, decode_address, decode_bool, decode_endpoint, decode_revert_reason,
+    encode_add_admin, encode_add_endpoint_with_description, encode_call, encode_get_endpoint_count,
+    encode_has_endpoint, encode_is_admin, encode_owner, encode_remove_admin, encode_remove_endpoint,
+    encode_transfer_ownership, method_id,
+};
+use polyendpoint_sdk::networks::{Network, NetworkRegistry};
+use polyendpoint_sdk::{selector, EndpointInfo, PolyEndpointClient, TxOutcome};
 
-use config::{Config, WalletsFile, DeployerConfig, NetworkConfig, ContractConfig};
-use crypto::{encrypt_private_key, decrypt_private_key};
+use artifact::{AbiRegistry, Artifact};
+use config::{Config, WalletEntry, WalletsFile, DeployerConfig, NetworkConfig, ContractConfig};
+use crypto::{encrypt_private_key, decrypt_private_key, DecryptError, Kdf};
+use output::Output;
+use signer::TxSigner;
+use tx::{
+    build_transaction, decode_raw, dry_run, estimate_cost, gas_limit, outcome_json, set_nonce, sign_raw, simulate,
+    tx_outcome, wait_for_receipt, OfflineTxArgs, TxArgs,
+};
 
 #[derive(Parser)]
 #[command(name = "polyportal-cli")]
 #[command(about = "A CLI tool for deploying and interacting with PolyPortal contract")]
 struct Cli {
+    /// Emit a single JSON object on stdout instead of human-readable output
+    #[arg(long, global = true)]
+    json: bool,
+    /// Log diagnostics to stderr (-v for debug, -vv for trace); RUST_LOG overrides
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// Use this network (e.g. base-sepolia) instead of the one in config.toml, for this invocation only
+    #[arg(long, global = true, value_name = "NAME")]
+    chain: Option<String>,
+    /// Also write the command's result as JSON to this file, replacing it
+    #[arg(long, global = true, value_name = "PATH")]
+    output: Option<PathBuf>,
+    /// Print every JSON-RPC request and raw response to stderr, for debugging
+    #[arg(long, global = true)]
+    trace: bool,
+    #[command(flatten)]
+    signer: SignerArgs,
     #[command(subcommand)]
     command: Commands,
 }
 
+/// Options that let signing commands run without interactive prompts.
+///
+/// The password is taken from `--password-file` first, then the
+/// `POLYPORTAL_PASSWORD` environment variable, and only prompted for
+/// interactively when neither is set.
+#[derive(Args, Debug, Clone, Default)]
+struct SignerArgs {
+    /// Name of the wallet to sign with (skips the selection menu)
+    #[arg(long, global = true)]
+    wallet: Option<String>,
+    /// Read the wallet password from this file
+    #[arg(long, global = true)]
+    password_file: Option<PathBuf>,
+    /// Use the wallet even if it is restricted to other networks
+    #[arg(long, global = true)]
+    allow_network: bool,
+    /// Sign with a Ledger device instead of a saved wallet (needs the `ledger` feature)
+    #[arg(long, global = true)]
+    ledger: bool,
+    /// Derivation path of the Ledger account to sign with, or of the account
+    /// `import-mnemonic` derives [default: m/44'/60'/0'/0/0]
+    #[arg(long, global = true, value_name = "PATH")]
+    hd_path: Option<String>,
+}
+
+const PASSWORD_ENV: &str = "POLYPORTAL_PASSWORD";
+
+/// How `init`, `import-key` and `add-wallet` encrypt the key they save.
+/// Existing keys are read whichever way they were saved.
+#[derive(Args, Debug, Clone)]
+struct KdfArgs {
+    /// Key derivation for the wallet password
+    #[arg(long, value_enum, default_value_t = KdfKind::Sha256)]
+    kdf: KdfKind,
+    /// PBKDF2 iteration count, stored with the key
+    #[arg(long, value_name = "N", default_value_t = crypto::PBKDF2_DEFAULT_ITERATIONS, value_parser = clap::value_parser!(u32).range(1..))]
+    kdf_iterations: u32,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum KdfKind {
+    /// A single SHA-256 of password and salt, readable by every version
+    Sha256,
+    /// PBKDF2-HMAC-SHA256, for tools that expect it
+    Pbkdf2,
+}
+
+impl KdfArgs {
+    fn kdf(&self) -> Kdf {
+        match self.kdf {
+            KdfKind::Sha256 => Kdf::Sha256,
+            KdfKind::Pbkdf2 => Kdf::Pbkdf2 { iterations: self.kdf_iterations },
+        }
+    }
+}
+
+/// The message for `sign-message` and `verify-message`
+#[derive(Args, Debug, Clone)]
+#[group(required = true, multiple = false)]
+struct MessageArgs {
+    /// Message text, signed as its UTF-8 bytes
+    #[arg(short, long)]
+    message: Option<String>,
+    /// Read the message from this file, byte for byte (for binary payloads)
+    #[arg(short, long)]
+    file: Option<PathBuf>,
+}
+
+impl MessageArgs {
+    fn bytes(&self) -> Result<Vec<u8>> {
+        match (&self.message, &self.file) {
+            (Some(message), _) => Ok(message.as_bytes().to_vec()),
+            (None, Some(path)) => std::fs::read(path)
+                .with_context(|| format!("Failed to read message file {}", path.display())),
+            (None, None) => anyhow::bail!("Pass --message or --file"),
+        }
+    }
+}
+
+/// Network selected with `--chain`, replacing the one in config.toml
+static CHAIN_OVERRIDE: OnceLock<NetworkConfig> = OnceLock::new();
+
+/// Set by `--trace`: print JSON-RPC traffic that doesn't go through ethers
+static TRACE_RPC: AtomicBool = AtomicBool::new(false);
+
+/// Factory used by `deploy --create2` unless `--factory` is given: the
+/// deterministic deployment proxy, which has the same address on most chains
+/// and takes `salt ++ init code` as calldata
+const DEFAULT_CREATE2_FACTORY: &str = "0x4e59b44847b379578588920cA78FbF26c0B4956C";
+
 #[derive(Subcommand)]
 enum Commands {
     /// Initialize CLI with network configuration and private key
-    Init,
+    Init {
+        #[command(flatten)]
+        kdf: KdfArgs,
+    },
     /// Deploy the PolyPortal contract
-    Deploy,
+    Deploy {
+        /// Deploy through a CREATE2 factory so the address only depends on the salt and bytecode
+        #[arg(long, requires = "salt")]
+        create2: bool,
+        /// 32-byte hex salt for --create2
+        #[arg(long, value_name = "HEX", requires = "create2")]
+        salt: Option<String>,
+        /// CREATE2 factory address [default: the deterministic deployment proxy, 0x4e59b448...]
+        #[arg(long, value_name = "ADDRESS", requires = "create2")]
+        factory: Option<String>,
+        #[command(flatten)]
+        tx: TxArgs,
+    },
     /// Import and encrypt a private key
-    ImportKey,
+    ImportKey {
+        #[command(flatten)]
+        kdf: KdfArgs,
+    },
+    /// Import one account of a BIP-39 mnemonic as a new wallet
+    ImportMnemonic {
+        #[arg(short, long)]
+        name: String,
+        /// Derive account N at m/44'/60'/0'/0/N [default: 0]; use --hd-path for other layouts
+        #[arg(long, value_name = "N")]
+        account_index: Option<u32>,
+        /// Only allow signing on this network (repeatable); any network if omitted
+        #[arg(long = "network", value_name = "NAME")]
+        networks: Vec<String>,
+        #[command(flatten)]
+        kdf: KdfArgs,
+    },
+    /// Show the address of a private key without saving anything
+    AddressFromKey,
     /// List all wallets
-    ListWallets,
+    ListWallets {
+        /// Also show each wallet's ETH balance on the configured network
+        #[arg(long)]
+        balances: bool,
+    },
+    /// Show the ETH balance of saved wallets on the configured network
+    Balance {
+        /// Only show the wallet with this name
+        #[arg(short, long)]
+        name: Option<String>,
+    },
+    /// Check that the RPC is reachable and on the expected chain
+    Ping {
+        /// Network to check, by name [default: the configured network]
+        #[arg(short, long)]
+        network: Option<String>,
+    },
+    /// Show current gas fees (base fee, suggested priority fee, max fee) in gwei
+    GasPrice {
+        /// Network to query, by name [default: the configured network]
+        #[arg(short, long)]
+        network: Option<String>,
+    },
+    /// Inspect the configuration commands run with
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
     /// Add a new wallet
     AddWallet {
         #[arg(short, long)]
         name: String,
+        /// Only allow signing on this network (repeatable); any network if omitted
+        #[arg(long = "network", value_name = "NAME")]
+        networks: Vec<String>,
+        #[command(flatten)]
+        kdf: KdfArgs,
     },
     /// Add an endpoint with description
     AddEndpoint {
         #[arg(short, long)]
         url: String,
+        /// Contract address; defaults to the one `deploy` saved in config.toml
         #[arg(short, long)]
-        contract: String,
+        contract: Option<String>,
         #[arg(short, long, default_value = "")]
         description: String,
+        /// Send even if `hasEndpoint` says the URL is already registered
+        #[arg(long)]
+        force: bool,
+        #[command(flatten)]
+        tx: TxArgs,
     },
     /// Remove an endpoint
     RemoveEndpoint {
         #[arg(short, long)]
         url: String,
+        /// Contract address; defaults to the one `deploy` saved in config.toml
+        #[arg(short, long)]
+        contract: Option<String>,
+        #[command(flatten)]
+        tx: TxArgs,
+    },
+    /// Add every endpoint listed in a file (one per line, or a JSON array)
+    AddEndpointsFile {
+        #[arg(short, long)]
+        file: PathBuf,
+        /// Contract address; defaults to the one `deploy` saved in config.toml
+        #[arg(short, long)]
+        contract: Option<String>,
+        /// Send entries that `hasEndpoint` says are already registered
+        #[arg(long)]
+        force: bool,
+        #[command(flatten)]
+        tx: TxArgs,
+    },
+    /// Remove every endpoint listed in a file (one per line, or a JSON array)
+    RemoveEndpointsFile {
+        #[arg(short, long)]
+        file: PathBuf,
+        /// Contract address; defaults to the one `deploy` saved in config.toml
+        #[arg(short, long)]
+        contract: Option<String>,
+        #[command(flatten)]
+        tx: TxArgs,
+    },
+    /// Save every endpoint of a contract to a JSON file
+    ExportEndpoints {
+        /// Contract address; defaults to the one `deploy` saved in config.toml
+        #[arg(short, long)]
+        contract: Option<String>,
+        /// File to write the `[{url, description}]` array to
+        #[arg(short, long, value_name = "FILE")]
+        out: PathBuf,
+    },
+    /// Add the endpoints from an export-endpoints file to a contract
+    ImportEndpoints {
         #[arg(short, long)]
-        contract: String,
+        file: PathBuf,
+        /// Contract address; defaults to the one `deploy` saved in config.toml
+        #[arg(short, long)]
+        contract: Option<String>,
+        /// Send entries that `hasEndpoint` says are already registered
+        #[arg(long)]
+        force: bool,
+        #[command(flatten)]
+        tx: TxArgs,
     },
     /// Add an admin
     AddAdmin {
+        /// Admin address or ENS name (e.g. alice.eth)
         #[arg(short, long)]
         admin: String,
+        /// Contract address; defaults to the one `deploy` saved in config.toml
         #[arg(short, long)]
-        contract: String,
+        contract: Option<String>,
+        #[command(flatten)]
+        tx: TxArgs,
     },
     /// Remove an admin
     RemoveAdmin {
+        /// Admin address or ENS name (e.g. alice.eth)
         #[arg(short, long)]
         admin: String,
+        /// Contract address; defaults to the one `deploy` saved in config.toml
+        #[arg(short, long)]
+        contract: Option<String>,
+        #[command(flatten)]
+        tx: TxArgs,
+    },
+    /// Transfer contract ownership to a new address
+    TransferOwnership {
+        /// New owner address or ENS name (e.g. alice.eth)
+        #[arg(short, long)]
+        new_owner: String,
+        /// Contract address; defaults to the one `deploy` saved in config.toml
         #[arg(short, long)]
-        contract: String,
+        contract: Option<String>,
+        #[command(flatten)]
+        tx: TxArgs,
     },
     /// Get all endpoints
     GetEndpoints {
+        /// Contract to read; repeat or separate with commas to read several.
+        /// Defaults to the one `deploy` saved in config.toml
+        #[arg(short, long, value_delimiter = ',')]
+        contract: Vec<String>,
+    },
+    /// Get one endpoint by its position in the list
+    GetEndpoint {
+        /// Contract address; defaults to the one `deploy` saved in config.toml
         #[arg(short, long)]
-        contract: String,
+        contract: Option<String>,
+        /// Zero-based index, in decimal (5) or 0x-prefixed hex (0x5)
+        #[arg(short, long, value_parser = parse_index)]
+        index: U256,
     },
     /// Get endpoint count
     GetCount {
+        /// Contract address; defaults to the one `deploy` saved in config.toml
         #[arg(short, long)]
-        contract: String,
+        contract: Option<String>,
     },
     /// Check if endpoint exists
     HasEndpoint {
         #[arg(short, long)]
         url: String,
+        /// Contract address; defaults to the one `deploy` saved in config.toml
+        #[arg(short, long)]
+        contract: Option<String>,
+    },
+    /// Poll the endpoint list and print endpoints as they are added or removed
+    Watch {
+        /// Contract address; defaults to the one `deploy` saved in config.toml
+        #[arg(short, long)]
+        contract: Option<String>,
+        /// Seconds between polls
+        #[arg(short, long, default_value_t = 30, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+    },
+    /// Show the contract owner
+    Owner {
+        /// Contract address; defaults to the one `deploy` saved in config.toml
+        #[arg(short, long)]
+        contract: Option<String>,
+    },
+    /// Check that an address has contract code on the configured network
+    CheckContract {
         #[arg(short, long)]
-        contract: String,
+        address: String,
     },
     /// Check if address is admin
     IsAdmin {
+        /// Contract address; defaults to the one `deploy` saved in config.toml
         #[arg(short, long)]
-        contract: String,
+        contract: Option<String>,
+        /// Address or ENS name (e.g. alice.eth)
         #[arg(short, long)]
         address: String,
     },
+    /// Send a transaction with raw calldata, for methods without a dedicated command
+    RawCall {
+        /// Contract address; defaults to the one `deploy` saved in config.toml
+        #[arg(short, long)]
+        contract: Option<String>,
+        /// Calldata as hex (selector followed by the ABI-encoded arguments)
+        #[arg(short, long)]
+        data: String,
+        #[command(flatten)]
+        tx: TxArgs,
+    },
+    /// Run eth_call with raw calldata and print the raw result hex
+    RawEthCall {
+        /// Contract address; defaults to the one `deploy` saved in config.toml
+        #[arg(short, long)]
+        contract: Option<String>,
+        /// Calldata as hex (selector followed by the ABI-encoded arguments)
+        #[arg(short, long)]
+        data: String,
+    },
+    /// Call a contract method by name, using the ABI in the configured artifact.
+    /// View and pure methods run as eth_call; others are sent as a transaction.
+    Call {
+        /// Contract address; defaults to the one `deploy` saved in config.toml
+        #[arg(short, long)]
+        contract: Option<String>,
+        /// Method name, or its full signature if it's overloaded, e.g. "addEndpoint(string)"
+        #[arg(short, long)]
+        method: String,
+        /// One argument per method parameter, in order
+        #[arg(long = "arg", allow_hyphen_values = true)]
+        args: Vec<String>,
+        #[command(flatten)]
+        tx: TxArgs,
+    },
+    /// Print the 4-byte selector of a function signature, or full calldata with --encode
+    Selector {
+        /// Function signature, e.g. "addEndpoint(string)"
+        #[arg(short, long)]
+        signature: String,
+        /// Encode the arguments that follow and print the whole calldata
+        #[arg(long)]
+        encode: bool,
+        /// Arguments for --encode, one per parameter in the signature
+        #[arg(requires = "encode", allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Sign a transaction without any network access and save the raw bytes for `broadcast`
+    SignTx {
+        #[command(flatten)]
+        tx: OfflineTxArgs,
+        /// File to write the signed transaction to, as hex
+        #[arg(short, long)]
+        out: PathBuf,
+    },
+    /// Send a transaction signed by `sign-tx` and wait for its receipt
+    Broadcast {
+        /// File written by `sign-tx`
+        #[arg(short, long)]
+        file: PathBuf,
+        /// Number of block confirmations to wait for before reporting success
+        #[arg(long, default_value_t = 1)]
+        confirmations: usize,
+    },
+    /// Sign a message with a saved wallet (EIP-191 personal_sign)
+    SignMessage {
+        /// Wallet to sign with (same as --wallet)
+        #[arg(short, long)]
+        name: Option<String>,
+        #[command(flatten)]
+        message: MessageArgs,
+    },
+    /// Recover the address that signed a message with personal_sign
+    VerifyMessage {
+        #[command(flatten)]
+        message: MessageArgs,
+        /// The 65-byte signature as hex
+        #[arg(short, long)]
+        signature: String,
+        /// Fail unless the signature was made by this address
+        #[arg(short, long)]
+        address: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print the resolved network, RPC, wallet and default contract, with
+    /// secrets redacted and where each value came from
+    Show,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let out = match cli.output.clone() {
+        Some(path) => Output::new(cli.json).with_file(path),
+        None => Output::new(cli.json),
+    };
+    logging::init(cli.verbose, cli.trace);
+    TRACE_RPC.store(cli.trace, Ordering::Relaxed);
+
+    let span = tracing::info_span!("command", name = matches.subcommand_name().unwrap_or_default());
+    let result = async {
+        if let Some(chain) = &cli.chain {
+            let network = resolve_network(chain)?;
+            tracing::debug!(network = %network.name, rpc_url = %network.rpc_url, "using --chain");
+            let _ = CHAIN_OVERRIDE.set(network);
+        }
+        run(cli.command, &out, &cli.signer).await
+    };
+    if let Err(e) = result.instrument(span).await {
+        tracing::debug!(error = %format!("{:#}", e), "command failed");
+        if out.is_json() {
+            match e.downcast_ref::<tx::Reverted>() {
+                // Still report what was mined, so the hash and gas used aren't lost
+                Some(tx::Reverted(outcome)) => {
+                    let mut result = outcome_json(outcome);
+                    result["error"] = json!(format!("{:#}", e));
+                    out.result(result);
+                }
+                None => out.error(&e),
+            }
+            std::process::exit(1);
+        }
+        return Err(e);
+    }
+
+    Ok(())
+}
 
-    match cli.command {
-        Commands::Init => {
-            init_cli().await?;
+async fn run(command: Commands, out: &Output, signer: &SignerArgs) -> Result<()> {
+    match command {
+        Commands::Init { kdf } => {
+            init_cli(out, kdf.kdf()).await?;
+        }
+        Commands::ImportKey { kdf } => {
+            import_key(out, kdf.kdf()).await?;
+        }
+        Commands::Ping { network } => {
+            ping(out, network.as_deref()).await?;
         }
-        Commands::ImportKey => {
-            import_key().await?;
+        Commands::GasPrice { network } => {
+            gas_price(out, network.as_deref()).await?;
         }
-        Commands::AddWallet { name } => {
-            add_wallet(&name).await?;
+        Commands::Config { command: ConfigCommand::Show } => {
+            show_config(out, signer)?;
         }
-        Commands::ListWallets => {
-            list_wallets().await?;
+        Commands::AddWallet { name, networks, kdf } => {
+            add_wallet(out, &name, networks, kdf.kdf()).await?;
         }
-        Commands::Deploy => {
-            deploy_contract().await?;
+        Commands::ImportMnemonic { name, account_index, networks, kdf } => {
+            let path = derivation_path(signer.hd_path.as_deref(), account_index)?;
+            import_mnemonic(out, &name, &path, networks, kdf.kdf())?;
         }
-        Commands::AddEndpoint { url, contract, description } => {
-            call_add_endpoint(contract, &url, &description).await?;
+        Commands::AddressFromKey => {
+            address_from_key(out)?;
         }
-        Commands::RemoveEndpoint { url, contract } => {
-            call_remove_endpoint(contract, url).await?;
+        Commands::ListWallets { balances } => {
+            list_wallets(out, balances).await?;
         }
-        Commands::AddAdmin { admin, contract } => {
-            call_add_admin(contract, admin).await?;
+        Commands::Balance { name } => {
+            show_balances(out, name.as_deref()).await?;
         }
-        Commands::RemoveAdmin { admin, contract } => {
-            call_remove_admin(contract, admin).await?;
+        Commands::Deploy { create2, salt, factory, tx } => {
+            let create2 = match (create2, salt) {
+                (true, Some(salt)) => Some(Create2::parse(&salt, factory.as_deref())?),
+                _ => None,
+            };
+            deploy_contract(out, signer, &tx, create2).await?;
+        }
+        Commands::AddEndpoint { url, contract, description, force, tx } => {
+            call_add_endpoint(out, signer, &tx, resolve_contract(contract)?, &url, &description, force).await?;
+        }
+        Commands::RemoveEndpoint { url, contract, tx } => {
+            call_remove_endpoint(out, signer, &tx, resolve_contract(contract)?, url).await?;
+        }
+        Commands::AddEndpointsFile { file, contract, force, tx } => {
+            call_endpoints_file(out, signer, &tx, resolve_contract(contract)?, &file, false, force).await?;
+        }
+        Commands::RemoveEndpointsFile { file, contract, tx } => {
+            call_endpoints_file(out, signer, &tx, resolve_contract(contract)?, &file, true, false).await?;
+        }
+        Commands::ExportEndpoints { contract, out: path } => {
+            export_endpoints(out, &resolve_contract(contract)?, &path).await?;
+        }
+        Commands::ImportEndpoints { file, contract, force, tx } => {
+            call_endpoints_file(out, signer, &tx, resolve_contract(contract)?, &file, false, force).await?;
+        }
+        Commands::AddAdmin { admin, contract, tx } => {
+            call_add_admin(out, signer, &tx, resolve_contract(contract)?, admin).await?;
+        }
+        Commands::RemoveAdmin { admin, contract, tx } => {
+            call_remove_admin(out, signer, &tx, resolve_contract(contract)?, admin).await?;
+        }
+        Commands::TransferOwnership { new_owner, contract, tx } => {
+            call_transfer_ownership(out, signer, &tx, resolve_contract(contract)?, new_owner).await?;
         }
         Commands::GetEndpoints { contract } => {
-            call_get_endpoints(contract).await?;
+            let contracts = match contract.is_empty() {
+                true => vec![resolve_contract(None)?],
+                false => contract,
+            };
+            call_get_endpoints(out, contracts).await?;
+        }
+        Commands::GetEndpoint { contract, index } => {
+            call_get_endpoint(out, resolve_contract(contract)?, index).await?;
         }
         Commands::GetCount { contract } => {
-            call_get_count(contract).await?;
+            call_get_count(out, resolve_contract(contract)?).await?;
         }
         Commands::HasEndpoint { url, contract } => {
-            call_has_endpoint(contract, url).await?;
+            call_has_endpoint(out, resolve_contract(contract)?, url).await?;
+        }
+        Commands::Watch { contract, interval } => {
+            watch_endpoints(out, &resolve_contract(contract)?, Duration::from_secs(interval)).await?;
+        }
+        Commands::Owner { contract } => {
+            call_owner(out, resolve_contract(contract)?).await?;
+        }
+        Commands::CheckContract { address } => {
+            check_contract(out, &address).await?;
         }
         Commands::IsAdmin { contract, address } => {
-            call_is_admin(contract, address).await?;
+            call_is_admin(out, resolve_contract(contract)?, address).await?;
+        }
+        Commands::RawCall { contract, data, tx } => {
+            call_raw(out, signer, &tx, resolve_contract(contract)?, &data).await?;
+        }
+        Commands::RawEthCall { contract, data } => {
+            call_raw_eth_call(out, resolve_contract(contract)?, &data).await?;
+        }
+        Commands::Call { contract, method, args, tx } => {
+            call_method(out, signer, &tx, resolve_contract(contract)?, &method, &args).await?;
+        }
+        Commands::SignTx { tx, out: path } => {
+            sign_tx(out, signer, &tx, &path).await?;
+        }
+        Commands::Broadcast { file, confirmations } => {
+            broadcast(out, &file, confirmations).await?;
+        }
+        Commands::SignMessage { name, message } => {
+            let signer = SignerArgs { wallet: name.or_else(|| signer.wallet.clone()), ..signer.clone() };
+            sign_message(out, &signer, &message.bytes()?).await?;
+        }
+        Commands::VerifyMessage { message, signature, address } => {
+            verify_message(out, &message.bytes()?, &signature, address.as_deref())?;
+        }
+        Commands::Selector { signature, encode, args } => {
+            print_selector(out, &signature, encode.then_some(args.as_slice()))?;
         }
     }
 
@@ -153,8 +673,8 @@ fn default_contract() -> ContractConfig {
     }
 }
 
-async fn add_wallet(name: &str) -> Result<()> {
-    println!("=== Add New Wallet ===");
+async fn add_wallet(out: &Output, name: &str, networks: Vec<String>, kdf: Kdf) -> Result<()> {
+    out.say("=== Add New Wallet ===");
     
     let mut private_key = prompt_password("Enter your private key (with or without 0x): ")
         .context("Failed to read private key")?;
@@ -177,62 +697,350 @@ async fn add_wallet(name: &str) -> Result<()> {
         anyhow::bail!("Password must be at least 8 characters");
     }
     
-    let encrypted_key = encrypt_private_key(&private_key, &password)?;
+    let encrypted_key = encrypt_private_key(&private_key, &password, kdf)?;
     let wallet = LocalWallet::from_str(&private_key)?;
     let address = wallet.address();
     
     let mut wallets = WalletsFile::load("wallet.toml")?;
-    wallets.add_wallet(name.to_string(), format!("{:#x}", address), encrypted_key);
+    wallets.add_wallet(name.to_string(), format!("{:#x}", address), encrypted_key, networks.clone());
+    wallets.save("wallet.toml")?;
+    
+    out.say(format!("✅ Wallet '{}' added successfully!", name));
+    out.say(format!("Address: {:#x}", address));
+    if !networks.is_empty() {
+        out.say(format!("Restricted to networks: {}", networks.join(", ")));
+    }
+    out.result(json!({ "name": name, "address": format!("{:#x}", address), "networks": networks }));
+    
+    Ok(())
+}
+
+/// The path `import-mnemonic` derives: `--hd-path` as given, or account
+/// `--account-index` of the standard Ethereum layout
+fn derivation_path(hd_path: Option<&str>, account_index: Option<u32>) -> Result<String> {
+    match (hd_path, account_index) {
+        (Some(_), Some(_)) => anyhow::bail!("Use either --hd-path or --account-index, not both"),
+        (Some(path), None) => Ok(path.to_string()),
+        (None, Some(index)) => Ok(format!("m/44'/60'/0'/0/{}", index)),
+        (None, None) => Ok(signer::DEFAULT_HD_PATH.to_string()),
+    }
+}
+
+/// Derive the private key at `path` from a BIP-39 English mnemonic
+fn derive_mnemonic_key(phrase: &str, path: &str) -> Result<String> {
+    let wallet = MnemonicBuilder::<English>::default()
+        .phrase(phrase.trim())
+        .derivation_path(path)
+        .with_context(|| format!("Invalid derivation path '{}'", path))?
+        .build()
+        .map_err(|e| anyhow::anyhow!("Invalid mnemonic: {}", e))?;
+    Ok(format!("0x{}", hex::encode(wallet.signer().to_bytes())))
+}
+
+/// Save the account at `path` of a mnemonic as wallet `name`. Each index of
+/// one seed is its own wallet, so saving an address twice is refused.
+fn import_mnemonic(out: &Output, name: &str, path: &str, networks: Vec<String>, kdf: Kdf) -> Result<()> {
+    out.say("=== Import Mnemonic ===");
+    
+    let mut wallets = WalletsFile::load("wallet.toml")?;
+    if wallets.get_wallet(name).is_some() {
+        anyhow::bail!("A wallet named '{}' already exists", name);
+    }
+    
+    let phrase = prompt_password("Enter your mnemonic phrase: ")
+        .context("Failed to read mnemonic")?;
+    let private_key = derive_mnemonic_key(&phrase, path)?;
+    let address = format!("{:#x}", key_address(&private_key)?);
+    if let Some(existing) = wallets.wallets.iter().find(|w| w.address.eq_ignore_ascii_case(&address)) {
+        anyhow::bail!("Account {} ({}) is already saved as wallet '{}'", path, address, existing.name);
+    }
+    out.say(format!("Account {}: {}", path, address));
+    
+    let password = prompt_password("Enter a password to encrypt your key: ")
+        .context("Failed to read password")?;
+    
+    let confirm_password = prompt_password("Confirm password: ")
+        .context("Failed to read password confirmation")?;
+    
+    if password != confirm_password {
+        anyhow::bail!("Passwords do not match");
+    }
+    
+    if password.len() < 8 {
+        anyhow::bail!("Password must be at least 8 characters");
+    }
+    
+    let encrypted_key = encrypt_private_key(&private_key, &password, kdf)?;
+    wallets.add_wallet(name.to_string(), address.clone(), encrypted_key, networks.clone());
     wallets.save("wallet.toml")?;
     
-    println!("✅ Wallet '{}' added successfully!", name);
-    println!("Address: {:#x}", address);
+    out.say(format!("✅ Wallet '{}' added successfully!", name));
+    out.say(format!("Address: {}", address));
+    if !networks.is_empty() {
+        out.say(format!("Restricted to networks: {}", networks.join(", ")));
+    }
+    out.result(json!({ "name": name, "address": address, "hdPath": path, "networks": networks }));
     
     Ok(())
 }
 
-async fn list_wallets() -> Result<()> {
+async fn list_wallets(out: &Output, balances: bool) -> Result<()> {
     let wallets = WalletsFile::load("wallet.toml")?;
     
     if wallets.wallets.is_empty() {
-        println!("No wallets found.");
+        out.say("No wallets found.");
+        out.result(json!({ "wallets": [] }));
         return Ok(());
     }
     
-    println!("=== Saved Wallets ===");
-    for wallet in wallets.wallets {
-        println!("  {} -> {}", wallet.name, wallet.address);
+    if balances {
+        return print_wallet_balances(out, &wallets.wallets).await;
     }
     
+    out.say("=== Saved Wallets ===");
+    for wallet in &wallets.wallets {
+        let damaged = if wallet.is_damaged() { "  ⚠️ damaged: key doesn't match its checksum" } else { "" };
+        if wallet.networks.is_empty() {
+            out.say(format!("  {} -> {}{}", wallet.name, wallet.address, damaged));
+        } else {
+            out.say(format!("  {} -> {}  [{}]{}", wallet.name, wallet.address, wallet.networks.join(", "), damaged));
+        }
+    }
+    
+    let entries: Vec<_> = wallets.wallets.iter()
+        .map(|w| json!({ "name": w.name, "address": w.address, "networks": w.networks, "damaged": w.is_damaged() }))
+        .collect();
+    out.result(json!({ "wallets": entries }));
+    
     Ok(())
 }
 
-async fn select_wallet_interactive() -> Result<(String, String)> {
+async fn show_balances(out: &Output, name: Option<&str>) -> Result<()> {
     let wallets = WalletsFile::load("wallet.toml")?;
     
+    let selected: Vec<WalletEntry> = match name {
+        Some(name) => vec![wallets.get_wallet(name)
+            .with_context(|| format!("Wallet '{}' not found", name))?
+            .clone()],
+        None => wallets.wallets,
+    };
+    
+    if selected.is_empty() {
+        anyhow::bail!("No wallets found. Run 'init' or 'add-wallet' first.");
+    }
+    
+    print_wallet_balances(out, &selected).await
+}
+
+/// Network `ping` checks: `network` if given, otherwise the configured one
+fn ping_target(network: Option<&str>) -> Result<NetworkConfig> {
+    match network {
+        Some(name) => resolve_network(name),
+        None => Ok(load_config().context("Failed to load config. Run 'init' first.")?.network),
+    }
+}
+
+async fn ping(out: &Output, network: Option<&str>) -> Result<()> {
+    let network = ping_target(network)?;
+    out.say(format!("Pinging {} ({})...", network.name, network.rpc_url));
+    let provider = Provider::<Http>::try_from(&network.rpc_url)
+        .context("Failed to create provider")?;
+    ping_provider(out, &provider, &network).await
+}
+
+/// Print the network's current fees, read without building or signing a transaction
+async fn gas_price(out: &Output, network: Option<&str>) -> Result<()> {
+    let network = ping_target(network)?;
+    // Fee queries never touch the contract, so any address will do
+    let mut client = PolyEndpointClient::new(format!("{:#x}", Address::zero()));
+    if TRACE_RPC.load(Ordering::Relaxed) {
+        client = client.with_trace();
+    }
+    let fees = client.suggested_fees(&network.rpc_url).await
+        .with_context(|| format!("Failed to query fees from {}", network.rpc_url))?;
+    
+    let gwei = |wei: u128| tx::format_gwei(U256::from(wei));
+    out.say(format!("Fees on {} (Chain ID: {})", network.name, network.chain_id));
+    match fees.base_fee {
+        Some(base_fee) => {
+            out.say(format!("Base fee:     {} gwei", gwei(base_fee)));
+            out.say(format!("Priority fee: {} gwei (suggested)", gwei(fees.priority_fee)));
+            out.say(format!("Max fee:      {} gwei (2 x base fee + priority fee)", gwei(fees.max_fee)));
+            out.say(format!("Gas price:    {} gwei", gwei(fees.gas_price)));
+        }
+        None => out.say(format!("Gas price:    {} gwei (no EIP-1559 base fee on this chain)", gwei(fees.gas_price))),
+    }
+    out.result(json!({
+        "network": network.name,
+        "chainId": network.chain_id,
+        "gasPriceWei": fees.gas_price.to_string(),
+        "baseFeeWei": fees.base_fee.map(|fee| fee.to_string()),
+        "priorityFeeWei": fees.priority_fee.to_string(),
+        "maxFeeWei": fees.max_fee.to_string(),
+    }));
+    
+    Ok(())
+}
+
+/// Query the chain ID and latest block, failing if the RPC is unreachable or
+/// on a different chain than `network` expects
+async fn ping_provider<M: Middleware>(out: &Output, provider: &M, network: &NetworkConfig) -> Result<()> {
+    let started = std::time::Instant::now();
+    let chain_id = provider.get_chainid().await
+        .map_err(|e| anyhow::anyhow!("RPC {} is unreachable: {}", network.rpc_url, e))?
+        .as_u64();
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let block = provider.get_block_number().await
+        .map_err(|e| anyhow::anyhow!("Failed to fetch the latest block from {}: {}", network.rpc_url, e))?
+        .as_u64();
+
+    out.say(format!("Chain ID:     {}", chain_id));
+    out.say(format!("Latest block: {}", block));
+    out.say(format!("Latency:      {} ms", latency_ms));
+    if chain_id != network.chain_id {
+        anyhow::bail!(
+            "RPC {} reports chain ID {}, but {} expects {}",
+            network.rpc_url, chain_id, network.name, network.chain_id
+        );
+    }
+
+    out.say("✅ RPC is reachable");
+    out.result(json!({
+        "network": network.name,
+        "rpcUrl": network.rpc_url,
+        "chainId": chain_id,
+        "blockNumber": block,
+        "latencyMs": latency_ms,
+    }));
+    Ok(())
+}
+
+/// Print the configuration after `--chain`, `--wallet` and the password
+/// options are applied, labelling each value with where it came from.
+/// Encrypted keys and the password are never printed, and the RPC URL goes
+/// through [`config::redact_url`].
+fn show_config(out: &Output, signer: &SignerArgs) -> Result<()> {
+    let config = load_config().context("Failed to load config. Run 'init' first.")?;
+    let network_source = if CHAIN_OVERRIDE.get().is_some() { "--chain" } else { "config.toml" };
+
+    let (wallet_name, wallet_source) = match (&signer.wallet, &config.active_wallet) {
+        (Some(name), _) => (Some(name.as_str()), "--wallet"),
+        (None, Some(name)) => (Some(name.as_str()), "config.toml"),
+        (None, None) => (None, "config.toml"),
+    };
+    let wallet_address = match wallet_name {
+        Some(name) => WalletsFile::load("wallet.toml")?.get_wallet(name).map(|wallet| wallet.address.clone()),
+        None => None,
+    };
+
+    let password_source = if signer.password_file.is_some() {
+        "--password-file"
+    } else if std::env::var_os(PASSWORD_ENV).is_some() {
+        "env POLYPORTAL_PASSWORD"
+    } else {
+        "prompt"
+    };
+
+    let rpc_url = config::redact_url(&config.network.rpc_url);
+    let wallet = match (wallet_name, &wallet_address) {
+        (Some(name), Some(address)) => format!("{} -> {}", name, address),
+        (Some(name), None) => format!("{} (not found in wallet.toml)", name),
+        (None, _) => "(none)".to_string(),
+    };
+
+    out.say("=== Effective Configuration ===");
+    out.say(format!("Network:          {} (Chain ID: {})  [{}]", config.network.name, config.network.chain_id, network_source));
+    out.say(format!("RPC URL:          {}  [{}]", rpc_url, network_source));
+    out.say(format!("Active wallet:    {}  [{}]", wallet, wallet_source));
+    out.say(format!(
+        "Default contract: {}  [{}]",
+        config.network.default_contract.as_deref().unwrap_or("(none)"),
+        network_source
+    ));
+    let password = if password_source == "prompt" { "(asked for when signing)" } else { "<redacted>" };
+    out.say(format!("Password:         {}  [{}]", password, password_source));
+    out.result(json!({
+        "network": { "value": config.network.name, "source": network_source },
+        "chainId": { "value": config.network.chain_id, "source": network_source },
+        "rpcUrl": { "value": rpc_url, "source": network_source },
+        "activeWallet": { "value": wallet_name, "address": wallet_address, "source": wallet_source },
+        "defaultContract": { "value": config.network.default_contract, "source": network_source },
+        "password": { "source": password_source },
+    }));
+
+    Ok(())
+}
+
+/// Fetch an address's balance, or `None` if the RPC can't be reached
+async fn fetch_balance(provider: &Provider<Http>, address: &str) -> Option<U256> {
+    let address: Address = address.parse().ok()?;
+    provider.get_balance(address, None).await.ok()
+}
+
+/// Print wallets with their balances on the configured network.
+///
+/// Balances that can't be fetched are shown as `unknown` instead of failing.
+async fn print_wallet_balances(out: &Output, wallets: &[WalletEntry]) -> Result<()> {
+    let config = load_config()
+        .context("Failed to load config. Run 'init' first.")?;
+    let provider = Provider::<Http>::try_from(&config.network.rpc_url)
+        .context("Failed to create provider")?;
+    
+    out.say(format!("=== Wallet Balances ({}) ===", config.network.name));
+    let mut entries = Vec::new();
+    for wallet in wallets {
+        let balance = fetch_balance(&provider, &wallet.address).await.map(format_ether);
+        out.say(format!(
+            "  {} -> {}  {} ETH",
+            wallet.name,
+            wallet.address,
+            balance.as_deref().unwrap_or("unknown")
+        ));
+        entries.push(json!({ "name": wallet.name, "address": wallet.address, "balance": balance }));
+    }
+    
+    out.result(json!({ "network": config.network.name, "wallets": entries }));
+    
+    Ok(())
+}
+
+/// Pick a wallet by `--wallet`, the only one, or a menu
+fn choose_wallet(out: &Output, signer: &SignerArgs) -> Result<WalletEntry> {
+    let mut wallets = WalletsFile::load("wallet.toml")?;
+    
     if wallets.wallets.is_empty() {
         anyhow::bail!("No wallets found. Run 'init' or 'add-wallet' first.");
     }
     
+    // Wallet chosen up front with --wallet
+    if let Some(name) = signer.wallet.as_deref() {
+        let wallet = wallets.get_wallet(name)
+            .with_context(|| format!("Wallet '{}' not found", name))?;
+        wallet.check_checksum()?;
+        out.say(format!("Using wallet: {} ({})", wallet.name, wallet.address));
+        return Ok(wallet.clone());
+    }
+    
     // If only one wallet, use it
     if wallets.wallets.len() == 1 {
-        let wallet = &wallets.wallets[0];
-        println!("Using wallet: {} ({})", wallet.name, wallet.address);
-        return Ok((wallet.encrypted_key.clone(), wallet.address.clone()));
+        let wallet = wallets.wallets.remove(0);
+        wallet.check_checksum()?;
+        out.say(format!("Using wallet: {} ({})", wallet.name, wallet.address));
+        return Ok(wallet);
     }
     
     // Multiple wallets - let user choose
-    println!("=== Select Wallet ===");
+    out.say("=== Select Wallet ===");
     for (i, wallet) in wallets.wallets.iter().enumerate() {
-        println!("  {}: {} -> {}", i + 1, wallet.name, wallet.address);
+        let damaged = if wallet.is_damaged() { "  (damaged)" } else { "" };
+        out.say(format!("  {}: {} -> {}{}", i + 1, wallet.name, wallet.address, damaged));
     }
-    println!();
+    out.blank();
     
-    print!("Select wallet (1-{}): ", wallets.wallets.len());
-    io::stdout().flush().unwrap();
+    out.prompt(format!("Select wallet (1-{}): ", wallets.wallets.len()))?;
     
     let mut input = String::new();
-    io::stdin().read_line(&mut input).unwrap();
+    io::stdin().read_line(&mut input)?;
     
     let choice = input.trim().parse::<usize>()
         .context("Invalid selection")?;
@@ -241,10 +1049,103 @@ async fn select_wallet_interactive() -> Result<(String, String)> {
         anyhow::bail!("Invalid selection");
     }
     
-    let wallet = &wallets.wallets[choice - 1];
-    println!("Selected: {} ({})", wallet.name, wallet.address);
+    let wallet = wallets.wallets.remove(choice - 1);
+    wallet.check_checksum()?;
+    out.say(format!("Selected: {} ({})", wallet.name, wallet.address));
+    
+    Ok(wallet)
+}
+
+/// Refuse a wallet restricted to other networks unless `--allow-network` is set
+fn check_wallet_network(out: &Output, wallet: &WalletEntry, network: &str, allow: bool) -> Result<()> {
+    if wallet.allows_network(network) {
+        return Ok(());
+    }
+    if allow {
+        out.say(format!(
+            "⚠️  Wallet '{}' is restricted to {}; using it on '{}' because of --allow-network",
+            wallet.name,
+            wallet.networks.join(", "),
+            network
+        ));
+        return Ok(());
+    }
+    anyhow::bail!(
+        "Wallet '{}' is restricted to networks [{}] and can't be used on '{}'. Pass --allow-network to override.",
+        wallet.name,
+        wallet.networks.join(", "),
+        network
+    )
+}
+
+/// Load config.toml, with its network replaced by `--chain` if given
+fn load_config() -> Result<Config> {
+    let mut config = Config::load("config.toml")?;
+    if let Some(network) = CHAIN_OVERRIDE.get() {
+        config.network = network.clone();
+    }
+    Ok(config)
+}
+
+/// `--contract` if given, otherwise the contract `deploy` saved for the
+/// configured network
+fn resolve_contract(contract: Option<String>) -> Result<String> {
+    match contract {
+        Some(contract) => Ok(contract),
+        None => contract_or_default(None, &load_config()?.network),
+    }
+}
+
+fn contract_or_default(contract: Option<String>, network: &NetworkConfig) -> Result<String> {
+    contract.or_else(|| network.default_contract.clone()).with_context(|| {
+        format!(
+            "No --contract given and no default contract saved for {} in config.toml; pass --contract or run `deploy`",
+            network.name
+        )
+    })
+}
+
+/// Look up a network by name among those `init` offers. The configured
+/// network is used as is when the name matches, so its RPC URL is kept.
+fn resolve_network(name: &str) -> Result<NetworkConfig> {
+    if let Some(config) = Config::load("config.toml").ok().filter(|config| config.network.name == name) {
+        return Ok(config.network);
+    }
+
+    let networks = load_networks()?;
+    let Some(network) = networks.get(name) else {
+        let names: Vec<&str> = networks.networks().iter().map(|network| network.name.as_str()).collect();
+        anyhow::bail!("Unknown network '{}'. Supported networks: {}", name, names.join(", "));
+    };
+    Ok(NetworkConfig {
+        name: network.name.clone(),
+        rpc_url: network.rpc_urls.first().cloned().with_context(|| format!("Network '{}' has no RPC URL", name))?,
+        chain_id: network.chain_id,
+        poll_ms: None,
+        gas_buffer_percent: None,
+        default_contract: None,
+    })
+}
+
+/// Networks offered by `init`: the built-in list, overridden and extended by
+/// `networks.toml` in the working directory if it exists
+fn load_networks() -> Result<NetworkRegistry> {
+    let mut networks = NetworkRegistry::new(vec![
+        Network::new("sepolia", 11155111, "https://sepolia.infura.io/v3/YOUR_INFURA_KEY"),
+        Network::new("mainnet", 1, "https://mainnet.infura.io/v3/YOUR_INFURA_KEY"),
+        Network::new("base", 8453, "https://mainnet.base.org"),
+        Network::new("base-sepolia", 84532, "https://sepolia.base.org"),
+        Network::new("goerli", 5, "https://goerli.infura.io/v3/YOUR_INFURA_KEY"),
+        Network::new("monad", 9090, "https://monad.gg"),
+        Network::new("localhost", 1337, "http://127.0.0.1:8545"),
+    ]);
     
-    Ok((wallet.encrypted_key.clone(), wallet.address.clone()))
+    let path = std::path::Path::new("networks.toml");
+    if path.exists() {
+        let contents = std::fs::read_to_string(path).context("Failed to read networks.toml")?;
+        networks.merge(NetworkRegistry::from_toml(&contents)?);
+    }
+    Ok(networks)
 }
 
 #[allow(dead_code)]
@@ -253,6 +1154,9 @@ fn default_network() -> NetworkConfig {
         name: "localhost".to_string(),
         rpc_url: "http://127.0.0.1:8545".to_string(),
         chain_id: 1337,
+        poll_ms: None,
+        gas_buffer_percent: None,
+        default_contract: None,
     }
 }
 
@@ -264,55 +1168,43 @@ fn default_deployer() -> DeployerConfig {
     }
 }
 
-async fn init_cli() -> Result<()> {
-    println!("=== Initialize PolyPortal CLI ===");
-    println!();
-    println!("This will guide you through setting up your configuration.");
-    println!();
+async fn init_cli(out: &Output, kdf: Kdf) -> Result<()> {
+    out.say("=== Initialize PolyPortal CLI ===");
+    out.blank();
+    out.say("This will guide you through setting up your configuration.");
+    out.blank();
     
     // Chain selection
-    println!("Network Configuration:");
-    println!("1. Sepolia Testnet (Default) - Chain ID: 11155111");
-    println!("2. Ethereum Mainnet - Chain ID: 1");
-    println!("3. Base Mainnet - Chain ID: 8453");
-    println!("4. Base Sepolia (Testnet) - Chain ID: 84532");
-    println!("5. Goerli Testnet - Chain ID: 5");
-    println!("6. Monad - Chain ID: 9090");
-    println!("7. Localhost - Chain ID: 1337");
-    println!("8. Custom");
-    println!();
+    let networks = load_networks()?;
+    let custom_choice = networks.networks().len() + 1;
+    out.say("Network Configuration:");
+    for (i, network) in networks.networks().iter().enumerate() {
+        let default = if i == 0 { " (Default)" } else { "" };
+        out.say(format!("{}. {}{} - Chain ID: {}", i + 1, network.name, default, network.chain_id));
+    }
+    out.say(format!("{}. Custom", custom_choice));
+    out.blank();
     
     let mut input = String::new();
-    print!("Select network (1-8) [default: 1]: ");
-    use std::io::{self, Write};
-    io::stdout().flush()?;
+    out.prompt(format!("Select network (1-{}) [default: 1]: ", custom_choice))?;
     io::stdin().read_line(&mut input)?;
     
-    let choice = input.trim().parse::<u8>().unwrap_or(1);
-    
-    let (chain_id, chain_name, default_rpc) = match choice {
-        1 => (11155111u64, "sepolia".to_string(), "https://sepolia.infura.io/v3/YOUR_INFURA_KEY".to_string()),
-        2 => (1u64, "mainnet".to_string(), "https://mainnet.infura.io/v3/YOUR_INFURA_KEY".to_string()),
-        3 => (8453u64, "base".to_string(), "https://mainnet.base.org".to_string()),
-        4 => (84532u64, "base-sepolia".to_string(), "https://sepolia.base.org".to_string()),
-        5 => (5u64, "goerli".to_string(), "https://goerli.infura.io/v3/YOUR_INFURA_KEY".to_string()),
-        6 => (9090u64, "monad".to_string(), "https://monad.gg".to_string()),
-        7 => (1337u64, "localhost".to_string(), "http://127.0.0.1:8545".to_string()),
-        _ => {
+    let choice = input.trim().parse::<usize>().unwrap_or(1).max(1);
+    
+    let (chain_id, chain_name, default_rpc) = match networks.networks().get(choice - 1) {
+        Some(network) => (network.chain_id, network.name.clone(), network.rpc_urls[0].clone()),
+        None => {
             let mut id_input = String::new();
             let mut name_input = String::new();
             let mut rpc_input = String::new();
             
-            print!("Enter Chain ID: ");
-            io::stdout().flush()?;
+            out.prompt("Enter Chain ID: ")?;
             io::stdin().read_line(&mut id_input)?;
             
-            print!("Enter Network Name: ");
-            io::stdout().flush()?;
+            out.prompt("Enter Network Name: ")?;
             io::stdin().read_line(&mut name_input)?;
             
-            print!("Enter RPC URL: ");
-            io::stdout().flush()?;
+            out.prompt("Enter RPC URL: ")?;
             io::stdin().read_line(&mut rpc_input)?;
             
             let chain_id = id_input.trim().parse().unwrap_or(1);
@@ -324,9 +1216,8 @@ async fn init_cli() -> Result<()> {
     };
     
     let mut rpc_url = default_rpc;
-    if choice != 7 {  // Not localhost
-        print!("Enter RPC URL [{}]: ", rpc_url);
-        io::stdout().flush()?;
+    if chain_name != "localhost" {
+        out.prompt(format!("Enter RPC URL [{}]: ", rpc_url))?;
         let mut rpc_input = String::new();
         io::stdin().read_line(&mut rpc_input)?;
         let trimmed = rpc_input.trim();
@@ -335,9 +1226,31 @@ async fn init_cli() -> Result<()> {
         }
     }
     
-    println!();
-    println!("Private Key Configuration:");
-    println!("⚠️  Your private key will be encrypted with a password");
+    // Ask the RPC which chain it serves so we never sign for the wrong one
+    let mut chain_id = chain_id;
+    match fetch_chain_id(&rpc_url).await {
+        Ok(live_chain_id) if live_chain_id == chain_id => {
+            out.say(format!("✓ RPC reports chain ID {}", live_chain_id));
+        }
+        Ok(live_chain_id) => {
+            out.blank();
+            out.say(format!("⚠️  WARNING: the RPC reports chain ID {}, but you selected {}.", live_chain_id, chain_id));
+            out.say("⚠️  Transactions signed for the wrong chain ID will be rejected.");
+            out.prompt(format!("Use chain ID {} from the RPC instead? [Y/n]: ", live_chain_id))?;
+            let mut answer = String::new();
+            io::stdin().read_line(&mut answer)?;
+            if !answer.trim().eq_ignore_ascii_case("n") {
+                chain_id = live_chain_id;
+            }
+        }
+        Err(e) => {
+            out.say(format!("⚠️  Could not reach the RPC to verify the chain ID ({:#})", e));
+        }
+    }
+    
+    out.blank();
+    out.say("Private Key Configuration:");
+    out.say("⚠️  Your private key will be encrypted with a password");
     
     // Get private key
     let mut private_key = prompt_password("Enter your private key (with or without 0x): ")
@@ -364,9 +1277,9 @@ async fn init_cli() -> Result<()> {
     }
     
     // Encrypt the private key
-    println!();
-    println!("Encrypting private key...");
-    let encrypted_key = encrypt_private_key(&private_key, &password)
+    out.blank();
+    out.say("Encrypting private key...");
+    let encrypted_key = encrypt_private_key(&private_key, &password, kdf)
         .context("Failed to encrypt private key")?;
     
     // Get address from private key
@@ -376,7 +1289,7 @@ async fn init_cli() -> Result<()> {
     // Load or create config
     let mut config = match Config::load("config.toml") {
         Ok(cfg) => {
-            println!("⚠️  Warning: config.toml already exists and will be overwritten.");
+            out.say("⚠️  Warning: config.toml already exists and will be overwritten.");
             cfg
         }
         Err(_) => {
@@ -386,6 +1299,9 @@ async fn init_cli() -> Result<()> {
                         name: chain_name.clone(),
                         rpc_url: rpc_url.clone(),
                         chain_id,
+                        poll_ms: None,
+                        gas_buffer_percent: None,
+                        default_contract: None,
                     },
                     deployer: DeployerConfig {
                         address: "".to_string(),
@@ -398,14 +1314,16 @@ async fn init_cli() -> Result<()> {
         }
     };
     
-    // Update config
+    // Update config; a contract saved for another network doesn't carry over
+    if config.network.name != chain_name {
+        config.network.default_contract = None;
+    }
     config.network.name = chain_name.clone();
     config.network.rpc_url = rpc_url.clone();
     config.network.chain_id = chain_id;
     
     // Ask for wallet name
-    print!("Enter a name for this wallet [default: wallet-1]: ");
-    io::stdout().flush()?;
+    out.prompt("Enter a name for this wallet [default: wallet-1]: ")?;
     let mut name_input = String::new();
     io::stdin().read_line(&mut name_input)?;
     let wallet_name = name_input.trim();
@@ -416,7 +1334,7 @@ async fn init_cli() -> Result<()> {
         .unwrap_or_else(|_| WalletsFile { wallets: vec![] });
     
     // Add wallet
-    wallets.add_wallet(wallet_name.to_string(), format!("{:#x}", address), encrypted_key);
+    wallets.add_wallet(wallet_name.to_string(), format!("{:#x}", address), encrypted_key, Vec::new());
     wallets.save("wallet.toml")?;
     
     // Update active wallet
@@ -426,346 +1344,966 @@ async fn init_cli() -> Result<()> {
     config.save("config.toml")
         .context("Failed to save config")?;
     
-    println!();
-    println!("✅ Configuration initialized successfully!");
-    println!();
-    println!("Network: {} (Chain ID: {})", chain_name, chain_id);
-    println!("RPC URL: {}", config.network.rpc_url);
-    println!("Wallet: {} -> {:#x}", wallet_name, address);
-    println!("Config saved to: config.toml");
-    println!("Wallets saved to: wallet.toml");
-    println!();
-    println!("Remember your password - you'll need it to deploy the contract.");
+    out.blank();
+    out.say("✅ Configuration initialized successfully!");
+    out.blank();
+    out.say(format!("Network: {} (Chain ID: {})", chain_name, chain_id));
+    out.say(format!("RPC URL: {}", config.network.rpc_url));
+    out.say(format!("Wallet: {} -> {:#x}", wallet_name, address));
+    out.say("Config saved to: config.toml");
+    out.say("Wallets saved to: wallet.toml");
+    out.blank();
+    out.say("Remember your password - you'll need it to deploy the contract.");
+    out.result(json!({
+        "network": chain_name,
+        "chainId": chain_id,
+        "rpcUrl": config.network.rpc_url,
+        "wallet": wallet_name,
+        "address": format!("{:#x}", address),
+    }));
     
     Ok(())
 }
 
-async fn get_password_and_wallet() -> Result<(String, String)> {
-    let (encrypted_key, _wallet_address) = select_wallet_interactive().await?;
-    
-    let password = prompt_password("Enter your password: ")
-        .context("Failed to read password")?;
+/// Read the wallet password: --password-file, then POLYPORTAL_PASSWORD, then prompt
+fn read_password(signer: &SignerArgs) -> Result<String> {
+    if let Some(path) = &signer.password_file {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read password file {}", path.display()))?;
+        return Ok(contents.trim_end_matches(['\r', '\n']).to_string());
+    }
     
-    let private_key = decrypt_private_key(&encrypted_key, &password)
-        .context("Failed to decrypt private key. Wrong password?")?;
+    if let Ok(password) = std::env::var(PASSWORD_ENV) {
+        return Ok(password);
+    }
     
-    Ok((private_key, password))
+    prompt_password("Enter your password: ")
+        .context("Failed to read password")
 }
 
-async fn setup_client(config: &Config, private_key: &str) -> Result<SignerMiddleware<Provider<Http>, LocalWallet>> {
-    let provider = Provider::<Http>::try_from(&config.network.rpc_url)
-        .context("Failed to create provider")?;
-    
-    let wallet = LocalWallet::from_str(private_key)
-        .context("Failed to create wallet")?
-        .with_chain_id(config.network.chain_id);
-    
-    Ok(SignerMiddleware::new(provider, wallet))
+/// The account a write command sends from. It is picked before anything is
+/// unlocked, so a dry run needs neither the password nor the device's approval.
+enum Sender {
+    Wallet(WalletEntry),
+    #[cfg(feature = "ledger")]
+    Ledger(ethers::signers::Ledger),
 }
 
-#[allow(dead_code)]
-async fn get_contract_abi() -> Result<serde_json::Value> {
+impl Sender {
+    fn address(&self) -> Result<Address> {
+        match self {
+            Sender::Wallet(wallet) => wallet.address.parse().context("Invalid wallet address"),
+            #[cfg(feature = "ledger")]
+            Sender::Ledger(ledger) => Ok(ledger.address()),
+        }
+    }
+}
+
+/// Connect to the Ledger when `--ledger` is set and show the account it will sign with
+#[cfg_attr(not(feature = "ledger"), allow(unused_variables))]
+async fn ledger_sender(out: &Output, signer: &SignerArgs, chain_id: u64) -> Result<Option<Sender>> {
+    if !signer.ledger {
+        return Ok(None);
+    }
+    #[cfg(feature = "ledger")]
+    {
+        let hd_path = signer.hd_path.as_deref().unwrap_or(signer::DEFAULT_HD_PATH);
+        let ledger = signer::connect_ledger(hd_path, chain_id).await?;
+        out.say(format!("Using Ledger account: {} ({})", to_checksum(&ledger.address(), None), hd_path));
+        Ok(Some(Sender::Ledger(ledger)))
+    }
+    #[cfg(not(feature = "ledger"))]
+    anyhow::bail!("This build has no Ledger support; rebuild with `cargo build --features ledger`")
+}
+
+/// Pick the sending account: the Ledger with `--ledger`, otherwise a saved
+/// wallet that may be used on `network`
+async fn choose_sender(out: &Output, signer: &SignerArgs, network: &NetworkConfig) -> Result<Sender> {
+    if let Some(sender) = ledger_sender(out, signer, network.chain_id).await? {
+        return Ok(sender);
+    }
+    let wallet = choose_wallet(out, signer)?;
+    check_wallet_network(out, &wallet, &network.name, signer.allow_network)?;
+    Ok(Sender::Wallet(wallet))
+}
+
+/// Get a signer for the chosen account: decrypt the wallet, or have the user
+/// confirm the Ledger account before the device is asked to sign
+#[cfg_attr(not(feature = "ledger"), allow(unused_variables))]
+fn unlock_sender(out: &Output, signer: &SignerArgs, sender: Sender) -> Result<TxSigner> {
+    match sender {
+        Sender::Wallet(wallet) => {
+            wallet.check_checksum()?;
+            let password = read_password(signer)?;
+            let private_key = unlock_wallet(&wallet.encrypted_key, &wallet.address, &password)?;
+            let wallet = LocalWallet::from_str(&private_key).context("Failed to create wallet")?;
+            Ok(TxSigner::Local(wallet))
+        }
+        #[cfg(feature = "ledger")]
+        Sender::Ledger(ledger) => {
+            out.prompt(format!("Sign with Ledger account {}? [y/N]: ", to_checksum(&ledger.address(), None)))?;
+            let mut answer = String::new();
+            io::stdin().read_line(&mut answer)?;
+            if !answer.trim().eq_ignore_ascii_case("y") {
+                anyhow::bail!("Aborted; nothing was signed");
+            }
+            out.say("Confirm on the Ledger when it asks.");
+            Ok(TxSigner::Ledger(ledger))
+        }
+    }
+}
+
+/// Decrypt a wallet's key and check that it belongs to the address stored
+/// next to it, so a corrupted or hand-edited entry can't sign as someone else
+fn unlock_wallet(encrypted_key: &str, wallet_address: &str, password: &str) -> Result<String> {
+    let private_key = decrypt_private_key(encrypted_key, password).map_err(|e| match e {
+        DecryptError::WrongPassword => anyhow::anyhow!("Failed to decrypt private key. Check your password."),
+        DecryptError::InvalidFormat(reason) => anyhow::anyhow!(
+            "The encrypted key in wallet.toml is damaged ({}). Check the file, or re-import the key.",
+            reason
+        ),
+    })?;
+    
+    let expected: Address = wallet_address.parse()
+        .with_context(|| format!("Invalid wallet address '{}' in wallet.toml", wallet_address))?;
+    let actual = LocalWallet::from_str(&private_key)
+        .context("Decrypted private key is invalid")?
+        .address();
+    if actual != expected {
+        anyhow::bail!(
+            "Decrypted key belongs to {}, but the wallet is stored as {}. wallet.toml may be corrupted or edited; refusing to sign.",
+            to_checksum(&actual, None),
+            to_checksum(&expected, None)
+        );
+    }
+    
+    Ok(private_key)
+}
+
+/// Query `eth_chainId` from an RPC URL
+async fn fetch_chain_id(rpc_url: &str) -> Result<u64> {
+    let provider = Provider::<Http>::try_from(rpc_url)
+        .context("Failed to create provider")?;
+    Ok(provider.get_chainid().await?.as_u64())
+}
+
+/// Make sure the RPC serves the chain the config (and therefore the signer) expects
+async fn verify_chain_id<M: Middleware + 'static>(provider: &M, config: &Config) -> Result<()> {
+    let live_chain_id = provider.get_chainid().await
+        .context("Failed to query chain ID from RPC")?
+        .as_u64();
+    if live_chain_id != config.network.chain_id {
+        anyhow::bail!(
+            "RPC {} reports chain ID {}, but config.toml expects {} ({}). Fix the network config before sending.",
+            config.network.rpc_url, live_chain_id, config.network.chain_id, config.network.name
+        );
+    }
+    Ok(())
+}
+
+/// Apply the pending transaction polling interval from `--poll-ms` or the
+/// network's `poll_ms`, keeping ethers' default when neither is set
+fn with_poll_interval(provider: Provider<Http>, config: &Config, tx_args: &TxArgs) -> Provider<Http> {
+    match tx_args.poll_ms.or(config.network.poll_ms) {
+        Some(ms) => provider.interval(Duration::from_millis(ms)),
+        None => provider,
+    }
+}
+
+async fn setup_client(config: &Config, tx_args: &TxArgs, signer: TxSigner) -> Result<SignerMiddleware<Provider<Http>, TxSigner>> {
+    tracing::debug!(rpc_url = %config.network.rpc_url, chain_id = config.network.chain_id, "connecting signer");
+    let provider = Provider::<Http>::try_from(&config.network.rpc_url)
+        .context("Failed to create provider")?;
+    signer_client(with_poll_interval(provider, config, tx_args), config, signer).await
+}
+
+/// Check that `inner` serves the configured chain and sign through it with `signer`
+async fn signer_client<M: Middleware + 'static>(inner: M, config: &Config, signer: TxSigner) -> Result<SignerMiddleware<M, TxSigner>> {
+    verify_chain_id(&inner, config).await?;
+    
+    Ok(SignerMiddleware::new(inner, signer.with_chain_id(config.network.chain_id)))
+}
+
+#[allow(dead_code)]
+async fn get_contract_abi() -> Result<serde_json::Value> {
     let abi_path = "../artifacts/contracts/PolyEndpoint.sol/PolyEndpoint.json";
     let abi_str = std::fs::read_to_string(abi_path)
         .context("Failed to read contract ABI")?;
     Ok(serde_json::from_str(&abi_str)?)
 }
 
-async fn call_add_endpoint(contract: String, url: &str, description: &str) -> Result<()> {
-    println!("Adding endpoint: {}", url);
-    if !description.is_empty() {
-        println!("Description: {}", description);
-    }
-    println!("Contract: {}", contract);
-    
-    let config = Config::load("config.toml")
+/// Sign and send a call to the contract, then wait for it to be mined
+async fn send_contract_call(out: &Output, signer: &SignerArgs, tx_args: &TxArgs, contract: &str, data: Vec<u8>, success_msg: &str) -> Result<()> {
+    let config = load_config()
         .context("Failed to load config. Run 'init' first.")?;
     
-    let (private_key, _password) = get_password_and_wallet().await?;
-    let client = setup_client(&config, &private_key).await?;
-    
     let contract_address: Address = contract.parse()
         .context("Invalid contract address")?;
     
-    // Manual ABI encoding for addEndpoint(string,string)
-    // Function signature: addEndpoint(string,string)
-    // Method ID: 0x + first 4 bytes of keccak256("addEndpoint(string,string)")
-    let method_id = ethers::utils::keccak256("addEndpoint(string,string)")[0..4].to_vec();
+    let sender = choose_sender(out, signer, &config.network).await?;
     
-    // Encode two string parameters
-    let encoded = ethers::abi::encode(&[
-        ethers::abi::Token::String(url.to_string()),
-        ethers::abi::Token::String(description.to_string())
-    ]);
-    let full_data = [&method_id[..], &encoded].concat();
+    // A dry run only needs the sender's address, not the decrypted key
+    if tx_args.dry_run {
+        let provider = Provider::<Http>::try_from(&config.network.rpc_url)
+            .context("Failed to create provider")?;
+        ensure_target_code(&provider, contract_address, &config.network.name, tx_args).await?;
+        return dry_run(&provider, out, sender.address()?, Some(contract_address), Bytes::from(data), tx_args).await;
+    }
     
-    let tx = TransactionRequest::new()
-        .to(contract_address)
-        .data(Bytes::from(full_data));
+    let client = setup_client(&config, tx_args, unlock_sender(out, signer, sender)?).await?;
+    ensure_target_code(&client, contract_address, &config.network.name, tx_args).await?;
     
-    println!("Sending transaction...");
-    let pending_tx = client.send_transaction(tx, None).await?;
-    println!("Transaction sent: {:?}", pending_tx.tx_hash());
+    let outcome = submit_call(out, &client, contract_address, data, tx_args, config.network.gas_buffer_percent).await?;
+    
+    out.say(success_msg);
+    out.result(outcome_json(&outcome));
     
-    println!("Waiting for confirmation...");
-    let receipt = pending_tx.await?;
+    Ok(())
+}
+
+/// Simulate, sign and send one call with an unlocked client and wait for it
+/// to be mined. `gas_buffer` is the network's `gas_buffer_percent`.
+async fn submit_call<M: Middleware + 'static>(
+    out: &Output,
+    client: &SignerMiddleware<M, TxSigner>,
+    contract_address: Address,
+    data: Vec<u8>,
+    tx_args: &TxArgs,
+    gas_buffer: Option<u64>,
+) -> Result<TxOutcome> {
+    tracing::debug!(to = ?contract_address, calldata_bytes = data.len(), value = %tx_args.value, "submitting call");
+    let mut tx = build_transaction(client, out, Some(contract_address), Bytes::from(data), tx_args).await?;
+    tx.set_from(client.address());
+    set_nonce(client, out, &mut tx, tx_args).await?;
+    
+    // Simulate first so a reverting call fails here instead of on-chain
+    let estimate = estimate_cost(client, out, &tx).await?;
+    tx.set_gas(gas_limit(out, estimate.gas, tx_args, gas_buffer));
+    
+    out.say("Sending transaction...");
+    let pending_tx = client.send_transaction(tx, None).await?;
+    let tx_hash = pending_tx.tx_hash();
+    tracing::debug!(tx_hash = ?tx_hash, gas = estimate.gas, "transaction sent");
+    out.say(format!("Transaction sent: {:?}", tx_hash));
     
-    if receipt.is_some() {
-        println!("✅ Endpoint added successfully!");
+    let receipt = wait_for_receipt(out, pending_tx, tx_args.confirmations).await?;
+    tx_outcome(out, &receipt)
+}
+
+/// Add one endpoint. Unless `force` is set, a `hasEndpoint` call first skips
+/// URLs that are already registered, which `addEndpoint` would revert on.
+async fn call_add_endpoint(out: &Output, signer: &SignerArgs, tx_args: &TxArgs, contract: String, url: &str, description: &str, force: bool) -> Result<()> {
+    out.say(format!("Adding endpoint: {}", url));
+    if !description.is_empty() {
+        out.say(format!("Description: {}", description));
+    }
+    out.say(format!("Contract: {}", contract));
+    
+    if !force {
+        let config = load_config()
+            .context("Failed to load config. Run 'init' first.")?;
+        let contract_address: Address = contract.parse()
+            .context("Invalid contract address")?;
+        let provider = Provider::<Http>::try_from(&config.network.rpc_url)
+            .context("Failed to create provider")?;
+        if endpoint_registered(&provider, contract_address, url).await? {
+            out.say(format!("⏭️  {} is already registered; nothing sent (use --force to send anyway)", url));
+            out.result(json!({ "url": url, "status": "skipped", "reason": "already registered" }));
+            return Ok(());
+        }
     }
     
-    Ok(())
+    let full_data = encode_add_endpoint_with_description(url, description).build();
+    
+    send_contract_call(out, signer, tx_args, &contract, full_data, "✅ Endpoint added successfully!").await
+}
+
+/// Whether `url` is already registered, from a `hasEndpoint(url)` `eth_call`
+async fn endpoint_registered<M: Middleware + 'static>(provider: &M, contract: Address, url: &str) -> Result<bool> {
+    let tx = TransactionRequest::new().to(contract).data(encode_has_endpoint(url).build());
+    let result = provider.call(&tx.into(), None).await
+        .map_err(|e| anyhow::anyhow!("hasEndpoint check failed: {}", e))?;
+    decode_bool(&result).map_err(anyhow::Error::msg)
 }
 
-async fn call_remove_endpoint(contract: String, url: String) -> Result<()> {
-    println!("Removing endpoint: {}", url);
+async fn call_remove_endpoint(out: &Output, signer: &SignerArgs, tx_args: &TxArgs, contract: String, url: String) -> Result<()> {
+    out.say(format!("Removing endpoint: {}", url));
     
-    let config = Config::load("config.toml")?;
-    let (private_key, _password) = get_password_and_wallet().await?;
-    let client = setup_client(&config, &private_key).await?;
+    let data = encode_remove_endpoint(&url);
     
-    let contract_address: Address = contract.parse()?;
+    send_contract_call(out, signer, tx_args, &contract, data.build(), "✅ Endpoint removed successfully!").await
+}
+
+/// Add or remove every endpoint in a batch file, unlocking the wallet only once.
+///
+/// A failing entry is recorded and the batch moves on to the next one. When
+/// adding, entries that are already registered are skipped unless `force` is set.
+async fn call_endpoints_file(out: &Output, signer: &SignerArgs, tx_args: &TxArgs, contract: String, file: &std::path::Path, remove: bool, force: bool) -> Result<()> {
+    if tx_args.nonce.is_some() {
+        anyhow::bail!("--nonce can't be used with batch commands; each entry is sent with the next pending nonce");
+    }
     
-    let method_id = ethers::utils::keccak256("removeEndpoint(string)")[0..4].to_vec();
-    let encoded = ethers::abi::encode(&[ethers::abi::Token::String(url.clone())]);
-    let full_data = [&method_id[..], &encoded].concat();
+    let entries = batch::read_endpoints_file(file)?;
+    if entries.is_empty() {
+        anyhow::bail!("No endpoints found in {}", file.display());
+    }
     
-    let tx = TransactionRequest::new()
-        .to(contract_address)
-        .data(Bytes::from(full_data));
+    let action = if remove { "Removing" } else { "Adding" };
+    out.say(format!("{} {} endpoints from {}", action, entries.len(), file.display()));
+    out.say(format!("Contract: {}", contract));
     
-    println!("Sending transaction...");
-    let pending_tx = client.send_transaction(tx, None).await?;
-    println!("Transaction sent: {:?}", pending_tx.tx_hash());
+    let config = load_config()
+        .context("Failed to load config. Run 'init' first.")?;
+    let contract_address: Address = contract.parse()
+        .context("Invalid contract address")?;
     
-    let receipt = pending_tx.await?;
-    if receipt.is_some() {
-        println!("✅ Endpoint removed successfully!");
+    let sender = choose_sender(out, signer, &config.network).await?;
+    let provider = Provider::<Http>::try_from(&config.network.rpc_url)
+        .context("Failed to create provider")?;
+    ensure_target_code(&provider, contract_address, &config.network.name, tx_args).await?;
+    let from = sender.address()?;
+    
+    // Ask for the password once for the whole batch
+    let client = if tx_args.dry_run {
+        None
+    } else {
+        Some(setup_client(&config, tx_args, unlock_sender(out, signer, sender)?).await?)
+    };
+    
+    let mut results = Vec::new();
+    let mut failures = Vec::new();
+    let mut skipped = 0;
+    for (i, entry) in entries.iter().enumerate() {
+        out.blank();
+        out.say(format!("[{}/{}] {}", i + 1, entries.len(), entry.url));
+        
+        if !remove && !force {
+            match endpoint_registered(&provider, contract_address, &entry.url).await {
+                Ok(true) => {
+                    out.say("⏭️  Already registered, skipped");
+                    skipped += 1;
+                    results.push(json!({ "line": entry.line, "url": entry.url, "status": "skipped", "reason": "already registered" }));
+                    continue;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    let error = format!("{:#}", e);
+                    out.say(format!("❌ Failed: {}", error));
+                    failures.push((entry, error.clone()));
+                    results.push(json!({ "line": entry.line, "url": entry.url, "status": "failed", "error": error }));
+                    continue;
+                }
+            }
+        }
+        
+        let data = if remove {
+            encode_remove_endpoint(&entry.url).build()
+        } else {
+            encode_add_endpoint_with_description(&entry.url, &entry.description).build()
+        };
+        
+        let outcome = match &client {
+            Some(client) => submit_call(out, client, contract_address, data, tx_args, config.network.gas_buffer_percent).await
+                .map(|outcome| outcome_json(&outcome)),
+            None => simulate(&provider, out, from, Some(contract_address), Bytes::from(data), tx_args).await
+                .map(|estimate| json!({
+                    "status": "simulated",
+                    "estimatedGas": estimate.gas.to_string(),
+                    "estimatedCostWei": estimate.wei.to_string(),
+                })),
+        };
+        
+        let result = match outcome {
+            Ok(mut details) => {
+                out.say("✅ OK");
+                details["line"] = json!(entry.line);
+                details["url"] = json!(entry.url);
+                details
+            }
+            Err(e) => {
+                let error = format!("{:#}", e);
+                out.say(format!("❌ Failed: {}", error));
+                failures.push((entry, error.clone()));
+                let mut details = match e.downcast_ref::<tx::Reverted>() {
+                    Some(tx::Reverted(outcome)) => outcome_json(outcome),
+                    None => json!({ "status": "failed" }),
+                };
+                details["line"] = json!(entry.line);
+                details["url"] = json!(entry.url);
+                details["error"] = json!(error);
+                details
+            }
+        };
+        results.push(result);
     }
     
+    out.blank();
+    let succeeded = entries.len() - failures.len() - skipped;
+    out.say(format!("=== Summary: {} succeeded, {} skipped, {} failed ===", succeeded, skipped, failures.len()));
+    for (entry, error) in &failures {
+        out.say(format!("  line {}: {} - {}", entry.line, entry.url, error));
+    }
+    out.result(json!({
+        "succeeded": succeeded,
+        "skipped": skipped,
+        "failed": failures.len(),
+        "results": results,
+    }));
+    
     Ok(())
 }
 
-async fn call_add_admin(contract: String, admin: String) -> Result<()> {
-    println!("Adding admin: {}", admin);
+/// Parse a hex address, or resolve it through ENS on the configured network if it ends in `.eth`
+async fn resolve_address(out: &Output, input: &str) -> Result<Address> {
+    if !polyendpoint_sdk::ens::is_ens_name(input) {
+        return input.parse().with_context(|| format!("Invalid address '{}'", input));
+    }
+
+    let config = load_config()?;
+    let provider = Provider::<Http>::try_from(&config.network.rpc_url)
+        .context("Failed to create provider")?;
+    let address = provider.resolve_name(input).await
+        .with_context(|| format!("Could not resolve ENS name '{}' on {}", input, config.network.name))?;
+    out.say(format!("Resolved {} -> {:#x}", input, address));
+    Ok(address)
+}
+
+async fn call_add_admin(out: &Output, signer: &SignerArgs, tx_args: &TxArgs, contract: String, admin: String) -> Result<()> {
+    out.say(format!("Adding admin: {}", admin));
+    
+    let admin_address = resolve_address(out, &admin).await?;
     
-    let config = Config::load("config.toml")?;
-    let (private_key, _password) = get_password_and_wallet().await?;
-    let client = setup_client(&config, &private_key).await?;
+    let data = encode_add_admin(&format!("{:#x}", admin_address))
+        .map_err(anyhow::Error::msg)?;
     
-    let contract_address: Address = contract.parse()?;
-    let admin_address: Address = admin.parse()?;
+    send_contract_call(out, signer, tx_args, &contract, data.build(), "✅ Admin added successfully!").await
+}
+
+async fn call_remove_admin(out: &Output, signer: &SignerArgs, tx_args: &TxArgs, contract: String, admin: String) -> Result<()> {
+    out.say(format!("Removing admin: {}", admin));
     
-    let method_id = ethers::utils::keccak256("addAdmin(address)")[0..4].to_vec();
-    let encoded = ethers::abi::encode(&[ethers::abi::Token::Address(admin_address)]);
-    let full_data = [&method_id[..], &encoded].concat();
+    let admin_address = resolve_address(out, &admin).await?;
     
-    let tx = TransactionRequest::new()
-        .to(contract_address)
-        .data(Bytes::from(full_data));
+    let data = encode_remove_admin(&format!("{:#x}", admin_address))
+        .map_err(anyhow::Error::msg)?;
     
-    println!("Sending transaction...");
-    let pending_tx = client.send_transaction(tx, None).await?;
-    println!("Transaction sent: {:?}", pending_tx.tx_hash());
+    send_contract_call(out, signer, tx_args, &contract, data.build(), "✅ Admin removed successfully!").await
+}
+
+async fn call_transfer_ownership(out: &Output, signer: &SignerArgs, tx_args: &TxArgs, contract: String, new_owner: String) -> Result<()> {
+    out.say(format!("Transferring ownership to: {}", new_owner));
+    
+    let new_owner_address = resolve_address(out, &new_owner).await?;
+    let data = encode_transfer_ownership(&format!("{:#x}", new_owner_address))
+        .map_err(anyhow::Error::msg)?;
     
-    let receipt = pending_tx.await?;
-    if receipt.is_some() {
-        println!("✅ Admin added successfully!");
+    send_contract_call(out, signer, tx_args, &contract, data.build(), "✅ Ownership transferred successfully!").await
+}
+
+async fn call_get_endpoints(out: &Output, contracts: Vec<String>) -> Result<()> {
+    let config = load_config()?;
+    
+    let mut results = serde_json::Map::new();
+    for contract in &contracts {
+        out.say(format!("Getting all endpoints from: {}", contract));
+        let contract_address: Address = contract.parse()
+            .with_context(|| format!("Invalid contract address '{}'", contract))?;
+        let endpoints = fetch_endpoints(&config.network, contract_address).await
+            .with_context(|| format!("Failed to query endpoints of {}", contract))?;
+        
+        out.say(format!("\n✅ Found {} endpoints:\n", endpoints.len()));
+        for (i, endpoint) in endpoints.iter().enumerate() {
+            if !endpoint.description.is_empty() {
+                out.say(format!("  {}. {} - {}", i + 1, endpoint.url, endpoint.description));
+            } else {
+                out.say(format!("  {}. {}", i + 1, endpoint.url));
+            }
+        }
+        if contracts.len() > 1 {
+            out.blank();
+        }
+        
+        let endpoints: Vec<_> = endpoints.iter()
+            .map(|endpoint| json!({ "url": endpoint.url, "description": endpoint.description }))
+            .collect();
+        results.insert(to_checksum(&contract_address, None), endpoints.into());
+    }
+    
+    // A single contract keeps the original shape; several are keyed by address
+    match results.values().next() {
+        Some(endpoints) if contracts.len() == 1 => out.result(json!({ "endpoints": endpoints })),
+        _ => out.result(serde_json::Value::Object(results)),
     }
     
     Ok(())
 }
 
-async fn call_remove_admin(contract: String, admin: String) -> Result<()> {
-    println!("Removing admin: {}", admin);
+/// Write every endpoint of `contract` to `path` in the JSON format
+/// `import-endpoints` (and `add-endpoints-file`) read back
+async fn export_endpoints(out: &Output, contract: &str, path: &std::path::Path) -> Result<()> {
+    let config = load_config()?;
+    let contract_address: Address = contract.parse()
+        .with_context(|| format!("Invalid contract address '{}'", contract))?;
+    
+    out.say(format!("Exporting endpoints from: {}", contract));
+    let endpoints: Vec<(String, String)> = fetch_endpoints(&config.network, contract_address).await
+        .with_context(|| format!("Failed to query endpoints of {}", contract))?
+        .into_iter()
+        .map(|endpoint| (endpoint.url, endpoint.description))
+        .collect();
+    batch::write_endpoints_file(path, &endpoints)?;
+    
+    out.say(format!("✅ Wrote {} endpoints to {}", endpoints.len(), path.display()));
+    out.result(json!({
+        "contract": to_checksum(&contract_address, None),
+        "network": config.network.name,
+        "count": endpoints.len(),
+        "file": path.display().to_string(),
+    }));
     
-    let config = Config::load("config.toml")?;
-    let (private_key, _password) = get_password_and_wallet().await?;
-    let client = setup_client(&config, &private_key).await?;
+    Ok(())
+}
+
+/// Read every endpoint of `contract` with the SDK client, which rejects a
+/// `getAllEndpoints` result whose url and description arrays differ in length
+async fn fetch_endpoints(network: &NetworkConfig, contract: Address) -> Result<Vec<EndpointInfo>> {
+    let client = PolyEndpointClient::builder()
+        .contract_address(format!("{:#x}", contract))
+        .rpc_urls([network.rpc_url.clone()])
+        .trace(TRACE_RPC.load(Ordering::Relaxed))
+        .build()?;
+    Ok(client.get_endpoints(&network.name).await?)
+}
+
+async fn watch_endpoints(out: &Output, contract: &str, interval: Duration) -> Result<()> {
+    let config = load_config()
+        .context("Failed to load config. Run 'init' first.")?;
+    let client = PolyEndpointClient::builder()
+        .contract_address(contract)
+        .rpc_urls([config.network.rpc_url])
+        .trace(TRACE_RPC.load(Ordering::Relaxed))
+        .build()?;
+    watch::watch(out, &client, &config.network.name, interval).await
+}
+
+async fn call_get_endpoint(out: &Output, contract: String, index: U256) -> Result<()> {
+    let mut data = method_id::get_endpoint().to_vec();
+    let mut word = [0u8; 32];
+    index.to_big_endian(&mut word);
+    data.extend_from_slice(&word);
+
+    let result = read_contract(&contract, data).await?;
+    let endpoint = decode_endpoint(&result).map_err(anyhow::Error::msg)?;
+
+    if endpoint.description.is_empty() {
+        out.say(&endpoint.url);
+    } else {
+        out.say(format!("{} - {}", endpoint.url, endpoint.description));
+    }
+    out.result(json!({ "index": index.to_string(), "url": endpoint.url, "description": endpoint.description }));
+    Ok(())
+}
+
+async fn call_get_count(out: &Output, contract: String) -> Result<()> {
+    let result = read_contract(&contract, encode_get_endpoint_count().build()).await?;
+    if result.len() < 32 {
+        anyhow::bail!("Invalid getEndpointCount result: expected 32 bytes, got {}", result.len());
+    }
+    let count = U256::from_big_endian(&result[..32]);
     
-    let contract_address: Address = contract.parse()?;
-    let admin_address: Address = admin.parse()?;
+    out.say(format!("Endpoint count: {}", count));
+    out.result(json!({ "count": count.to_string() }));
+    Ok(())
+}
+
+async fn call_has_endpoint(out: &Output, contract: String, url: String) -> Result<()> {
+    let result = read_contract(&contract, encode_has_endpoint(&url).build()).await?;
+    let exists = decode_bool(&result).map_err(anyhow::Error::msg)?;
     
-    let method_id = ethers::utils::keccak256("removeAdmin(address)")[0..4].to_vec();
-    let encoded = ethers::abi::encode(&[ethers::abi::Token::Address(admin_address)]);
-    let full_data = [&method_id[..], &encoded].concat();
+    out.say(exists.to_string());
+    out.result(json!({ "url": url, "exists": exists }));
+    Ok(())
+}
+
+/// `eth_call` read-only calldata against the contract on the configured network (no wallet needed)
+async fn read_contract(contract: &str, data: Vec<u8>) -> Result<Bytes> {
+    let config = load_config()
+        .context("Failed to load config. Run 'init' first.")?;
+    let contract_address: Address = contract.parse()
+        .context("Invalid contract address")?;
+    let provider = Provider::<Http>::try_from(&config.network.rpc_url)
+        .context("Failed to create provider")?;
     
-    let tx = TransactionRequest::new()
-        .to(contract_address)
-        .data(Bytes::from(full_data));
+    if contract_address.is_zero() {
+        anyhow::bail!("Contract address is the zero address; pass the deployed contract's address");
+    }
+
+    let tx = TransactionRequest::new().to(contract_address).data(data);
+    let result = provider.call(&tx.into(), None).await
+        .map_err(|e| anyhow::anyhow!("eth_call failed: {}", e))?;
+    if result.is_empty() {
+        anyhow::bail!("No contract at {:#x} on {} (the call returned no data)", contract_address, config.network.name);
+    }
+    Ok(result)
+}
+
+async fn call_owner(out: &Output, contract: String) -> Result<()> {
+    let result = read_contract(&contract, encode_owner().build()).await?;
+    let owner: Address = decode_address(&result)
+        .map_err(anyhow::Error::msg)?
+        .parse()?;
     
-    println!("Sending transaction...");
-    let pending_tx = client.send_transaction(tx, None).await?;
-    println!("Transaction sent: {:?}", pending_tx.tx_hash());
+    out.say(format!("Owner of {}: {}", contract, to_checksum(&owner, None)));
+    out.result(json!({ "contract": contract, "owner": to_checksum(&owner, None) }));
     
-    let receipt = pending_tx.await?;
-    if receipt.is_some() {
-        println!("✅ Admin removed successfully!");
+    Ok(())
+}
+
+async fn check_contract(out: &Output, address: &str) -> Result<()> {
+    let config = load_config()
+        .context("Failed to load config. Run 'init' first.")?;
+    let address: Address = address.parse()
+        .context("Invalid address")?;
+    let provider = Provider::<Http>::try_from(&config.network.rpc_url)
+        .context("Failed to create provider")?;
+    check_contract_code(out, &provider, address, &config.network.name).await
+}
+
+/// Fail unless `address` has code. Calls to an account without code
+/// succeed with no data, which otherwise looks like an empty contract.
+async fn check_contract_code<M: Middleware>(out: &Output, provider: &M, address: Address, network: &str) -> Result<()> {
+    let code = provider.get_code(address, None).await
+        .map_err(|e| anyhow::anyhow!("Failed to read code at {:?}: {}", address, e))?;
+    if code.is_empty() {
+        anyhow::bail!("No contract at {} on {}; it is a wallet or unused address", to_checksum(&address, None), network);
     }
     
+    out.say(format!("✅ {} is a contract on {} ({} bytes of code)", to_checksum(&address, None), network, code.len()));
+    out.result(json!({
+        "address": to_checksum(&address, None),
+        "network": network,
+        "isContract": true,
+        "codeSize": code.len(),
+    }));
     Ok(())
 }
 
-async fn call_get_endpoints(contract: String) -> Result<()> {
-    println!("Getting all endpoints from: {}", contract);
+/// Before sending to `address`, fail unless it has code on `network`, so a
+/// contract deployed on another chain isn't sent a transaction that does
+/// nothing. `--skip-code-check` turns this off.
+async fn ensure_target_code<M: Middleware>(provider: &M, address: Address, network: &str, tx_args: &TxArgs) -> Result<()> {
+    if tx_args.skip_code_check {
+        return Ok(());
+    }
+    let code = provider.get_code(address, None).await
+        .map_err(|e| anyhow::anyhow!("Failed to read code at {:?}: {}", address, e))?;
+    if code.is_empty() {
+        anyhow::bail!(
+            "No contract at {} on {}. Check the address and network, or pass --skip-code-check to send anyway.",
+            to_checksum(&address, None),
+            network
+        );
+    }
+    Ok(())
+}
+
+async fn call_is_admin(out: &Output, contract: String, address: String) -> Result<()> {
+    out.say(format!("Checking if address is admin: {}", address));
     
-    let config = Config::load("config.toml")?;
-    let contract_address: Address = contract.parse()?;
+    let check_address = resolve_address(out, &address).await?;
+    let data = encode_is_admin(&format!("{:#x}", check_address))
+        .map_err(anyhow::Error::msg)?;
     
-    // Call getAllEndpoints() view function
-    // Method ID: keccak256("getAllEndpoints()")[0:4]
-    let method_id = ethers::utils::keccak256("getAllEndpoints()")[0..4].to_vec();
-    let call_data = method_id;
+    let result = read_contract(&contract, data.build()).await?;
+    let is_admin = decode_bool(&result).map_err(anyhow::Error::msg)?;
     
-    // Use eth_call to query the contract
-    let request = serde_json::json!({
-        "jsonrpc": "2.0",
-        "method": "eth_call",
-        "params": [{
-            "to": format!("{:#x}", contract_address),
-            "data": format!("0x{}", hex::encode(&call_data))
-        }, "latest"],
-        "id": 1
-    });
+    out.say(is_admin.to_string());
+    out.result(json!({ "address": format!("{:#x}", check_address), "isAdmin": is_admin }));
+    
+    Ok(())
+}
+
+/// Parse an index given in decimal or as `0x`-prefixed hex, as explorers show it
+fn parse_index(value: &str) -> Result<U256, String> {
+    let value = value.trim();
+    let parsed = match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) if !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()) => U256::from_str_radix(hex, 16).ok(),
+        Some(_) => None,
+        None if !value.is_empty() && value.chars().all(|c| c.is_ascii_digit()) => U256::from_dec_str(value).ok(),
+        None => None,
+    };
+    parsed.ok_or_else(|| format!("expected a decimal or 0x-prefixed hex index, got '{}'", value))
+}
+
+/// Parse `--data` calldata given as `0x`-prefixed (or bare) hex
+fn parse_calldata(data: &str) -> Result<Vec<u8>> {
+    let data = data.trim();
+    hex::decode(data.strip_prefix("0x").unwrap_or(data))
+        .with_context(|| format!("Invalid --data '{}': expected hex calldata", data))
+}
+
+async fn call_raw(out: &Output, signer: &SignerArgs, tx_args: &TxArgs, contract: String, data: &str) -> Result<()> {
+    let calldata = parse_calldata(data)?;
     
-    let client = reqwest::Client::new();
-    let response: serde_json::Value = client
-        .post(&config.network.rpc_url)
-        .json(&request)
-        .send()
-        .await?
-        .json()
-        .await?;
+    out.say(format!("Sending {} bytes of calldata to {}", calldata.len(), contract));
+    send_contract_call(out, signer, tx_args, &contract, calldata, "✅ Raw call succeeded!").await
+}
+
+async fn call_raw_eth_call(out: &Output, contract: String, data: &str) -> Result<()> {
+    let calldata = parse_calldata(data)?;
+    let config = load_config()?;
+    let contract_address: Address = contract.parse()
+        .context("Invalid contract address")?;
+    let provider = Provider::<Http>::try_from(&config.network.rpc_url)
+        .context("Failed to create provider")?;
     
-    if let Some(result) = response["result"].as_str() {
-        let result_bytes = hex::decode(result.trim_start_matches("0x"))?;
-        
-        // Decode the result: (string[] memory, string[] memory)
-        // Updated signature: getAllEndpoints() returns (string[], string[])
-        let tokens = ethers::abi::decode(&[
-            ethers::abi::ParamType::Array(Box::new(ethers::abi::ParamType::String)),
-            ethers::abi::ParamType::Array(Box::new(ethers::abi::ParamType::String))
-        ], result_bytes.as_slice())?;
-        
-        if tokens.len() == 2 {
-            let urls = if let Some(ethers::abi::Token::Array(arr)) = tokens.first() {
-                arr.iter().filter_map(|token| {
-                    if let ethers::abi::Token::String(s) = token {
-                        Some(s.clone())
-                    } else {
-                        None
-                    }
-                }).collect::<Vec<_>>()
-            } else {
-                vec![]
-            };
-            
-            let descriptions = if let Some(ethers::abi::Token::Array(arr)) = tokens.get(1) {
-                arr.iter().filter_map(|token| {
-                    if let ethers::abi::Token::String(s) = token {
-                        Some(s.clone())
-                    } else {
-                        None
-                    }
-                }).collect::<Vec<_>>()
-            } else {
-                vec![]
-            };
-            
-            println!("\n✅ Found {} endpoints:\n", urls.len());
-            for (i, url) in urls.iter().enumerate() {
-                if let Some(desc) = descriptions.get(i) {
-                    if !desc.is_empty() {
-                        println!("  {}. {} - {}", i + 1, url, desc);
-                    } else {
-                        println!("  {}. {}", i + 1, url);
-                    }
-                } else {
-                    println!("  {}. {}", i + 1, url);
-                }
+    let tx = TransactionRequest::new().to(contract_address).data(calldata);
+    let result = match provider.call(&tx.into(), None).await {
+        Ok(result) => result,
+        Err(e) => {
+            let reason = e.as_error_response()
+                .and_then(|error| error.data.as_ref())
+                .and_then(|data| data.as_str())
+                .and_then(|data| hex::decode(data.trim_start_matches("0x")).ok())
+                .and_then(|data| decode_revert_reason(&data));
+            match reason {
+                Some(reason) => anyhow::bail!("Call reverted: {}", reason),
+                None => anyhow::bail!("eth_call failed: {}", e),
             }
-        } else {
-            println!("No endpoints found.");
         }
-    } else if let Some(error) = response["error"].as_object() {
-        eprintln!("RPC Error: {:?}", error);
+    };
+    
+    let result = format!("0x{}", hex::encode(&result));
+    out.say(&result);
+    out.result(json!({ "result": result }));
+    
+    Ok(())
+}
+
+/// Look `method` up in the artifact's ABI, encode `args` by its parameter
+/// types, and run it as a read or a transaction depending on its mutability
+async fn call_method(
+    out: &Output,
+    signer: &SignerArgs,
+    tx_args: &TxArgs,
+    contract: String,
+    method: &str,
+    args: &[String],
+) -> Result<()> {
+    let config = load_config()?;
+    let artifact = Artifact::load(&config.contract.bytecode_path)?;
+    let registry = AbiRegistry::from_abi(&artifact.abi)?;
+    let method = registry.method(method, args.len())?;
+    let calldata = encode_signature_call(&method.signature(), args)?;
+
+    if method.is_read_only() {
+        call_raw_eth_call(out, contract, &calldata).await
     } else {
-        println!("Failed to query endpoints: {:?}", response);
+        out.say(format!("Calling {} on {}", method.signature(), contract));
+        send_contract_call(out, signer, tx_args, &contract, parse_calldata(&calldata)?, "✅ Call succeeded!").await
     }
-    
+}
+
+/// Sign a transaction with a saved wallet and write it to `path`, without
+/// any RPC call: the chain ID comes from `--chain` or config.toml and every
+/// other field from the command line
+async fn sign_tx(out: &Output, signer: &SignerArgs, tx_args: &OfflineTxArgs, path: &std::path::Path) -> Result<()> {
+    let network = match CHAIN_OVERRIDE.get() {
+        Some(network) => network.clone(),
+        None => Config::load("config.toml")
+            .context("Failed to load config. Run 'init' or pass --chain.")?
+            .network,
+    };
+    let data = parse_calldata(&tx_args.data)?;
+    
+    let sender = choose_sender(out, signer, &network).await?;
+    let wallet = unlock_sender(out, signer, sender)?.with_chain_id(network.chain_id);
+    
+    let tx = tx_args.transaction(wallet.address(), network.chain_id, Bytes::from(data))?;
+    let raw = sign_raw(&wallet, &tx).await?;
+    std::fs::write(path, format!("0x{}\n", hex::encode(&raw)))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    
+    let tx_hash = H256::from(ethers::utils::keccak256(&raw));
+    out.say(format!(
+        "✅ Signed transaction {:#x} (nonce {}, chain {}) written to {}",
+        tx_hash, tx_args.nonce, network.chain_id, path.display()
+    ));
+    out.say(format!("Send it from an online machine with: broadcast --file {}", path.display()));
+    out.result(json!({
+        "txHash": format!("{:#x}", tx_hash),
+        "from": to_checksum(&wallet.address(), None),
+        "chainId": network.chain_id,
+        "file": path.display().to_string(),
+    }));
     Ok(())
 }
 
-async fn call_get_count(_contract: String) -> Result<()> {
-    println!("Getting endpoint count...");
-    println!("⚠️  Read operations temporarily disabled due to API changes");
-    println!("Use a block explorer to view contract state.");
+/// Send a raw transaction written by `sign-tx` to the configured network
+async fn broadcast(out: &Output, path: &std::path::Path, confirmations: usize) -> Result<()> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let raw = parse_calldata(&contents)
+        .with_context(|| format!("{} does not contain a hex transaction", path.display()))?;
+    let (tx, from) = decode_raw(&raw)?;
+    
+    let config = load_config()
+        .context("Failed to load config. Run 'init' first.")?;
+    let chain_id = tx.chain_id().map(|id| id.as_u64());
+    if chain_id.is_some_and(|id| id != config.network.chain_id) {
+        anyhow::bail!(
+            "Transaction was signed for chain ID {}, but the configured network {} is {}",
+            chain_id.unwrap_or_default(), config.network.name, config.network.chain_id
+        );
+    }
+    let provider = Provider::<Http>::try_from(&config.network.rpc_url)
+        .context("Failed to create provider")?;
+    let provider = match config.network.poll_ms {
+        Some(ms) => provider.interval(Duration::from_millis(ms)),
+        None => provider,
+    };
+    verify_chain_id(&provider, &config).await?;
+    
+    out.say(format!(
+        "Broadcasting transaction from {} (nonce {}) to {}",
+        to_checksum(&from, None),
+        tx.nonce().copied().unwrap_or_default(),
+        config.network.name
+    ));
+    let pending_tx = provider.send_raw_transaction(Bytes::from(raw)).await
+        .map_err(|e| anyhow::anyhow!("Failed to broadcast transaction: {}", e))?;
+    let tx_hash = pending_tx.tx_hash();
+    out.say(format!("Transaction sent: {:?}", tx_hash));
+    
+    let receipt = wait_for_receipt(out, pending_tx, confirmations).await?;
+    out.result(outcome_json(&tx_outcome(out, &receipt)?));
     Ok(())
 }
 
-async fn call_has_endpoint(_contract: String, _url: String) -> Result<()> {
-    println!("⚠️  This feature not yet implemented");
+/// Sign `message` with EIP-191 `personal_sign`. The signature isn't tied to
+/// a chain, so the wallet's network restriction doesn't apply.
+async fn sign_message(out: &Output, signer: &SignerArgs, message: &[u8]) -> Result<()> {
+    let sender = match ledger_sender(out, signer, 1).await? {
+        Some(sender) => sender,
+        None => Sender::Wallet(choose_wallet(out, signer)?),
+    };
+    let wallet = unlock_sender(out, signer, sender)?;
+    let signature = wallet.sign_message(message).await
+        .context("Failed to sign message")?;
+    let signature = format!("0x{}", hex::encode(signature.to_vec()));
+    
+    out.say(&signature);
+    out.result(json!({
+        "address": to_checksum(&wallet.address(), None),
+        "signature": signature,
+    }));
     Ok(())
 }
 
-async fn call_is_admin(contract: String, address: String) -> Result<()> {
-    println!("Checking if address is admin: {}", address);
-    
-    let config = Config::load("config.toml")?;
-    let contract_address: Address = contract.parse()?;
-    let check_address: Address = address.parse()?;
-    
-    // Call admins(address) public mapping
-    // Method ID: keccak256("admins(address)")[0:4]
-    let method_id = ethers::utils::keccak256("admins(address)")[0..4].to_vec();
-    
-    // Encode address parameter
-    let encoded = ethers::abi::encode(&[ethers::abi::Token::Address(check_address)]);
-    let call_data = [&method_id[..], &encoded].concat();
-    
-    // Use eth_call to query the contract
-    let request = serde_json::json!({
-        "jsonrpc": "2.0",
-        "method": "eth_call",
-        "params": [{
-            "to": format!("{:#x}", contract_address),
-            "data": format!("0x{}", hex::encode(&call_data))
-        }, "latest"],
-        "id": 1
-    });
-    
-    let client = reqwest::Client::new();
-    let response: serde_json::Value = client
-        .post(&config.network.rpc_url)
-        .json(&request)
-        .send()
-        .await?
-        .json()
-        .await?;
+fn verify_message(out: &Output, message: &[u8], signature: &str, expected: Option<&str>) -> Result<()> {
+    let signer = recover_signer(message, signature)?;
+    if let Some(expected) = expected {
+        let expected: Address = expected.parse()
+            .with_context(|| format!("Invalid address '{}'", expected))?;
+        if signer != expected {
+            anyhow::bail!(
+                "Signature was made by {}, not {}",
+                to_checksum(&signer, None),
+                to_checksum(&expected, None)
+            );
+        }
+    }
     
-    if let Some(result) = response["result"].as_str() {
-        let result_bytes = hex::decode(result.trim_start_matches("0x"))?;
-        
-        // Decode the result: bool
-        let tokens = ethers::abi::decode(&[ethers::abi::ParamType::Bool], result_bytes.as_slice())?;
-        
-        if let Some(ethers::abi::Token::Bool(is_admin)) = tokens.first() {
-            if *is_admin {
-                println!("✅ {} IS an admin", address);
-            } else {
-                println!("❌ {} is NOT an admin", address);
-            }
+    out.say(format!("✅ Signed by {}", to_checksum(&signer, None)));
+    out.result(json!({ "signer": to_checksum(&signer, None) }));
+    Ok(())
+}
+
+/// The address whose `personal_sign` signature over `message` is `signature`
+fn recover_signer(message: &[u8], signature: &str) -> Result<Address> {
+    let signature = Signature::from_str(signature.trim())
+        .map_err(|e| anyhow::anyhow!("Invalid signature '{}': {}", signature, e))?;
+    signature.recover(message)
+        .map_err(|e| anyhow::anyhow!("Failed to recover signer: {}", e))
+}
+
+/// Print the selector of `signature`, or the calldata for `args` if given
+fn print_selector(out: &Output, signature: &str, args: Option<&[String]>) -> Result<()> {
+    let signature: String = signature.split_whitespace().collect();
+    let selector = format!("0x{}", hex::encode(selector(&signature)));
+    match args {
+        None => {
+            out.say(&selector);
+            out.result(json!({ "signature": signature, "selector": selector }));
+        }
+        Some(args) => {
+            let calldata = encode_signature_call(&signature, args)?;
+            out.say(&calldata);
+            out.result(json!({ "signature": signature, "selector": selector, "calldata": calldata }));
         }
-    } else {
-        println!("Failed to query admin status: {:?}", response);
     }
+    Ok(())
+}
+
+/// Calldata for `signature` with `args` parsed by the parameter types in the
+/// signature. Supports the types `AbiToken` can encode.
+fn encode_signature_call(signature: &str, args: &[String]) -> Result<String> {
+    let params = signature
+        .split_once('(')
+        .and_then(|(_, rest)| rest.strip_suffix(')'))
+        .with_context(|| format!("Invalid signature '{}': expected name(type,...)", signature))?;
+    let types: Vec<&str> = if params.is_empty() { Vec::new() } else { params.split(',').collect() };
+    if types.len() != args.len() {
+        anyhow::bail!("{} takes {} argument(s), got {}", signature, types.len(), args.len());
+    }
+
+    let tokens = types
+        .iter()
+        .zip(args)
+        .map(|(kind, arg)| abi_token(kind, arg).with_context(|| format!("Invalid {} argument '{}'", kind, arg)))
+        .collect::<Result<Vec<_>>>()?;
+    let data = encode_call(signature, &tokens).map_err(|e| anyhow::anyhow!(e))?;
+    Ok(data.to_hex())
+}
+
+/// Parse one command-line argument as a value of ABI type `kind`
+fn abi_token(kind: &str, arg: &str) -> Result<AbiToken> {
+    let hex_bytes = |arg: &str| hex::decode(arg.strip_prefix("0x").unwrap_or(arg));
+    Ok(match kind {
+        "address" => AbiToken::Address(format!("{:?}", arg.parse::<Address>()?)),
+        "uint256" => AbiToken::Uint(arg.parse()?),
+        "bool" => AbiToken::Bool(arg.parse()?),
+        "bytes32" => AbiToken::FixedBytes32(
+            hex_bytes(arg)?.try_into().map_err(|_| anyhow::anyhow!("expected 32 bytes"))?,
+        ),
+        "string" => AbiToken::String(arg.to_string()),
+        "bytes" => AbiToken::Bytes(hex_bytes(arg)?),
+        _ => anyhow::bail!("type {} is not supported (use address, uint256, bool, bytes32, string or bytes)", kind),
+    })
+}
+
+/// Prompt for a private key and print its address; nothing is written to disk
+fn address_from_key(out: &Output) -> Result<()> {
+    let private_key = prompt_password("Enter your private key (with or without 0x): ")
+        .context("Failed to read private key")?;
+    let address = key_address(&private_key)?;
     
+    out.say(to_checksum(&address, None));
+    out.result(json!({ "address": to_checksum(&address, None) }));
     Ok(())
 }
 
-async fn import_key() -> Result<()> {
-    println!("=== Private Key Import ===");
-    println!();
+/// The address of a hex private key, with or without `0x`
+fn key_address(private_key: &str) -> Result<Address> {
+    let private_key = private_key.trim();
+    let wallet = LocalWallet::from_str(private_key.strip_prefix("0x").unwrap_or(private_key))
+        .map_err(|e| anyhow::anyhow!("Invalid private key: {}", e))?;
+    Ok(wallet.address())
+}
+
+async fn import_key(out: &Output, kdf: Kdf) -> Result<()> {
+    out.say("=== Private Key Import ===");
+    out.blank();
     
     // Get private key from user
     let mut private_key = prompt_password("Enter your private key (with or without 0x): ")
@@ -792,8 +2330,8 @@ async fn import_key() -> Result<()> {
     }
     
     // Encrypt the private key
-    println!("Encrypting private key...");
-    let encrypted_key = encrypt_private_key(&private_key, &password)
+    out.say("Encrypting private key...");
+    let encrypted_key = encrypt_private_key(&private_key, &password, kdf)
         .context("Failed to encrypt private key")?;
     
     // Get address from private key
@@ -804,7 +2342,7 @@ async fn import_key() -> Result<()> {
     let mut config = match Config::load("rust/config.toml") {
         Ok(cfg) => cfg,
         Err(_) => {
-            println!("Creating new config.toml...");
+            out.say("Creating new config.toml...");
             let template = include_str!("../config.toml");
             std::fs::write("rust/config.toml", template)?;
             Config::load("rust/config.toml")?
@@ -819,86 +2357,603 @@ async fn import_key() -> Result<()> {
     config.save("rust/config.toml")
         .context("Failed to save config")?;
     
-    println!();
-    println!("✓ Private key encrypted and saved!");
-    println!("✓ Address: {:#x}", address);
-    println!("✓ Configuration saved to rust/config.toml");
-    println!();
-    println!("Remember your password - you'll need it to deploy the contract.");
+    out.blank();
+    out.say("✓ Private key encrypted and saved!");
+    out.say(format!("✓ Address: {:#x}", address));
+    out.say("✓ Configuration saved to rust/config.toml");
+    out.blank();
+    out.say("Remember your password - you'll need it to deploy the contract.");
+    out.result(json!({ "address": format!("{:#x}", address), "config": "rust/config.toml" }));
     
     Ok(())
 }
 
-async fn deploy_contract() -> Result<()> {
-    // Select wallet interactively
-    let (encrypted_key, _wallet_address) = select_wallet_interactive().await?;
+/// Where and how `deploy --create2` deploys
+#[derive(Debug, Clone, Copy)]
+struct Create2 {
+    factory: Address,
+    salt: H256,
+}
+
+impl Create2 {
+    fn parse(salt: &str, factory: Option<&str>) -> Result<Self> {
+        let salt = salt.parse::<H256>()
+            .with_context(|| format!("Invalid --salt '{}': expected 32 bytes of hex", salt))?;
+        let factory = factory.unwrap_or(DEFAULT_CREATE2_FACTORY);
+        let factory = factory.parse::<Address>()
+            .with_context(|| format!("Invalid --factory '{}'", factory))?;
+        Ok(Self { factory, salt })
+    }
+
+    /// Address the factory will deploy `init_code` to
+    fn predicted_address(&self, init_code: &[u8]) -> Address {
+        get_create2_address(self.factory, self.salt.as_bytes(), init_code)
+    }
+
+    /// Calldata for the factory: the salt followed by the init code
+    fn calldata(&self, init_code: &[u8]) -> Vec<u8> {
+        [self.salt.as_bytes(), init_code].concat()
+    }
+}
+
+/// Check that the factory exists and nothing is deployed at `predicted` yet
+async fn check_create2_target<M: Middleware>(client: &M, create2: &Create2, predicted: Address) -> Result<()> {
+    let factory_code = client.get_code(create2.factory, None).await
+        .map_err(|e| anyhow::anyhow!("Failed to read code at factory {:?}: {}", create2.factory, e))?;
+    if factory_code.is_empty() {
+        anyhow::bail!("No CREATE2 factory deployed at {:?} on this network", create2.factory);
+    }
     
+    let existing_code = client.get_code(predicted, None).await
+        .map_err(|e| anyhow::anyhow!("Failed to read code at {:?}: {}", predicted, e))?;
+    if !existing_code.is_empty() {
+        anyhow::bail!("A contract already exists at the predicted address {:?}; use a different --salt", predicted);
+    }
+    Ok(())
+}
+
+async fn deploy_contract(out: &Output, signer: &SignerArgs, tx_args: &TxArgs, create2: Option<Create2>) -> Result<()> {
     // Load config
-    let config = Config::load("config.toml")
+    let config = load_config()
         .context("Failed to load config. Run 'init' first.")?;
     
-    // Get password from user
-    println!();
-    println!("=== Deploy Contract ===");
-    println!();
-    let password = prompt_password("Enter your password: ")
-        .context("Failed to read password")?;
+    // Select wallet interactively
+    let sender = choose_sender(out, signer, &config.network).await?;
     
-    // Decrypt private key
-    println!("Decrypting private key...");
-    let private_key = decrypt_private_key(&encrypted_key, &password)
-        .context("Failed to decrypt private key. Wrong password?")?;
+    out.blank();
+    out.say("=== Deploy Contract ===");
+    out.blank();
+    
+    // Read contract artifacts
+    out.say("Reading contract artifacts...");
+    let artifact = Artifact::load(&config.contract.bytecode_path)?;
+    if artifact.constructor_inputs() > 0 {
+        anyhow::bail!(
+            "The contract's constructor takes {} argument(s), but deploy doesn't pass constructor arguments",
+            artifact.constructor_inputs()
+        );
+    }
+    let bytecode_bytes = artifact.bytecode;
+    let deployed_bytecode = artifact.deployed_bytecode.as_deref();
     
     // Setup provider
     let provider = Provider::<Http>::try_from(&config.network.rpc_url)
         .context("Failed to create provider")?;
     
-    let wallet = LocalWallet::from_str(&private_key)
-        .context("Failed to create wallet")?
-        .with_chain_id(config.network.chain_id);
-    
-    let client = SignerMiddleware::new(provider, wallet);
-    
-    // Read contract artifacts
-    println!("Reading contract artifacts...");
-    let artifact_str = std::fs::read_to_string(&config.contract.bytecode_path)?;
-    let artifact: serde_json::Value = serde_json::from_str(&artifact_str)?;
+    // A CREATE2 deployment is a call to the factory instead of a creation transaction
+    let (to, data, predicted) = match &create2 {
+        Some(create2) => {
+            let predicted = create2.predicted_address(&bytecode_bytes);
+            out.say(format!("CREATE2 factory: {:?}", create2.factory));
+            out.say(format!("Predicted contract address: {}", to_checksum(&predicted, None)));
+            check_create2_target(&provider, create2, predicted).await?;
+            (Some(create2.factory), create2.calldata(&bytecode_bytes), Some(predicted))
+        }
+        None => (None, bytecode_bytes, None),
+    };
     
-    let bytecode = artifact["bytecode"]
-        .as_str()
-        .context("No bytecode found")?;
+    if tx_args.dry_run {
+        return dry_run(&provider, out, sender.address()?, to, Bytes::from(data), tx_args).await;
+    }
     
-    println!("Deploying contract to {}...", config.network.name);
-    println!("RPC URL: {}", config.network.rpc_url);
+    // Decrypt the private key or confirm the Ledger account
+    let tx_signer = unlock_sender(out, signer, sender)?;
+    let client = signer_client(with_poll_interval(provider, &config, tx_args), &config, tx_signer).await?;
     
-    let bytecode_bytes = hex::decode(bytecode.strip_prefix("0x").unwrap_or(bytecode))?;
+    out.say(format!("Deploying contract to {}...", config.network.name));
+    out.say(format!("RPC URL: {}", config.network.rpc_url));
     
     let deployer_address = client.address();
-    println!("Deploying with wallet: {:?}", deployer_address);
+    out.say(format!("Deploying with wallet: {:?}", deployer_address));
+    
+    let mut tx = build_transaction(&client, out, to, Bytes::from(data), tx_args).await?;
+    tx.set_from(deployer_address);
+    set_nonce(&client, out, &mut tx, tx_args).await?;
     
-    let tx = TransactionRequest::new().data(Bytes::from(bytecode_bytes));
+    let estimate = estimate_cost(&client, out, &tx).await?;
+    tx.set_gas(gas_limit(out, estimate.gas, tx_args, config.network.gas_buffer_percent));
     
-    println!("Sending deployment transaction...");
+    out.say("Sending deployment transaction...");
     let pending_tx = client.send_transaction(tx, None).await?;
-    println!("Transaction sent: {:?}", pending_tx.tx_hash());
-    
-    println!("Waiting for confirmation...");
-    let receipt = pending_tx.await?;
-    
-    if let Some(receipt) = receipt {
-        if let Some(contract_address) = receipt.contract_address {
-            println!();
-            println!("✓ Contract deployed successfully!");
-            println!("Contract address: {:?}", contract_address);
-            println!();
-            println!("You can now use this address with other commands:");
-            println!("  cargo run -- add-endpoint --contract {:?} --url https://example.com", contract_address);
-        } else {
-            println!("⚠ Contract deployed but no contract address in receipt");
+    let tx_hash = pending_tx.tx_hash();
+    out.say(format!("Transaction sent: {:?}", tx_hash));
+    
+    let receipt = wait_for_receipt(out, pending_tx, tx_args.confirmations).await?;
+    let outcome = tx_outcome(out, &receipt)?;
+    
+    let contract_address = match (predicted, &create2) {
+        (Some(predicted), Some(create2)) => {
+            verify_create2_deployment(out, &client, create2, predicted, &receipt).await?;
+            predicted
+        }
+        _ => receipt.contract_address
+            .with_context(|| format!("Contract deployed but no contract address in receipt (tx {:#x})", tx_hash))?,
+    };
+    
+    // The deployment already succeeded, so a mismatch is only a warning
+    let code_verified = match deployed_bytecode {
+        Some(expected) => verify_deployed_code(out, &client, contract_address, expected).await,
+        None => {
+            out.say("⚠️  The artifact has no deployedBytecode, so the deployed code was not checked.");
+            None
         }
+    };
+    
+    out.blank();
+    out.say("✓ Contract deployed successfully!");
+    out.say(format!("Contract address: {:?}", contract_address));
+    
+    // The deployment already succeeded, so a config problem is only a warning
+    let saved = save_default_contract(out, &config.network.name, contract_address)
+        .unwrap_or_else(|e| {
+            out.say(format!("⚠️  Could not save the contract address to config.toml ({:#})", e));
+            false
+        });
+    
+    out.blank();
+    if saved {
+        out.say("Commands now use this contract when --contract is left out:");
+        out.say("  cargo run -- add-endpoint --url https://example.com");
+        out.say("  cargo run -- add-admin --admin 0x...");
+        out.say("  cargo run -- get-endpoints");
     } else {
-        println!("⚠ No receipt received");
+        let contract = to_checksum(&contract_address, None);
+        out.say("You can now use this address with other commands:");
+        out.say(format!("  cargo run -- add-endpoint --contract {} --url https://example.com", contract));
+        out.say(format!("  cargo run -- add-admin --contract {} --admin 0x...", contract));
+        out.say(format!("  cargo run -- get-endpoints --contract {}", contract));
     }
+    let mut result = outcome_json(&outcome);
+    result["contractAddress"] = json!(format!("{:#x}", contract_address));
+    result["network"] = json!(config.network.name);
+    result["chainId"] = json!(config.network.chain_id);
+    result["create2"] = json!(create2.is_some());
+    result["codeVerified"] = json!(code_verified);
+    result["savedToConfig"] = json!(saved);
+    out.result(result);
     
     Ok(())
 }
+
+/// Save `address` as the `default_contract` of the network in config.toml,
+/// asking before replacing a different saved address. Returns whether it was
+/// saved; nothing is written when `--chain` picked a network other than the
+/// configured one.
+fn save_default_contract(out: &Output, network: &str, address: Address) -> Result<bool> {
+    let mut config = Config::load("config.toml")?;
+    if config.network.name != network {
+        out.say(format!("config.toml is set up for {}, so the address was not saved.", config.network.name));
+        return Ok(false);
+    }
+    
+    let address = to_checksum(&address, None);
+    if let Some(saved) = config.network.default_contract.as_deref().filter(|saved| !saved.eq_ignore_ascii_case(&address)) {
+        out.prompt(format!("config.toml already has contract {} for {}. Replace it? [y/N]: ", saved, network))?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            out.say("Kept the saved contract address.");
+            return Ok(false);
+        }
+    }
+    
+    config.network.default_contract = Some(address);
+    config.save("config.toml")?;
+    out.say(format!("✓ Saved as the default contract for {} in config.toml", network));
+    Ok(true)
+}
+
+/// Check that a CREATE2 deployment landed at the predicted address.
+///
+/// A factory call has no `contract_address` in its receipt, so the code at
+/// the predicted address is checked instead. Also warns when the factory
+/// ended up as the owner, which happens with contracts (like `PolyEndpoint`)
+/// that make `msg.sender` the owner.
+async fn verify_create2_deployment<M: Middleware>(
+    out: &Output,
+    client: &M,
+    create2: &Create2,
+    predicted: Address,
+    receipt: &TransactionReceipt,
+) -> Result<()> {
+    if let Some(address) = receipt.contract_address {
+        if address != predicted {
+            anyhow::bail!("Receipt contract address {:?} does not match the predicted {:?}", address, predicted);
+        }
+    }
+    
+    let code = client.get_code(predicted, None).await
+        .map_err(|e| anyhow::anyhow!("Failed to read code at {:?}: {}", predicted, e))?;
+    if code.is_empty() {
+        anyhow::bail!("Deployment transaction succeeded but there is no contract at the predicted address {:?}", predicted);
+    }
+    
+    let owner_call = TransactionRequest::new().to(predicted).data(encode_owner().build());
+    if let Ok(result) = client.call(&owner_call.into(), None).await {
+        if decode_address(&result).ok().and_then(|owner| owner.parse::<Address>().ok()) == Some(create2.factory) {
+            out.say("⚠️  The CREATE2 factory is the contract owner, so no wallet can administer it.");
+            out.say("    This contract sets its owner to msg.sender; deploy it without --create2 instead.");
+        }
+    }
+    
+    Ok(())
+}
+
+/// Compare the code at `address` with the artifact's `deployedBytecode`,
+/// warning on a mismatch. Returns whether they match, or `None` if the check
+/// couldn't be made.
+async fn verify_deployed_code<M: Middleware>(out: &Output, client: &M, address: Address, expected: &str) -> Option<bool> {
+    let expected = match hex::decode(expected.strip_prefix("0x").unwrap_or(expected)) {
+        Ok(expected) => expected,
+        Err(_) => {
+            out.say("⚠️  The artifact's deployedBytecode is not plain hex (unlinked libraries?), so the deployed code was not checked.");
+            return None;
+        }
+    };
+    let code = match client.get_code(address, None).await {
+        Ok(code) => code,
+        Err(e) => {
+            out.say(format!("⚠️  Could not read the deployed code to check it ({})", e));
+            return None;
+        }
+    };
+    
+    let matches = code_matches(&code, &expected);
+    if matches {
+        out.say("✓ Deployed code matches the artifact");
+    } else {
+        out.say(format!(
+            "⚠️  The code at {:?} ({} bytes) does not match the artifact's deployedBytecode ({} bytes).",
+            address,
+            code.len(),
+            expected.len()
+        ));
+        out.say("    Check that the artifact is the one you meant to deploy, and that the deployment wasn't reorged out.");
+    }
+    Some(matches)
+}
+
+/// Whether two runtime bytecodes are the same apart from the CBOR metadata
+/// solc appends, which changes with source paths and compiler settings that
+/// don't affect behaviour
+fn code_matches(deployed: &[u8], expected: &[u8]) -> bool {
+    !expected.is_empty() && strip_metadata(deployed) == strip_metadata(expected)
+}
+
+/// `code` without its trailing metadata: a CBOR map followed by its length
+/// as a big-endian `u16`. Code without recognisable metadata is returned whole.
+fn strip_metadata(code: &[u8]) -> &[u8] {
+    let Some(split) = code.len().checked_sub(2) else {
+        return code;
+    };
+    let len = usize::from(u16::from_be_bytes([code[split], code[split + 1]]));
+    match split.checked_sub(len) {
+        // CBOR maps with up to 23 entries start with 0xa0 + count
+        Some(start) if len > 0 && (0xa0..=0xb7).contains(&code[start]) => &code[..start],
+        _ => code,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create2() {
+        // EIP-1014 example 0
+        let create2 = Create2::parse(&format!("0x{}", "00".repeat(32)), Some("0x0000000000000000000000000000000000000000")).unwrap();
+        assert_eq!(
+            create2.predicted_address(&[0x00]),
+            "0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38".parse::<Address>().unwrap()
+        );
+        assert_eq!(create2.calldata(&[0xaa]).len(), 33);
+
+        let create2 = Create2::parse(&format!("0x{}", "11".repeat(32)), None).unwrap();
+        assert_eq!(create2.factory, DEFAULT_CREATE2_FACTORY.parse::<Address>().unwrap());
+
+        assert!(Create2::parse("0x1234", None).is_err());
+        assert!(Create2::parse(&format!("0x{}", "00".repeat(32)), Some("0xABC")).is_err());
+    }
+    /// Anvil's first dev account
+    const TEST_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    fn test_config(chain_id: u64) -> Config {
+        Config {
+            network: NetworkConfig { chain_id, ..default_network() },
+            deployer: default_deployer(),
+            contract: default_contract(),
+            active_wallet: None,
+        }
+    }
+
+    fn test_tx_args() -> TxArgs {
+        TxArgs {
+            max_fee: None,
+            priority_fee: None,
+            dry_run: false,
+            confirmations: 1,
+            nonce: None,
+            poll_ms: None,
+            value: U256::zero(),
+            gas_limit: None,
+            gas_buffer: None,
+            skip_code_check: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_endpoint_calldata() {
+        use crate::mock::MockProvider;
+        use ethers::abi::{encode, Token};
+
+        let mock = MockProvider::new(1337);
+        let provider = Provider::new(mock.clone()).interval(Duration::from_millis(1));
+        let client = signer_client(provider, &test_config(1337), TxSigner::Local(TEST_KEY.parse().unwrap())).await.unwrap();
+
+        let contract: Address = "0x1234567890123456789012345678901234567890".parse().unwrap();
+        let data = encode_add_endpoint_with_description("https://rpc.example.com", "Primary").build();
+        let tx_args = TxArgs { value: U256::from(12345), ..test_tx_args() };
+        let outcome = submit_call(&Output::new(true), &client, contract, data.clone(), &tx_args, None)
+            .await
+            .unwrap();
+        assert_eq!(outcome.block_number, 1);
+        assert_eq!(outcome.gas_used, 100_000);
+        assert!(outcome.succeeded());
+        assert!(outcome.fee().is_some());
+        assert_eq!(outcome_json(&outcome)["status"], "confirmed");
+
+        let sent = mock.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(format!("{:#x}", sent[0].hash), outcome.hash);
+        assert_eq!(sent[0].from, "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".parse::<Address>().unwrap());
+        assert_eq!(sent[0].to, Some(contract));
+        assert_eq!(sent[0].value, U256::from(12345));
+        // The mock's 100000 gas estimate plus the default 20% buffer
+        assert_eq!(sent[0].gas, U256::from(120_000));
+
+        // addEndpoint(string,string)
+        let expected = [
+            &hex::decode("2c986cce").unwrap()[..],
+            &encode(&[Token::String("https://rpc.example.com".into()), Token::String("Primary".into())]),
+        ]
+        .concat();
+        assert_eq!(sent[0].data.to_vec(), expected);
+
+        let tx_args = TxArgs { gas_limit: Some(250_000), ..test_tx_args() };
+        submit_call(&Output::new(true), &client, contract, data.clone(), &tx_args, Some(50)).await.unwrap();
+        assert_eq!(mock.sent()[1].gas, U256::from(250_000));
+
+        // A mined revert is an error that still carries the outcome
+        mock.set_reverting(true);
+        let err = submit_call(&Output::new(true), &client, contract, data, &test_tx_args(), None).await.unwrap_err();
+        let tx::Reverted(outcome) = err.downcast_ref::<tx::Reverted>().unwrap();
+        assert_eq!(outcome.hash, format!("{:#x}", mock.sent()[2].hash));
+        assert_eq!(outcome.gas_used, 100_000);
+        assert_eq!(outcome_json(outcome)["status"], "reverted");
+    }
+
+    #[test]
+    fn test_unlock_wallet_checks_address() {
+        let encrypted = encrypt_private_key(TEST_KEY, "password", Kdf::Sha256).unwrap();
+        let address = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+        assert_eq!(unlock_wallet(&encrypted, address, "password").unwrap(), format!("0x{}", TEST_KEY));
+        let err = unlock_wallet(&encrypted, address, "wrong").unwrap_err();
+        assert!(err.to_string().contains("Check your password"), "{}", err);
+        let err = unlock_wallet(&encrypted[2..], address, "password").unwrap_err();
+        assert!(err.to_string().contains("Check the file"), "{}", err);
+
+        let err = unlock_wallet(&encrypted, "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed", "password").unwrap_err();
+        assert!(err.to_string().contains("refusing to sign"), "{}", err);
+    }
+
+    #[test]
+    fn test_encode_signature_call() {
+        let args = ["https://rpc.example.com".to_string(), "Primary".to_string()];
+        assert_eq!(
+            encode_signature_call("addEndpoint(string,string)", &args).unwrap(),
+            encode_add_endpoint_with_description(&args[0], &args[1]).to_hex()
+        );
+        assert_eq!(encode_signature_call("owner()", &[]).unwrap(), encode_owner().to_hex());
+
+        let admin = ["0x1234567890123456789012345678901234567890".to_string()];
+        assert_eq!(
+            encode_signature_call("admins(address)", &admin).unwrap(),
+            encode_is_admin(&admin[0]).unwrap().to_hex()
+        );
+
+        assert!(encode_signature_call("owner()", &admin).is_err());
+        assert!(encode_signature_call("admins(address)", &["0x1234".to_string()]).is_err());
+        assert!(encode_signature_call("f(uint8)", &["1".to_string()]).is_err());
+        assert!(encode_signature_call("no-parens", &[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_recover_signer() {
+        let wallet = LocalWallet::from_str(TEST_KEY).unwrap();
+        let signature = wallet.sign_message(b"hello\x00\xff").await.unwrap();
+        let hex_signature = format!("0x{}", hex::encode(signature.to_vec()));
+        assert_eq!(hex_signature.len(), 2 + 65 * 2);
+        
+        assert_eq!(recover_signer(b"hello\x00\xff", &hex_signature).unwrap(), wallet.address());
+        assert_ne!(recover_signer(b"hello", &hex_signature).unwrap(), wallet.address());
+        assert!(recover_signer(b"hello", "0x1234").is_err());
+        assert!(recover_signer(b"hello", "not hex").is_err());
+    }
+
+    #[test]
+    fn test_parse_index() {
+        assert_eq!(parse_index("5").unwrap(), U256::from(5));
+        assert_eq!(parse_index("0x5").unwrap(), U256::from(5));
+        assert_eq!(parse_index(" 0X1f ").unwrap(), U256::from(31));
+        assert_eq!(parse_index("18446744073709551616").unwrap(), U256::from(u64::MAX) + 1);
+        assert_eq!(parse_index("0x10000000000000000").unwrap(), U256::from(u64::MAX) + 1);
+
+        for invalid in ["", "0x", "-1", "+5", "5.0", "abc", "0xg1", "1e3", "0x-1"] {
+            assert!(parse_index(invalid).is_err(), "{:?}", invalid);
+        }
+        let overflow = format!("0x1{}", "0".repeat(64));
+        assert!(parse_index(&overflow).is_err());
+        assert!(parse_index(&"9".repeat(80)).is_err());
+    }
+
+    #[test]
+    fn test_key_address() {
+        let address: Address = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".parse().unwrap();
+        assert_eq!(key_address(TEST_KEY).unwrap(), address);
+        assert_eq!(key_address(&format!(" 0x{}\n", TEST_KEY)).unwrap(), address);
+        assert!(key_address("0x1234").is_err());
+        assert!(key_address("").is_err());
+    }
+
+    #[test]
+    fn test_resolve_network() {
+        let network = resolve_network("base-sepolia").unwrap();
+        assert_eq!(network.chain_id, 84532);
+        assert_eq!(network.rpc_url, "https://sepolia.base.org");
+
+        let err = resolve_network("not-a-chain").unwrap_err().to_string();
+        assert!(err.contains("Unknown network 'not-a-chain'"), "{}", err);
+        assert!(err.contains("base-sepolia"), "{}", err);
+    }
+
+    #[test]
+    fn test_contract_or_default() {
+        let saved = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let mut network = default_network();
+        let err = contract_or_default(None, &network).unwrap_err().to_string();
+        assert!(err.contains("--contract") && err.contains(&network.name), "{}", err);
+
+        network.default_contract = Some(saved.to_string());
+        assert_eq!(contract_or_default(None, &network).unwrap(), saved);
+        assert_eq!(contract_or_default(Some("0x1".to_string()), &network).unwrap(), "0x1");
+    }
+
+    #[tokio::test]
+    async fn test_ping_provider() {
+        let network = NetworkConfig { chain_id: 1337, ..default_network() };
+        let provider = Provider::new(crate::mock::MockProvider::new(1337));
+        ping_provider(&Output::new(true), &provider, &network).await.unwrap();
+
+        let provider = Provider::new(crate::mock::MockProvider::new(1));
+        let err = ping_provider(&Output::new(true), &provider, &network).await.unwrap_err();
+        assert!(err.to_string().contains("reports chain ID 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_code_matches() {
+        // Runtime code followed by a two-entry CBOR map and its length
+        let with_metadata = |hash: u8| {
+            let mut code = vec![0x60, 0x80, 0x60, 0x40, 0x52];
+            let metadata = [0xa2, 0x64, b'i', b'p', b'f', b's', 0x42, hash, hash, 0x64, b's', b'o', b'l', b'c', 0x43, 0, 8, 24];
+            code.extend_from_slice(&metadata);
+            code.extend_from_slice(&(metadata.len() as u16).to_be_bytes());
+            code
+        };
+        assert_eq!(strip_metadata(&with_metadata(1)), [0x60, 0x80, 0x60, 0x40, 0x52]);
+        assert!(code_matches(&with_metadata(1), &with_metadata(2)));
+        assert!(code_matches(&[0x60, 0x80], &[0x60, 0x80]));
+
+        let mut different = with_metadata(1);
+        different[0] = 0x61;
+        assert!(!code_matches(&different, &with_metadata(1)));
+        assert!(!code_matches(&[], &with_metadata(1)));
+        assert!(!code_matches(&[], &[]));
+        assert_eq!(strip_metadata(&[0x00]), [0x00]);
+        assert_eq!(strip_metadata(&[0x60, 0xff, 0xff]), [0x60, 0xff, 0xff]);
+    }
+
+    #[tokio::test]
+    async fn test_check_contract_code() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Bytes, _>(Bytes::from(vec![0x60, 0x80])).unwrap();
+        mock.push::<Bytes, _>(Bytes::new()).unwrap();
+
+        // Responses are served last pushed first
+        let err = check_contract_code(&Output::new(true), &provider, Address::repeat_byte(1), "localhost").await.unwrap_err();
+        assert!(err.to_string().contains("No contract at"), "{}", err);
+        check_contract_code(&Output::new(true), &provider, Address::repeat_byte(1), "localhost").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_ensure_target_code() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Bytes, _>(Bytes::new()).unwrap();
+
+        let err = ensure_target_code(&provider, Address::repeat_byte(1), "mainnet", &test_tx_args()).await.unwrap_err();
+        assert!(err.to_string().contains("No contract at"), "{}", err);
+        assert!(err.to_string().contains("on mainnet"), "{}", err);
+
+        // Skipping doesn't touch the RPC at all
+        let skip = TxArgs { skip_code_check: true, ..test_tx_args() };
+        ensure_target_code(&provider, Address::repeat_byte(1), "mainnet", &skip).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_registered() {
+        let (provider, mock) = Provider::mocked();
+        let word = |value: u8| Bytes::from([[0u8; 31].as_slice(), &[value]].concat());
+        mock.push::<Bytes, _>(word(0)).unwrap();
+        mock.push::<Bytes, _>(word(1)).unwrap();
+
+        let contract = Address::repeat_byte(1);
+        assert!(endpoint_registered(&provider, contract, "https://rpc.example.com").await.unwrap());
+        assert!(!endpoint_registered(&provider, contract, "https://rpc.example.com").await.unwrap());
+        assert!(endpoint_registered(&provider, contract, "https://rpc.example.com").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_signer_client_rejects_wrong_chain() {
+        let provider = Provider::new(crate::mock::MockProvider::new(1));
+        let err = signer_client(provider, &test_config(1337), TxSigner::Local(TEST_KEY.parse().unwrap())).await.unwrap_err();
+        assert!(err.to_string().contains("reports chain ID 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_mnemonic_accounts() {
+        let phrase = "test test test test test test test test test test test junk";
+        let first = derive_mnemonic_key(phrase, &derivation_path(None, None).unwrap()).unwrap();
+        assert_eq!(first, format!("0x{}", TEST_KEY));
+
+        let path = derivation_path(None, Some(3)).unwrap();
+        assert_eq!(path, "m/44'/60'/0'/0/3");
+        let fourth = derive_mnemonic_key(phrase, &path).unwrap();
+        assert_eq!(
+            key_address(&fourth).unwrap(),
+            "0x90F79bf6EB2c4f870365E785982E1f101E93b906".parse::<Address>().unwrap()
+        );
+        assert_eq!(derive_mnemonic_key(phrase, "m/44'/60'/0'/0/3").unwrap(), fourth);
+
+        assert_eq!(derivation_path(Some("m/44'/60'/1'/0/0"), None).unwrap(), "m/44'/60'/1'/0/0");
+        assert!(derivation_path(Some("m/44'/60'/1'/0/0"), Some(1)).is_err());
+        assert!(derive_mnemonic_key("not a mnemonic", "m/44'/60'/0'/0/0").is_err());
+    }
+
+    #[cfg(not(feature = "ledger"))]
+    #[tokio::test]
+    async fn test_ledger_needs_feature() {
+        let signer = SignerArgs { ledger: true, ..Default::default() };
+        let err = choose_sender(&Output::new(true), &signer, &test_config(1337).network).await.err().unwrap();
+        assert!(err.to_string().contains("--features ledger"), "{}", err);
+        assert!(ledger_sender(&Output::new(true), &SignerArgs::default(), 1).await.unwrap().is_none());
+    }
+}
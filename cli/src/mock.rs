@@ -0,0 +1,143 @@
+//! In-memory JSON-RPC backend for exercising the send paths offline.
+//!
+//! [`MockProvider`] answers the handful of calls a write command makes
+//! (chain ID, nonce, gas, send, receipt) and records every transaction sent
+//! through `eth_sendRawTransaction`. `eth_feeHistory` is not supported, so
+//! transactions are built as legacy ones.
+
+#![cfg_attr(not(test), allow(dead_code))]
+
+use async_trait::async_trait;
+use ethers::{
+    providers::{JsonRpcClient, JsonRpcError, MockError},
+    types::{Address, Bytes, Transaction, TransactionReceipt, TxHash, U256, U64},
+    utils::rlp,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Block every mocked transaction is mined in
+const MOCK_BLOCK: u64 = 1;
+
+/// Gas returned for every `eth_estimateGas`
+const MOCK_GAS: u64 = 100_000;
+
+/// A transaction recorded by [`MockProvider`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SentTransaction {
+    pub hash: TxHash,
+    pub from: Address,
+    pub to: Option<Address>,
+    pub data: Bytes,
+    pub value: U256,
+    pub gas: U256,
+}
+
+#[derive(Debug, Clone)]
+pub struct MockProvider {
+    chain_id: u64,
+    sent: Arc<Mutex<Vec<Transaction>>>,
+    /// Report every receipt with status 0, as if the transaction reverted on-chain
+    reverting: Arc<AtomicBool>,
+}
+
+impl MockProvider {
+    pub fn new(chain_id: u64) -> Self {
+        Self { chain_id, sent: Arc::default(), reverting: Arc::default() }
+    }
+
+    /// Make transactions revert once mined (their receipts get status 0)
+    pub fn set_reverting(&self, reverting: bool) {
+        self.reverting.store(reverting, Ordering::Relaxed);
+    }
+
+    /// Transactions sent so far, oldest first
+    pub fn sent(&self) -> Vec<SentTransaction> {
+        self.sent
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|tx| SentTransaction {
+                hash: tx.hash,
+                from: tx.from,
+                to: tx.to,
+                data: tx.input.clone(),
+                value: tx.value,
+                gas: tx.gas,
+            })
+            .collect()
+    }
+
+    fn find(&self, hash: &Value) -> Option<Transaction> {
+        let hash: TxHash = serde_json::from_value(hash.clone()).ok()?;
+        self.sent.lock().unwrap().iter().find(|tx| tx.hash == hash).cloned()
+    }
+
+    fn send_raw(&self, raw: &Value) -> Result<Value, MockError> {
+        let raw: Bytes = serde_json::from_value(raw.clone())?;
+        let mut tx: Transaction = rlp::decode(&raw)
+            .map_err(|e| rpc_error(-32602, &format!("invalid transaction: {}", e)))?;
+        tx.recover_from_mut()
+            .map_err(|e| rpc_error(-32602, &format!("invalid signature: {}", e)))?;
+
+        let hash = tx.hash;
+        self.sent.lock().unwrap().push(tx);
+        Ok(json!(hash))
+    }
+
+    fn respond(&self, method: &str, params: &Value) -> Result<Value, MockError> {
+        let param = |i: usize| params.get(i).cloned().unwrap_or(Value::Null);
+
+        Ok(match method {
+            "eth_chainId" => json!(U64::from(self.chain_id)),
+            "eth_blockNumber" => json!(U64::from(MOCK_BLOCK)),
+            "eth_gasPrice" => json!(U256::from(1_000_000_000u64)),
+            "eth_estimateGas" => json!(U256::from(MOCK_GAS)),
+            "eth_getTransactionCount" => json!(U256::from(self.sent.lock().unwrap().len())),
+            "eth_sendRawTransaction" => self.send_raw(&param(0))?,
+            "eth_getTransactionByHash" => match self.find(&param(0)) {
+                Some(mut tx) => {
+                    tx.block_number = Some(MOCK_BLOCK.into());
+                    json!(tx)
+                }
+                None => Value::Null,
+            },
+            "eth_getTransactionReceipt" => match self.find(&param(0)) {
+                Some(tx) => json!(TransactionReceipt {
+                    transaction_hash: tx.hash,
+                    from: tx.from,
+                    to: tx.to,
+                    block_number: Some(MOCK_BLOCK.into()),
+                    gas_used: Some(MOCK_GAS.into()),
+                    status: Some(u64::from(!self.reverting.load(Ordering::Relaxed)).into()),
+                    effective_gas_price: tx.max_fee_per_gas.or(tx.gas_price),
+                    ..Default::default()
+                }),
+                None => Value::Null,
+            },
+            _ => return Err(rpc_error(-32601, &format!("the method {} does not exist/is not available", method))),
+        })
+    }
+}
+
+fn rpc_error(code: i64, message: &str) -> MockError {
+    MockError::JsonRpcError(JsonRpcError { code, message: message.to_string(), data: None })
+}
+
+#[async_trait]
+impl JsonRpcClient for MockProvider {
+    type Error = MockError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, MockError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = serde_json::to_value(params)?;
+        let result = self.respond(method, &params)?;
+        Ok(serde_json::from_value(result)?)
+    }
+}
@@ -0,0 +1,124 @@
+use serde_json::Value;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Controls how commands report progress and results.
+///
+/// In human mode status lines go to stdout as before. In JSON mode stdout is
+/// reserved for a single result object, so status lines and prompts are sent
+/// to stderr instead.
+#[derive(Debug, Clone, Default)]
+pub struct Output {
+    json: bool,
+    file: Option<Arc<ResultFile>>,
+}
+
+/// Where `--output` copies results; created on the first result, so a
+/// command that fails early leaves no empty file behind
+#[derive(Debug)]
+struct ResultFile {
+    path: PathBuf,
+    file: Mutex<Option<File>>,
+}
+
+impl ResultFile {
+    fn write(&self, line: &str) -> io::Result<()> {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if file.is_none() {
+            *file = Some(File::create(&self.path)?);
+        }
+        let file = file.as_mut().expect("opened above");
+        writeln!(file, "{}", line)?;
+        file.flush()
+    }
+}
+
+impl Output {
+    pub fn new(json: bool) -> Self {
+        Self { json, file: None }
+    }
+
+    /// Also write every result to `path`, replacing its contents
+    pub fn with_file(mut self, path: PathBuf) -> Self {
+        self.file = Some(Arc::new(ResultFile { path, file: Mutex::new(None) }));
+        self
+    }
+
+    pub fn is_json(&self) -> bool {
+        self.json
+    }
+
+    /// Print a human-readable status line
+    pub fn say(&self, msg: impl Display) {
+        if self.json {
+            eprintln!("{}", msg);
+        } else {
+            println!("{}", msg);
+        }
+    }
+
+    /// Print an empty status line
+    pub fn blank(&self) {
+        self.say("");
+    }
+
+    /// Print a prompt without a trailing newline and flush it
+    pub fn prompt(&self, msg: impl Display) -> io::Result<()> {
+        if self.json {
+            eprint!("{}", msg);
+            io::stderr().flush()
+        } else {
+            print!("{}", msg);
+            io::stdout().flush()
+        }
+    }
+
+    /// Emit the machine-readable result of a command (JSON mode only), and
+    /// write it to the `--output` file in either mode: one JSON line per
+    /// result with `--json`, pretty-printed otherwise
+    pub fn result(&self, value: Value) {
+        if let Some(file) = &self.file {
+            let text = if self.json { value.to_string() } else { format!("{:#}", value) };
+            if let Err(e) = file.write(&text) {
+                eprintln!("⚠️  Could not write the result to {}: {}", file.path.display(), e);
+            }
+        }
+        if self.json {
+            println!("{}", value);
+        }
+    }
+
+    /// Emit an error object (JSON mode only)
+    pub fn error(&self, err: &anyhow::Error) {
+        if self.json {
+            println!("{}", serde_json::json!({ "error": format!("{:#}", err) }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_result_file() {
+        let path = std::env::temp_dir().join(format!("polyportal-output-{}.json", std::process::id()));
+        std::fs::write(&path, "old contents").unwrap();
+
+        // JSON mode writes one line per result, replacing what was there
+        let out = Output::new(true).with_file(path.clone());
+        out.say("status lines are not written");
+        out.result(json!({ "event": "added" }));
+        out.clone().result(json!({ "event": "removed" }));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"event\":\"added\"}\n{\"event\":\"removed\"}\n");
+
+        Output::new(false).with_file(path.clone()).result(json!({ "count": 2 }));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\n  \"count\": 2\n}\n");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
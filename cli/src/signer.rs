@@ -0,0 +1,107 @@
+//! The key that signs write commands: a wallet decrypted from wallet.toml,
+//! or a Ledger device with `--ledger` (built with the `ledger` feature)
+
+use std::fmt;
+
+use async_trait::async_trait;
+use ethers::signers::{LocalWallet, Signer, WalletError};
+use ethers::types::{
+    transaction::{eip2718::TypedTransaction, eip712::Eip712},
+    Address, Signature,
+};
+#[cfg(feature = "ledger")]
+use ethers::signers::{HDPath, Ledger, LedgerError};
+
+/// Default `--hd-path`: the first account of the Ledger Ethereum app
+pub const DEFAULT_HD_PATH: &str = "m/44'/60'/0'/0/0";
+
+/// A signer for `SignerMiddleware` that is either a local key or a Ledger
+#[derive(Debug)]
+pub enum TxSigner {
+    Local(LocalWallet),
+    #[cfg(feature = "ledger")]
+    Ledger(Ledger),
+}
+
+#[derive(Debug)]
+pub enum TxSignerError {
+    Local(WalletError),
+    #[cfg(feature = "ledger")]
+    Ledger(LedgerError),
+}
+
+impl fmt::Display for TxSignerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxSignerError::Local(e) => write!(f, "{}", e),
+            #[cfg(feature = "ledger")]
+            TxSignerError::Ledger(e) => write!(f, "Ledger: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TxSignerError {}
+
+#[async_trait]
+impl Signer for TxSigner {
+    type Error = TxSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(&self, message: S) -> Result<Signature, Self::Error> {
+        match self {
+            TxSigner::Local(wallet) => wallet.sign_message(message).await.map_err(TxSignerError::Local),
+            #[cfg(feature = "ledger")]
+            TxSigner::Ledger(ledger) => ledger.sign_message(message).await.map_err(TxSignerError::Ledger),
+        }
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        match self {
+            TxSigner::Local(wallet) => wallet.sign_transaction(tx).await.map_err(TxSignerError::Local),
+            #[cfg(feature = "ledger")]
+            TxSigner::Ledger(ledger) => ledger.sign_transaction(tx).await.map_err(TxSignerError::Ledger),
+        }
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(&self, payload: &T) -> Result<Signature, Self::Error> {
+        match self {
+            TxSigner::Local(wallet) => wallet.sign_typed_data(payload).await.map_err(TxSignerError::Local),
+            #[cfg(feature = "ledger")]
+            TxSigner::Ledger(ledger) => ledger.sign_typed_data(payload).await.map_err(TxSignerError::Ledger),
+        }
+    }
+
+    fn address(&self) -> Address {
+        match self {
+            TxSigner::Local(wallet) => wallet.address(),
+            #[cfg(feature = "ledger")]
+            TxSigner::Ledger(ledger) => ledger.address(),
+        }
+    }
+
+    fn chain_id(&self) -> u64 {
+        match self {
+            TxSigner::Local(wallet) => wallet.chain_id(),
+            #[cfg(feature = "ledger")]
+            TxSigner::Ledger(ledger) => ledger.chain_id(),
+        }
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        match self {
+            TxSigner::Local(wallet) => TxSigner::Local(wallet.with_chain_id(chain_id)),
+            #[cfg(feature = "ledger")]
+            TxSigner::Ledger(ledger) => TxSigner::Ledger(ledger.with_chain_id(chain_id)),
+        }
+    }
+}
+
+/// Open the Ledger's Ethereum app and read the account at `hd_path`
+#[cfg(feature = "ledger")]
+pub async fn connect_ledger(hd_path: &str, chain_id: u64) -> anyhow::Result<Ledger> {
+    Ledger::new(HDPath::Other(hd_path.to_string()), chain_id).await.map_err(|e| {
+        anyhow::anyhow!(
+            "Could not read account {} from the Ledger ({}). Is it connected, unlocked and in the Ethereum app?",
+            hd_path, e
+        )
+    })
+}
@@ -0,0 +1,529 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use ethers::{
+    providers::{JsonRpcClient, MiddlewareError, PendingTransaction},
+    signers::Signer,
+    types::{
+        transaction::eip2718::TypedTransaction, Address, BlockNumber, Bytes, Eip1559TransactionRequest,
+        TransactionReceipt, TransactionRequest, U256,
+    },
+    utils::{format_ether, format_units, parse_units, rlp},
+};
+use ethers_middleware::Middleware;
+use polyendpoint_sdk::{contract::decode_revert_reason, GasEstimate, TxOutcome};
+use serde_json::json;
+use std::fmt;
+
+use crate::output::Output;
+
+/// Options shared by every command that sends a transaction
+#[derive(Args, Debug, Clone)]
+pub struct TxArgs {
+    /// Max fee per gas in gwei (EIP-1559); estimated from eth_feeHistory if omitted
+    #[arg(long, value_name = "GWEI")]
+    pub max_fee: Option<String>,
+    /// Max priority fee per gas in gwei (EIP-1559); estimated from eth_feeHistory if omitted
+    #[arg(long, value_name = "GWEI")]
+    pub priority_fee: Option<String>,
+    /// Simulate the transaction and print the gas estimate without sending it
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Number of block confirmations to wait for before reporting success
+    #[arg(long, default_value_t = 1)]
+    pub confirmations: usize,
+    /// Use this nonce instead of the account's pending nonce (to replace a stuck transaction)
+    #[arg(long)]
+    pub nonce: Option<u64>,
+    /// Poll for the transaction's receipt every MS milliseconds (overrides the network's `poll_ms`)
+    #[arg(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub poll_ms: Option<u64>,
+    /// Amount of wei to send with the transaction
+    #[arg(long, value_name = "WEI", default_value = "0", value_parser = parse_wei)]
+    pub value: U256,
+    /// Send with this gas limit instead of the buffered estimate
+    #[arg(long, value_name = "GAS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub gas_limit: Option<u64>,
+    /// Percentage added to the gas estimate (overrides the network's `gas_buffer_percent`) [default: 20]
+    #[arg(long, value_name = "PERCENT")]
+    pub gas_buffer: Option<u64>,
+    /// Send even if the target address has no code on the configured network
+    #[arg(long)]
+    pub skip_code_check: bool,
+}
+
+/// Percentage added to `eth_estimateGas` results unless configured otherwise
+pub const DEFAULT_GAS_BUFFER_PERCENT: u64 = 20;
+
+/// Every field of a transaction signed by `sign-tx`, so nothing has to be
+/// fetched from an RPC
+#[derive(Args, Debug, Clone)]
+pub struct OfflineTxArgs {
+    /// Recipient, e.g. the contract to call
+    #[arg(long)]
+    pub to: Address,
+    /// Calldata as hex
+    #[arg(long, default_value = "0x")]
+    pub data: String,
+    /// Nonce of the signing account
+    #[arg(long)]
+    pub nonce: u64,
+    /// Gas limit
+    #[arg(long)]
+    pub gas: u64,
+    /// Max fee per gas in gwei (the gas price with --legacy)
+    #[arg(long, value_name = "GWEI")]
+    pub fee: String,
+    /// Max priority fee per gas in gwei [default: 1, capped at --fee]
+    #[arg(long, value_name = "GWEI", conflicts_with = "legacy")]
+    pub priority_fee: Option<String>,
+    /// Amount of wei to send with the transaction
+    #[arg(long, value_name = "WEI", default_value = "0", value_parser = parse_wei)]
+    pub value: U256,
+    /// Sign a legacy (pre-EIP-1559) transaction
+    #[arg(long)]
+    pub legacy: bool,
+}
+
+impl OfflineTxArgs {
+    /// The unsigned transaction from `from` on `chain_id`, carrying `data`
+    pub fn transaction(&self, from: Address, chain_id: u64, data: Bytes) -> Result<TypedTransaction> {
+        let fee = parse_gwei(&self.fee, "--fee")?;
+        if self.legacy {
+            return Ok(TransactionRequest::new()
+                .from(from)
+                .to(self.to)
+                .data(data)
+                .value(self.value)
+                .nonce(self.nonce)
+                .gas(self.gas)
+                .gas_price(fee)
+                .chain_id(chain_id)
+                .into());
+        }
+
+        let priority_fee = match &self.priority_fee {
+            Some(priority_fee) => parse_gwei(priority_fee, "--priority-fee")?,
+            None => parse_gwei("1", "--priority-fee")?.min(fee),
+        };
+        if priority_fee > fee {
+            anyhow::bail!("--priority-fee cannot be higher than --fee");
+        }
+        Ok(Eip1559TransactionRequest::new()
+            .from(from)
+            .to(self.to)
+            .data(data)
+            .value(self.value)
+            .nonce(self.nonce)
+            .gas(self.gas)
+            .max_fee_per_gas(fee)
+            .max_priority_fee_per_gas(priority_fee)
+            .chain_id(chain_id)
+            .into())
+    }
+}
+
+/// Sign `tx` without touching the network and return the raw bytes for
+/// `eth_sendRawTransaction`
+pub async fn sign_raw<S: Signer>(signer: &S, tx: &TypedTransaction) -> Result<Bytes> {
+    let signature = signer
+        .sign_transaction(tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to sign transaction: {}", e))?;
+    Ok(tx.rlp_signed(&signature))
+}
+
+/// Decode a raw signed transaction and recover its sender
+pub fn decode_raw(raw: &[u8]) -> Result<(TypedTransaction, Address)> {
+    let (tx, signature) = TypedTransaction::decode_signed(&rlp::Rlp::new(raw))
+        .map_err(|e| anyhow::anyhow!("Not a signed transaction: {}", e))?;
+    let from = signature
+        .recover(tx.sighash())
+        .map_err(|e| anyhow::anyhow!("Invalid transaction signature: {}", e))?;
+    Ok((tx, from))
+}
+
+/// Extract a human-readable revert reason from a middleware error
+fn revert_reason<E: MiddlewareError>(err: &E) -> Option<String> {
+    let response = err.as_error_response()?;
+    response
+        .as_revert_data()
+        .and_then(|data| decode_revert_reason(&data))
+        .or_else(|| {
+            response.message.contains("revert").then(|| response.message.clone())
+        })
+}
+
+/// Estimate gas for a transaction, failing with the revert reason if the simulation reverts
+pub async fn estimate_gas<M: Middleware>(client: &M, tx: &TypedTransaction) -> Result<U256> {
+    tracing::debug!(to = ?tx.to(), "estimating gas");
+    client.estimate_gas(tx, None).await.map_err(|e| match revert_reason(&e) {
+        Some(reason) => anyhow::anyhow!("Transaction would revert: {}", reason),
+        None => anyhow::anyhow!("Gas estimation failed: {}", e),
+    })
+}
+
+/// Fee per gas the transaction is expected to pay: the latest base fee plus
+/// its priority fee (capped at its max fee), or its legacy gas price
+async fn fee_per_gas<M: Middleware>(client: &M, tx: &TypedTransaction) -> Result<U256> {
+    if let TypedTransaction::Eip1559(tx) = tx {
+        let block = client
+            .get_block(BlockNumber::Latest)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to fetch latest block: {}", e))?
+            .context("Latest block not found")?;
+        let fee = block.base_fee_per_gas.unwrap_or_default() + tx.max_priority_fee_per_gas.unwrap_or_default();
+        return Ok(tx.max_fee_per_gas.map_or(fee, |max_fee| fee.min(max_fee)));
+    }
+
+    match tx.gas_price() {
+        Some(gas_price) => Ok(gas_price),
+        None => client
+            .get_gas_price()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to fetch gas price: {}", e)),
+    }
+}
+
+/// Estimate gas for a transaction and print what it would cost in ETH
+pub async fn estimate_cost<M: Middleware>(client: &M, out: &Output, tx: &TypedTransaction) -> Result<GasEstimate> {
+    let gas = estimate_gas(client, tx).await?;
+    let fee = fee_per_gas(client, tx).await?;
+
+    let estimate = GasEstimate::new(
+        u64::try_from(gas).map_err(|_| anyhow::anyhow!("Gas estimate {} is too large", gas))?,
+        u128::try_from(fee).map_err(|_| anyhow::anyhow!("Fee per gas {} is too large", fee))?,
+    );
+    out.say(format!(
+        "Estimated gas: {} (~{} ETH at {} gwei)",
+        estimate.gas,
+        estimate.eth_string,
+        format_gwei(fee)
+    ));
+    Ok(estimate)
+}
+
+/// Gas limit to send with: `--gas-limit`, or the estimate plus a buffer
+/// (`--gas-buffer`, then the network's `gas_buffer_percent`, then 20%) so
+/// paths that need more gas than the node estimated don't run out
+pub fn gas_limit(out: &Output, estimate: u64, args: &TxArgs, network_buffer: Option<u64>) -> U256 {
+    if let Some(limit) = args.gas_limit {
+        if limit < estimate {
+            out.say(format!("⚠️  --gas-limit {} is below the estimate of {}; the transaction may run out of gas", limit, estimate));
+        } else {
+            out.say(format!("Gas limit: {} (--gas-limit)", limit));
+        }
+        return limit.into();
+    }
+
+    let percent = args.gas_buffer.or(network_buffer).unwrap_or(DEFAULT_GAS_BUFFER_PERCENT);
+    // In U256 so a huge buffer can't overflow the u64 addition
+    let limit = U256::from(estimate) * (U256::from(100) + U256::from(percent)) / 100;
+    out.say(format!("Gas limit: {} (estimate + {}%)", limit, percent));
+    limit
+}
+
+/// Set the nonce of a transaction whose `from` is already set.
+///
+/// Uses `--nonce` if given, otherwise the sender's pending nonce so that
+/// back-to-back sends don't collide.
+pub async fn set_nonce<M: Middleware>(
+    client: &M,
+    out: &Output,
+    tx: &mut TypedTransaction,
+    args: &TxArgs,
+) -> Result<()> {
+    let nonce = match args.nonce {
+        Some(nonce) => {
+            out.say(format!("Using nonce {} (--nonce)", nonce));
+            U256::from(nonce)
+        }
+        None => {
+            let from = *tx.from().context("Transaction has no sender")?;
+            client
+                .get_transaction_count(from, Some(BlockNumber::Pending.into()))
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to fetch pending nonce: {}", e))?
+        }
+    };
+    tx.set_nonce(nonce);
+    Ok(())
+}
+
+/// Wait for a sent transaction to reach the requested depth and check its status.
+///
+/// Fails if the transaction is dropped or mined with a reverted (status 0) receipt.
+#[tracing::instrument(level = "debug", skip_all, fields(tx_hash = ?pending_tx.tx_hash(), confirmations))]
+pub async fn wait_for_receipt<P: JsonRpcClient>(
+    out: &Output,
+    pending_tx: PendingTransaction<'_, P>,
+    confirmations: usize,
+) -> Result<TransactionReceipt> {
+    let tx_hash = pending_tx.tx_hash();
+    out.say(format!(
+        "Waiting for {} confirmation{}...",
+        confirmations,
+        if confirmations == 1 { "" } else { "s" }
+    ));
+
+    let receipt = pending_tx
+        .confirmations(confirmations)
+        .await?
+        .with_context(|| format!("Transaction {:#x} was dropped from the mempool", tx_hash))?;
+
+    let block = receipt.block_number.unwrap_or_default();
+    let status = if receipt.status == Some(0u64.into()) { "reverted" } else { "success" };
+    out.say(format!("Mined in block {} (status: {})", block, status));
+    Ok(receipt)
+}
+
+/// A transaction that was mined but reverted. `main` reports its outcome
+/// (hash, block, gas used) as the `--json` result before exiting nonzero.
+#[derive(Debug)]
+pub struct Reverted(pub TxOutcome);
+
+impl fmt::Display for Reverted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Transaction {} reverted in block {}", self.0.hash, self.0.block_number)
+    }
+}
+
+impl std::error::Error for Reverted {}
+
+/// Read a mined transaction's receipt into a [`TxOutcome`] and print the
+/// gas it used and the fee paid. A reverted transaction is a [`Reverted`] error.
+pub fn tx_outcome(out: &Output, receipt: &TransactionReceipt) -> Result<TxOutcome> {
+    let outcome = TxOutcome::from_receipt(&serde_json::to_value(receipt)?)
+        .map_err(|e| anyhow::anyhow!("Unreadable receipt for {:#x}: {}", receipt.transaction_hash, e))?;
+    match (outcome.fee(), outcome.effective_gas_price) {
+        (Some(fee), Some(price)) => out.say(format!(
+            "Gas used: {} (fee {} ETH at {} gwei)",
+            outcome.gas_used,
+            fee.eth_string,
+            format_gwei(U256::from(price))
+        )),
+        _ => out.say(format!("Gas used: {}", outcome.gas_used)),
+    }
+    if !outcome.succeeded() {
+        return Err(Reverted(outcome).into());
+    }
+    Ok(outcome)
+}
+
+/// The `--json` fields describing a mined transaction
+pub fn outcome_json(outcome: &TxOutcome) -> serde_json::Value {
+    json!({
+        "txHash": outcome.hash,
+        "status": if outcome.succeeded() { "confirmed" } else { "reverted" },
+        "blockNumber": outcome.block_number,
+        "gasUsed": outcome.gas_used,
+        "effectiveGasPrice": outcome.effective_gas_price.map(|price| price.to_string()),
+        "feeWei": outcome.fee().map(|fee| fee.wei.to_string()),
+    })
+}
+
+/// Simulate a transaction from `from` and return its gas and cost estimate
+pub async fn simulate<M: Middleware>(
+    client: &M,
+    out: &Output,
+    from: Address,
+    to: Option<Address>,
+    data: Bytes,
+    args: &TxArgs,
+) -> Result<GasEstimate> {
+    let mut tx = build_transaction(client, out, to, data, args).await?;
+    tx.set_from(from);
+
+    out.say("Simulating transaction...");
+    estimate_cost(client, out, &tx).await
+}
+
+/// Simulate a transaction from `from` and report the result without broadcasting it
+pub async fn dry_run<M: Middleware>(
+    client: &M,
+    out: &Output,
+    from: Address,
+    to: Option<Address>,
+    data: Bytes,
+    args: &TxArgs,
+) -> Result<()> {
+    let estimate = simulate(client, out, from, to, data, args).await?;
+
+    out.say(format!(
+        "✅ Simulation succeeded, estimated gas: {} (~{} ETH)",
+        estimate.gas, estimate.eth_string
+    ));
+    out.say("Dry run - transaction not sent.");
+    out.result(json!({
+        "dryRun": true,
+        "estimatedGas": estimate.gas.to_string(),
+        "estimatedCostWei": estimate.wei.to_string(),
+        "estimatedCostEth": estimate.eth_string,
+    }));
+
+    Ok(())
+}
+
+fn parse_wei(value: &str) -> Result<U256, String> {
+    U256::from_dec_str(value.trim()).map_err(|_| format!("expected an amount in wei, got '{}'", value))
+}
+
+fn parse_gwei(value: &str, flag: &str) -> Result<U256> {
+    let parsed = parse_units(value, "gwei")
+        .with_context(|| format!("Invalid {} value '{}'", flag, value))?;
+    Ok(parsed.into())
+}
+
+pub fn format_gwei(value: U256) -> String {
+    format_units(value, "gwei").unwrap_or_else(|_| value.to_string())
+}
+
+/// Build a transaction sending `--value` wei with fees filled in.
+///
+/// EIP-1559 fees are estimated via `eth_feeHistory` and overridden by
+/// `--max-fee` / `--priority-fee`. If the node does not support
+/// `eth_feeHistory` the chain is assumed to be pre-1559 and a legacy
+/// transaction is built instead.
+pub async fn build_transaction<M: Middleware>(
+    client: &M,
+    out: &Output,
+    to: Option<Address>,
+    data: Bytes,
+    args: &TxArgs,
+) -> Result<TypedTransaction> {
+    let max_fee = args.max_fee.as_deref().map(|v| parse_gwei(v, "--max-fee")).transpose()?;
+    let priority_fee = args.priority_fee.as_deref().map(|v| parse_gwei(v, "--priority-fee")).transpose()?;
+    let value = args.value;
+    if !value.is_zero() {
+        out.say(format!("Value: {} ETH", format_ether(value)));
+    }
+
+    match client.estimate_eip1559_fees(None).await {
+        Ok((estimated_max, estimated_priority)) => {
+            let priority_fee = priority_fee.unwrap_or(estimated_priority);
+            let max_fee = max_fee.unwrap_or_else(|| estimated_max.max(priority_fee));
+            if priority_fee > max_fee {
+                anyhow::bail!("--priority-fee cannot be higher than --max-fee");
+            }
+
+            out.say(format!(
+                "Fees: max {} gwei, priority {} gwei",
+                format_gwei(max_fee),
+                format_gwei(priority_fee)
+            ));
+
+            let mut tx = Eip1559TransactionRequest::new()
+                .data(data)
+                .value(value)
+                .max_fee_per_gas(max_fee)
+                .max_priority_fee_per_gas(priority_fee);
+            if let Some(to) = to {
+                tx = tx.to(to);
+            }
+            Ok(tx.into())
+        }
+        Err(e) => {
+            out.say(format!("eth_feeHistory unavailable ({}), sending a legacy transaction", e));
+
+            let mut tx = TransactionRequest::new().data(data).value(value);
+            if let Some(to) = to {
+                tx = tx.to(to);
+            }
+            // On a legacy chain the max fee is the closest thing to a gas price
+            if let Some(gas_price) = max_fee {
+                tx = tx.gas_price(gas_price);
+            }
+            Ok(tx.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gwei() {
+        assert_eq!(parse_gwei("1", "--max-fee").unwrap(), U256::from(1_000_000_000u64));
+        assert_eq!(parse_gwei("0.5", "--max-fee").unwrap(), U256::from(500_000_000u64));
+        assert!(parse_gwei("abc", "--max-fee").is_err());
+    }
+
+    #[test]
+    fn test_gas_limit() {
+        let out = Output::new(true);
+        let args = TxArgs {
+            max_fee: None,
+            priority_fee: None,
+            dry_run: false,
+            confirmations: 1,
+            nonce: None,
+            poll_ms: None,
+            value: U256::zero(),
+            gas_limit: None,
+            gas_buffer: None,
+            skip_code_check: false,
+        };
+        assert_eq!(gas_limit(&out, 100_000, &args, None), U256::from(120_000));
+        assert_eq!(gas_limit(&out, 100_000, &args, Some(50)), U256::from(150_000));
+        assert_eq!(gas_limit(&out, 100_000, &TxArgs { gas_buffer: Some(0), ..args.clone() }, Some(50)), U256::from(100_000));
+        assert_eq!(
+            gas_limit(&out, 100, &args, Some(u64::MAX)),
+            U256::from(100) * (U256::from(u64::MAX) + 100) / 100
+        );
+        assert_eq!(gas_limit(&out, 100_000, &TxArgs { gas_limit: Some(90_000), ..args }, Some(50)), U256::from(90_000));
+    }
+
+    #[tokio::test]
+    async fn test_sign_raw() {
+        use ethers::signers::LocalWallet;
+
+        let wallet: LocalWallet = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".parse().unwrap();
+        let mut args = OfflineTxArgs {
+            to: "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".parse().unwrap(),
+            data: "0x".to_string(),
+            nonce: 7,
+            gas: 100_000,
+            fee: "30".to_string(),
+            priority_fee: None,
+            value: U256::from(5),
+            legacy: false,
+        };
+
+        let tx = args.transaction(wallet.address(), 84532, Bytes::from(vec![0x8d, 0xa5, 0xcb, 0x5b])).unwrap();
+        let raw = sign_raw(&wallet, &tx).await.unwrap();
+        assert_eq!(raw[0], 0x02);
+        let (decoded, from) = decode_raw(&raw).unwrap();
+        assert_eq!(from, wallet.address());
+        assert_eq!(decoded.chain_id(), Some(84532u64.into()));
+        assert_eq!(decoded.nonce(), Some(&U256::from(7)));
+        assert_eq!(decoded.gas(), Some(&U256::from(100_000)));
+        assert_eq!(decoded.data().unwrap().to_vec(), [0x8d, 0xa5, 0xcb, 0x5b]);
+        match decoded {
+            TypedTransaction::Eip1559(tx) => {
+                assert_eq!(tx.max_fee_per_gas, Some(U256::from(30_000_000_000u64)));
+                assert_eq!(tx.max_priority_fee_per_gas, Some(U256::from(1_000_000_000u64)));
+            }
+            other => panic!("expected an EIP-1559 transaction, got {:?}", other),
+        }
+
+        args.legacy = true;
+        let tx = args.transaction(wallet.address(), 84532, Bytes::new()).unwrap();
+        let (decoded, from) = decode_raw(&sign_raw(&wallet, &tx).await.unwrap()).unwrap();
+        assert_eq!(from, wallet.address());
+        assert_eq!(decoded.gas_price(), Some(U256::from(30_000_000_000u64)));
+        assert_eq!(decoded.chain_id(), Some(84532u64.into()));
+
+        args.legacy = false;
+        args.priority_fee = Some("31".to_string());
+        assert!(args.transaction(wallet.address(), 84532, Bytes::new()).is_err());
+        assert!(decode_raw(&[0x02, 0xc0]).is_err());
+    }
+
+    #[test]
+    fn test_parse_wei() {
+        assert_eq!(parse_wei("0").unwrap(), U256::zero());
+        assert_eq!(parse_wei("1000000000000000000").unwrap(), U256::exp10(18));
+        assert!(parse_wei("0.1").is_err());
+        assert!(parse_wei("-1").is_err());
+    }
+}
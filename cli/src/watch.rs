@@ -0,0 +1,122 @@
+use anyhow::Result;
+use polyendpoint_sdk::{EndpointInfo, PolyEndpointClient};
+use serde_json::json;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::MissedTickBehavior;
+
+use crate::output::Output;
+
+/// Endpoints added and removed between two snapshots, matched by URL
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct EndpointDiff {
+    pub added: Vec<EndpointInfo>,
+    pub removed: Vec<EndpointInfo>,
+}
+
+impl EndpointDiff {
+    pub fn new(old: &[EndpointInfo], new: &[EndpointInfo]) -> Self {
+        let missing_from = |list: &[EndpointInfo], endpoint: &EndpointInfo| !list.iter().any(|e| e.url == endpoint.url);
+        Self {
+            added: new.iter().filter(|e| missing_from(old, e)).cloned().collect(),
+            removed: old.iter().filter(|e| missing_from(new, e)).cloned().collect(),
+        }
+    }
+}
+
+/// Poll the endpoint list every `interval` and report changes until Ctrl-C.
+///
+/// A failed poll is reported and skipped; the next one is compared against
+/// the last list that was read successfully. In JSON mode each event is one
+/// line on stdout.
+pub async fn watch(out: &Output, client: &PolyEndpointClient, network: &str, interval: Duration) -> Result<()> {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut previous: Option<Vec<EndpointInfo>> = None;
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+
+        let time = timestamp(SystemTime::now());
+        let endpoints = match client.refresh(network).await {
+            Ok(endpoints) => endpoints,
+            Err(e) => {
+                out.say(format!("[{}] ⚠️  Poll failed, skipping: {}", time, e));
+                continue;
+            }
+        };
+
+        match &previous {
+            None => {
+                out.say(format!("[{}] Watching {} endpoints (every {}s, Ctrl-C to stop)", time, endpoints.len(), interval.as_secs()));
+                out.result(json!({ "time": time, "event": "snapshot", "endpoints": endpoints }));
+            }
+            Some(previous) => {
+                let diff = EndpointDiff::new(previous, &endpoints);
+                for endpoint in &diff.added {
+                    out.say(format!("[{}] + {}", time, endpoint.url));
+                    out.result(json!({ "time": time, "event": "added", "url": endpoint.url, "description": endpoint.description }));
+                }
+                for endpoint in &diff.removed {
+                    out.say(format!("[{}] - {}", time, endpoint.url));
+                    out.result(json!({ "time": time, "event": "removed", "url": endpoint.url, "description": endpoint.description }));
+                }
+            }
+        }
+        previous = Some(endpoints);
+    }
+}
+
+/// Format `time` as an RFC 3339 UTC timestamp, e.g. `2024-05-01T12:00:00Z`
+pub fn timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // Days since 1970-01-01 to a civil date (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_diff() {
+        let a = EndpointInfo::new("https://a.example", "");
+        let b = EndpointInfo::new("https://b.example", "B");
+        let c = EndpointInfo::new("https://c.example", "C");
+
+        let diff = EndpointDiff::new(&[a.clone(), b.clone()], &[b.clone(), c.clone()]);
+        assert_eq!(diff, EndpointDiff { added: vec![c], removed: vec![a] });
+        let same = [b];
+        assert_eq!(EndpointDiff::new(&same, &same), EndpointDiff::default());
+    }
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(timestamp(UNIX_EPOCH + Duration::from_secs(951_782_400)), "2000-02-29T00:00:00Z");
+        assert_eq!(timestamp(UNIX_EPOCH + Duration::from_secs(1_714_564_800)), "2024-05-01T12:00:00Z");
+        assert_eq!(timestamp(UNIX_EPOCH + Duration::from_secs(4_102_444_799)), "2099-12-31T23:59:59Z");
+    }
+}
@@ -0,0 +1,18 @@
+//! Reading the contract from synchronous code
+#![cfg(not(target_arch = "wasm32"))]
+
+use polyendpoint_sdk::PolyEndpointClient;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Replace with actual contract address
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890").blocking()?;
+
+    println!("Owner: {}", client.owner("base-sepolia")?);
+    println!("Endpoint count: {}", client.get_endpoint_count("base-sepolia")?);
+
+    for endpoint in client.get_endpoints("base-sepolia")? {
+        println!("  {} {}", endpoint.url, endpoint.description);
+    }
+
+    Ok(())
+}
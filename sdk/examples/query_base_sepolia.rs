@@ -0,0 +1,116 @@
+//! Querying the Base Sepolia contract from synchronous code, the robust way:
+//! the client fails over between several RPCs and times out slow ones, and
+//! each step is retried a few times on errors that may go away
+//!
+//! Run with `cargo run --example query_base_sepolia --features blocking`
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::thread;
+use std::time::Duration;
+
+use polyendpoint_sdk::{BlockingPolyEndpointClient, ClientError, PolyEndpointClient};
+
+const CONTRACT: &str = "0xf16e03526d1be6d120cfbf5a24e1ac78a8192663";
+const NETWORK: &str = "base-sepolia";
+
+/// Tried in order until one answers; see `ClientBuilder::rpc_urls`
+const RPC_URLS: [&str; 2] = ["https://sepolia.base.org", "https://base-sepolia-rpc.publicnode.com"];
+
+const ATTEMPTS: u32 = 3;
+const BACKOFF: Duration = Duration::from_millis(500);
+
+/// Errors where asking again later may succeed. Reverts, decode errors and
+/// bad addresses will fail the same way every time.
+fn is_transient(error: &ClientError) -> bool {
+    matches!(error, ClientError::Network(_) | ClientError::Timeout | ClientError::RateLimited)
+}
+
+/// Run `step`, retrying transient errors with a doubling backoff
+fn with_retry<T>(name: &str, mut step: impl FnMut() -> Result<T, ClientError>) -> Result<T, ClientError> {
+    let mut delay = BACKOFF;
+    for attempt in 1..=ATTEMPTS {
+        match step() {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < ATTEMPTS && is_transient(&e) => {
+                println!("   ⚠️  {} failed (attempt {}/{}): {}; retrying in {:?}", name, attempt, ATTEMPTS, e, delay);
+                thread::sleep(delay);
+                delay *= 2;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!("the last attempt always returns")
+}
+
+/// Run one step, printing its status and recording the outcome
+fn step<T>(
+    results: &mut Vec<(&'static str, Result<(), String>)>,
+    name: &'static str,
+    run: impl FnMut() -> Result<T, ClientError>,
+) -> Option<T> {
+    println!("▶️  {}...", name);
+    match with_retry(name, run) {
+        Ok(value) => {
+            println!("   ✅ done");
+            results.push((name, Ok(())));
+            Some(value)
+        }
+        Err(e) => {
+            println!("   ❌ {}", e);
+            results.push((name, Err(e.to_string())));
+            None
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("🔍 Querying {} on {}", CONTRACT, NETWORK);
+    println!("RPCs: {}", RPC_URLS.join(", "));
+    println!();
+
+    let client: BlockingPolyEndpointClient = PolyEndpointClient::builder()
+        .contract_address(CONTRACT)
+        .rpc_urls(RPC_URLS)
+        .timeout(Duration::from_secs(10))
+        .build()?
+        .blocking()?;
+
+    let mut results = Vec::new();
+
+    let deployed = step(&mut results, "Check the contract is deployed", || {
+        match client.is_contract(NETWORK)? {
+            true => Ok(()),
+            false => Err(ClientError::NoContract(CONTRACT.to_string())),
+        }
+    });
+
+    if deployed.is_some() {
+        if let Some(owner) = step(&mut results, "Read the owner", || client.owner(NETWORK)) {
+            println!("   Owner: {}", owner);
+        }
+        if let Some(count) = step(&mut results, "Read the endpoint count", || client.get_endpoint_count(NETWORK)) {
+            println!("   Count: {}", count);
+        }
+        if let Some(endpoints) = step(&mut results, "Fetch the endpoints", || client.get_endpoints(NETWORK)) {
+            for (i, endpoint) in endpoints.iter().enumerate() {
+                println!("   {}. {} {}", i + 1, endpoint.url, endpoint.description);
+            }
+        }
+    }
+
+    println!();
+    println!("📋 Summary");
+    for (name, result) in &results {
+        match result {
+            Ok(()) => println!("  ✅ {}", name),
+            Err(e) => println!("  ❌ {}: {}", name, e),
+        }
+    }
+    let failed = results.iter().filter(|(_, r)| r.is_err()).count();
+    println!("{} of {} steps succeeded", results.len() - failed, results.len());
+
+    if failed > 0 {
+        return Err(format!("{} step(s) failed", failed).into());
+    }
+    Ok(())
+}
@@ -11,20 +11,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🔍 Fetching endpoints from Base Sepolia...");
     println!("Contract: {}", contract_address);
     println!("Network: base-sepolia");
-    println!("");
+    println!();
     
     let client = PolyEndpointClient::new(contract_address);
     
     match client.get_endpoints("base-sepolia").await {
         Ok(endpoints) => {
             println!("✅ Found {} endpoints:", endpoints.len());
-            println!("");
+            println!();
             
             for (i, endpoint) in endpoints.iter().enumerate() {
                 println!("Endpoint {}:", i + 1);
                 println!("  URL: {}", endpoint.url);
                 println!("  Description: {}", endpoint.description);
-                println!("");
+                println!();
             }
         }
         Err(e) => {
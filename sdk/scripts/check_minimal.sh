@@ -0,0 +1,29 @@
+#!/bin/bash
+# Check that the SDK builds without default features (no std, no network code)
+# and that nothing pulls reqwest, ethers or a TLS stack into that build
+
+set -e
+
+cd "$(dirname "$0")/.."
+
+echo "🔧 Building PolyEndpoint SDK with --no-default-features..."
+cargo build --no-default-features
+
+echo ""
+echo "🔍 Checking the dependency tree..."
+deps=$(cargo tree --no-default-features -e normal --prefix none --format "{p}")
+for forbidden in reqwest ethers hyper tokio rustls native-tls openssl; do
+    if echo "$deps" | grep -q "^$forbidden "; then
+        echo "❌ $forbidden is a dependency of the minimal build:"
+        cargo tree --no-default-features -e normal -i "$forbidden"
+        exit 1
+    fi
+done
+echo "   No network or TLS crates"
+
+echo ""
+echo "🧪 Running the minimal encoder tests..."
+cargo test --no-default-features --test minimal_test
+
+echo ""
+echo "✅ Minimal build OK"
@@ -0,0 +1,66 @@
+//! Synchronous client for scripts and other non-async code
+
+use core::future::Future;
+
+use crate::{ClientError, EndpointInfo, PolyEndpointClient};
+
+/// Run a future from this crate to completion on the current thread.
+///
+/// No runtime needs to be set up: requests run on the caller's tokio
+/// runtime if there is one, otherwise on a shared background thread (see the
+/// `any-runtime` feature). Like any `block_on`, it must not be called from
+/// inside an async task, where it would block the executor's thread.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    futures_executor::block_on(future)
+}
+
+/// Blocking wrapper around [`PolyEndpointClient`].
+///
+/// Each call runs the async read to completion with [`block_on`], so this
+/// must not be used from within an async context; use
+/// [`PolyEndpointClient`] directly instead.
+pub struct BlockingPolyEndpointClient {
+    inner: PolyEndpointClient,
+}
+
+impl BlockingPolyEndpointClient {
+    pub fn new(client: PolyEndpointClient) -> Result<Self, ClientError> {
+        Ok(Self { inner: client })
+    }
+
+    /// The wrapped async client
+    pub fn inner(&self) -> &PolyEndpointClient {
+        &self.inner
+    }
+
+    pub fn contract_address(&self) -> &str {
+        self.inner.contract_address()
+    }
+
+    /// See [`PolyEndpointClient::get_endpoints`]
+    pub fn get_endpoints(&self, network: impl AsRef<str>) -> Result<Vec<EndpointInfo>, ClientError> {
+        block_on(self.inner.get_endpoints(network))
+    }
+
+    /// See [`PolyEndpointClient::get_endpoint_count`]
+    pub fn get_endpoint_count(&self, network: impl AsRef<str>) -> Result<u64, ClientError> {
+        block_on(self.inner.get_endpoint_count(network))
+    }
+
+    /// See [`PolyEndpointClient::owner`]
+    pub fn owner(&self, network: impl AsRef<str>) -> Result<String, ClientError> {
+        block_on(self.inner.owner(network))
+    }
+
+    /// See [`PolyEndpointClient::is_contract`]
+    pub fn is_contract(&self, network: impl AsRef<str>) -> Result<bool, ClientError> {
+        block_on(self.inner.is_contract(network))
+    }
+}
+
+impl PolyEndpointClient {
+    /// Turn this client into a [`BlockingPolyEndpointClient`]
+    pub fn blocking(self) -> Result<BlockingPolyEndpointClient, ClientError> {
+        BlockingPolyEndpointClient::new(self)
+    }
+}
@@ -0,0 +1,386 @@
+//! Calldata builders for the PolyEndpoint contract
+//!
+//! Each `encode_*` function returns the [`TransactionData`] for one contract
+//! method, ready to be sent as the `data` of a transaction or `eth_call`.
+
+pub mod abi;
+#[cfg(feature = "ethers")]
+pub mod ethers_abi;
+
+#[cfg(feature = "ethers")]
+pub use ethers_abi::decode;
+
+use abi::{AbiDecoder, AbiEncoder, AbiToken};
+use crate::EndpointInfo;
+use alloc::{format, string::String, vec::Vec};
+
+/// Encoded calldata for a single contract call.
+///
+/// With the `std` feature it (de)serializes with `selector` and `args` as
+/// `0x`-prefixed hex strings, so prepared calls can be stored and replayed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransactionData {
+    #[cfg_attr(feature = "std", serde(with = "hex_bytes"))]
+    pub selector: [u8; 4],
+    #[cfg_attr(feature = "std", serde(with = "hex_bytes"))]
+    pub args: Vec<u8>,
+    /// Wei sent with the call; zero unless set with [`with_value`](Self::with_value)
+    pub value: u128,
+}
+
+impl TransactionData {
+    pub fn new(selector: [u8; 4], args: Vec<u8>) -> Self {
+        Self { selector, args, value: 0 }
+    }
+
+    /// Send `wei` with the call, for payable methods
+    pub fn with_value(mut self, wei: u128) -> Self {
+        self.value = wei;
+        self
+    }
+
+    /// Full calldata: the selector followed by the encoded arguments
+    pub fn build(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(4 + self.args.len());
+        data.extend_from_slice(&self.selector);
+        data.extend_from_slice(&self.args);
+        data
+    }
+
+    /// Full calldata as a `0x`-prefixed hex string
+    pub fn to_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.build()))
+    }
+
+    /// Parse existing calldata (`0x`-prefixed or bare hex) into its selector
+    /// and encoded arguments, e.g. to check what a pending transaction does.
+    /// The arguments are kept as raw bytes; `value` is zero.
+    pub fn from_hex(hex_str: &str) -> Result<Self, String> {
+        let trimmed = hex_str.trim();
+        let data = hex::decode(trimmed.strip_prefix("0x").unwrap_or(trimmed))
+            .map_err(|e| format!("Invalid calldata '{}': {}", hex_str, e))?;
+        if data.len() < 4 {
+            return Err(format!("Invalid calldata '{}': shorter than a 4-byte selector", hex_str));
+        }
+        let (selector, args) = data.split_at(4);
+        Ok(Self::new([selector[0], selector[1], selector[2], selector[3]], args.to_vec()))
+    }
+
+    /// Signature of the contract method this calls, e.g.
+    /// `addEndpoint(string,string)`, if the selector is one of [`method_id::SIGNATURES`]
+    pub fn selector_name(&self) -> Option<&'static str> {
+        method_id::SIGNATURES
+            .iter()
+            .find(|(_, selector)| *selector == self.selector)
+            .map(|(signature, _)| *signature)
+    }
+}
+
+/// Serde helpers writing byte fields as `0x`-prefixed hex strings
+#[cfg(feature = "std")]
+mod hex_bytes {
+    use alloc::{format, string::String, vec::Vec};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: impl AsRef<[u8]>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(bytes.as_ref())))
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: TryFrom<Vec<u8>>,
+    {
+        let hex_str = String::deserialize(deserializer)?;
+        let bytes = hex::decode(hex_str.strip_prefix("0x").unwrap_or(&hex_str)).map_err(D::Error::custom)?;
+        let len = bytes.len();
+        T::try_from(bytes).map_err(|_| D::Error::custom(format!("unexpected length {}", len)))
+    }
+}
+
+/// Selector of the standard `Error(string)` revert payload
+pub const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Decode an `Error(string)` revert payload into its message.
+///
+/// Returns `None` for custom errors, panics (`Panic(uint256)`) and anything
+/// that isn't a well-formed `Error(string)`.
+pub fn decode_revert_reason(data: &[u8]) -> Option<String> {
+    abi::decode_string(data.strip_prefix(&ERROR_STRING_SELECTOR)?)
+}
+
+/// Topic hashes (`topics[0]`) of the PolyEndpoint contract events
+pub mod event_topic {
+    use super::AbiEncoder;
+
+    pub fn endpoint_added() -> [u8; 32] {
+        AbiEncoder::keccak256("EndpointAdded(string,string)")
+    }
+
+    pub fn endpoint_removed() -> [u8; 32] {
+        AbiEncoder::keccak256("EndpointRemoved(string)")
+    }
+
+    pub fn admin_added() -> [u8; 32] {
+        AbiEncoder::keccak256("AdminAdded(address)")
+    }
+
+    pub fn admin_removed() -> [u8; 32] {
+        AbiEncoder::keccak256("AdminRemoved(address)")
+    }
+
+    pub fn ownership_transferred() -> [u8; 32] {
+        AbiEncoder::keccak256("OwnershipTransferred(address,address)")
+    }
+}
+
+/// Function selectors of the PolyEndpoint contract methods
+///
+/// The selectors are precomputed so builders don't re-hash the signature on
+/// every call; [`SIGNATURES`](method_id::SIGNATURES) lists each one next to
+/// its signature so tests can check them against
+/// [`AbiEncoder::function_selector`].
+pub mod method_id {
+    /// `addEndpoint(string)`
+    pub const ADD_ENDPOINT: [u8; 4] = [0x00, 0x7a, 0x53, 0xe3];
+    /// `addEndpoint(bytes32)`, for deployments keyed by URL hash
+    pub const ADD_ENDPOINT_BY_HASH: [u8; 4] = [0xb7, 0xb0, 0xfb, 0xd1];
+    /// `addEndpoint(string,string)`
+    pub const ADD_ENDPOINT_WITH_DESCRIPTION: [u8; 4] = [0x2c, 0x98, 0x6c, 0xce];
+    /// `removeEndpoint(string)`
+    pub const REMOVE_ENDPOINT: [u8; 4] = [0xc8, 0x40, 0x92, 0xbd];
+    /// `addAdmin(address)`
+    pub const ADD_ADMIN: [u8; 4] = [0x70, 0x48, 0x02, 0x75];
+    /// `removeAdmin(address)`
+    pub const REMOVE_ADMIN: [u8; 4] = [0x17, 0x85, 0xf5, 0x3c];
+    /// `transferOwnership(address)`
+    pub const TRANSFER_OWNERSHIP: [u8; 4] = [0xf2, 0xfd, 0xe3, 0x8b];
+    /// `getAllEndpoints()`
+    pub const GET_ALL_ENDPOINTS: [u8; 4] = [0x5a, 0x30, 0xe4, 0x6a];
+    /// `getEndpointCount()`
+    pub const GET_ENDPOINT_COUNT: [u8; 4] = [0xe4, 0x63, 0x8b, 0x84];
+    /// `getEndpoint(uint256)`
+    pub const GET_ENDPOINT: [u8; 4] = [0x93, 0x7b, 0xbc, 0x4a];
+    /// `hasEndpoint(string)`
+    pub const HAS_ENDPOINT: [u8; 4] = [0x79, 0xbd, 0x75, 0x25];
+    /// `hasEndpoint(bytes32)`
+    pub const HAS_ENDPOINT_BY_HASH: [u8; 4] = [0xf0, 0x04, 0x6d, 0x20];
+    /// `owner()`
+    pub const OWNER: [u8; 4] = [0x8d, 0xa5, 0xcb, 0x5b];
+    /// `admins(address)`
+    pub const ADMINS: [u8; 4] = [0x42, 0x9b, 0x62, 0xe5];
+
+    /// Every selector above with the signature it was derived from
+    pub const SIGNATURES: [(&str, [u8; 4]); 14] = [
+        ("addEndpoint(string)", ADD_ENDPOINT),
+        ("addEndpoint(bytes32)", ADD_ENDPOINT_BY_HASH),
+        ("addEndpoint(string,string)", ADD_ENDPOINT_WITH_DESCRIPTION),
+        ("removeEndpoint(string)", REMOVE_ENDPOINT),
+        ("addAdmin(address)", ADD_ADMIN),
+        ("removeAdmin(address)", REMOVE_ADMIN),
+        ("transferOwnership(address)", TRANSFER_OWNERSHIP),
+        ("getAllEndpoints()", GET_ALL_ENDPOINTS),
+        ("getEndpointCount()", GET_ENDPOINT_COUNT),
+        ("getEndpoint(uint256)", GET_ENDPOINT),
+        ("hasEndpoint(string)", HAS_ENDPOINT),
+        ("hasEndpoint(bytes32)", HAS_ENDPOINT_BY_HASH),
+        ("owner()", OWNER),
+        ("admins(address)", ADMINS),
+    ];
+
+    pub fn add_endpoint() -> [u8; 4] {
+        ADD_ENDPOINT
+    }
+
+    pub fn add_endpoint_by_hash() -> [u8; 4] {
+        ADD_ENDPOINT_BY_HASH
+    }
+
+    pub fn add_endpoint_with_description() -> [u8; 4] {
+        ADD_ENDPOINT_WITH_DESCRIPTION
+    }
+
+    pub fn remove_endpoint() -> [u8; 4] {
+        REMOVE_ENDPOINT
+    }
+
+    pub fn add_admin() -> [u8; 4] {
+        ADD_ADMIN
+    }
+
+    pub fn remove_admin() -> [u8; 4] {
+        REMOVE_ADMIN
+    }
+
+    pub fn transfer_ownership() -> [u8; 4] {
+        TRANSFER_OWNERSHIP
+    }
+
+    pub fn get_all_endpoints() -> [u8; 4] {
+        GET_ALL_ENDPOINTS
+    }
+
+    pub fn get_endpoint_count() -> [u8; 4] {
+        GET_ENDPOINT_COUNT
+    }
+
+    pub fn get_endpoint() -> [u8; 4] {
+        GET_ENDPOINT
+    }
+
+    pub fn has_endpoint() -> [u8; 4] {
+        HAS_ENDPOINT
+    }
+
+    pub fn has_endpoint_by_hash() -> [u8; 4] {
+        HAS_ENDPOINT_BY_HASH
+    }
+
+    pub fn owner() -> [u8; 4] {
+        OWNER
+    }
+
+    pub fn admins() -> [u8; 4] {
+        ADMINS
+    }
+}
+
+/// Encode a call to any method from its signature, e.g.
+/// `encode_call("addEndpoint(string,string)", &[AbiToken::String(..), AbiToken::String(..)])`.
+///
+/// The arguments are not checked against the signature's parameter types.
+pub fn encode_call(signature: &str, args: &[AbiToken]) -> Result<TransactionData, String> {
+    let params = AbiEncoder::encode_params(args)?;
+    Ok(TransactionData::new(AbiEncoder::function_selector(signature), params))
+}
+
+/// `addEndpoint(string url)`
+pub fn encode_add_endpoint(url: &str) -> TransactionData {
+    TransactionData::new(method_id::add_endpoint(), AbiEncoder::encode_string_args(&[url]))
+}
+
+/// `addEndpoint(bytes32 urlHash)`, for deployments that key endpoints by
+/// [`endpoint_hash`] instead of the URL string
+pub fn encode_add_endpoint_by_hash(hash: [u8; 32]) -> TransactionData {
+    TransactionData::new(method_id::add_endpoint_by_hash(), AbiEncoder::encode_bytes32(&hash).to_vec())
+}
+
+/// `addEndpoint(string url, string description)`
+pub fn encode_add_endpoint_with_description(url: &str, description: &str) -> TransactionData {
+    TransactionData::new(
+        method_id::add_endpoint_with_description(),
+        AbiEncoder::encode_string_args(&[url, description]),
+    )
+}
+
+/// `addEndpoint(string url)`, rejecting `url` unless it is an
+/// `http`/`https`/`ws`/`wss` URL.
+///
+/// Use [`encode_add_endpoint`] to store identifiers that aren't URLs.
+pub fn encode_add_endpoint_validated(url: &str) -> Result<TransactionData, String> {
+    crate::endpoint::validate_url(url)?;
+    Ok(encode_add_endpoint(url))
+}
+
+/// `removeEndpoint(string url)`
+pub fn encode_remove_endpoint(url: &str) -> TransactionData {
+    TransactionData::new(method_id::remove_endpoint(), AbiEncoder::encode_string_args(&[url]))
+}
+
+/// `addAdmin(address admin)`
+pub fn encode_add_admin(admin: &str) -> Result<TransactionData, String> {
+    let arg = AbiEncoder::encode_address(admin)?;
+    Ok(TransactionData::new(method_id::add_admin(), arg.to_vec()))
+}
+
+/// `removeAdmin(address admin)`
+pub fn encode_remove_admin(admin: &str) -> Result<TransactionData, String> {
+    let arg = AbiEncoder::encode_address(admin)?;
+    Ok(TransactionData::new(method_id::remove_admin(), arg.to_vec()))
+}
+
+/// `transferOwnership(address newOwner)`
+pub fn encode_transfer_ownership(new_owner: &str) -> Result<TransactionData, String> {
+    let arg = AbiEncoder::encode_address(new_owner)?;
+    Ok(TransactionData::new(method_id::transfer_ownership(), arg.to_vec()))
+}
+
+/// `getAllEndpoints()`
+pub fn encode_get_all_endpoints() -> TransactionData {
+    TransactionData::new(method_id::get_all_endpoints(), Vec::new())
+}
+
+/// `getEndpointCount()`
+pub fn encode_get_endpoint_count() -> TransactionData {
+    TransactionData::new(method_id::get_endpoint_count(), Vec::new())
+}
+
+/// `getEndpoint(uint256 index)`
+pub fn encode_get_endpoint(index: u64) -> TransactionData {
+    TransactionData::new(method_id::get_endpoint(), AbiEncoder::encode_uint256(index).to_vec())
+}
+
+/// `hasEndpoint(string url)`
+pub fn encode_has_endpoint(url: &str) -> TransactionData {
+    TransactionData::new(method_id::has_endpoint(), AbiEncoder::encode_string_args(&[url]))
+}
+
+/// `hasEndpoint(bytes32 urlHash)`; see [`encode_add_endpoint_by_hash`]
+pub fn encode_has_endpoint_by_hash(hash: [u8; 32]) -> TransactionData {
+    TransactionData::new(method_id::has_endpoint_by_hash(), AbiEncoder::encode_bytes32(&hash).to_vec())
+}
+
+/// The `bytes32` key hashed-key deployments use for `url`: `keccak256(bytes(url))`
+pub fn endpoint_hash(url: &str) -> [u8; 32] {
+    AbiEncoder::keccak256(url)
+}
+
+/// Decode the `(string url, string description)` tuple returned by
+/// [`encode_get_endpoint`]'s call
+pub fn decode_endpoint(result: &[u8]) -> Result<EndpointInfo, String> {
+    let url = AbiDecoder::decode_string(result, 0)
+        .map_err(|e| format!("Invalid getEndpoint result: bad url: {}", e))?;
+    let description = AbiDecoder::decode_string(result, abi::WORD_SIZE)
+        .map_err(|e| format!("Invalid getEndpoint result: bad description: {}", e))?;
+    Ok(EndpointInfo { url, description })
+}
+
+/// Decode the `(string[] urls, string[] descriptions)` returned by
+/// [`encode_get_all_endpoints`]'s call, pairing them up in order
+pub fn decode_all_endpoints(result: &[u8]) -> Result<Vec<EndpointInfo>, String> {
+    let urls = AbiDecoder::decode_string_array(result, 0)
+        .map_err(|e| format!("Invalid getAllEndpoints result: bad urls: {}", e))?;
+    let descriptions = AbiDecoder::decode_string_array(result, abi::WORD_SIZE)
+        .map_err(|e| format!("Invalid getAllEndpoints result: bad descriptions: {}", e))?;
+    if urls.len() != descriptions.len() {
+        return Err(format!("{} urls but {} descriptions", urls.len(), descriptions.len()));
+    }
+    Ok(urls
+        .into_iter()
+        .zip(descriptions)
+        .map(|(url, description)| EndpointInfo { url, description })
+        .collect())
+}
+
+/// `owner()`
+pub fn encode_owner() -> TransactionData {
+    TransactionData::new(method_id::owner(), Vec::new())
+}
+
+/// `admins(address account)`
+pub fn encode_is_admin(account: &str) -> Result<TransactionData, String> {
+    let arg = AbiEncoder::encode_address(account)?;
+    Ok(TransactionData::new(method_id::admins(), arg.to_vec()))
+}
+
+/// Decode the `address` returned by [`encode_owner`]'s call as `0x`-prefixed
+/// lowercase hex
+pub fn decode_address(result: &[u8]) -> Result<String, String> {
+    AbiDecoder::decode_address(result, 0).map_err(|e| format!("Invalid address result: {}", e))
+}
+
+/// Decode the `bool` returned by [`encode_is_admin`]'s call
+pub fn decode_bool(result: &[u8]) -> Result<bool, String> {
+    AbiDecoder::decode_bool(result, 0).map_err(|e| format!("Invalid bool result: {}", e))
+}
@@ -0,0 +1,310 @@
+//! Minimal ABI encoding helpers
+//!
+//! Hand-rolled encoders for the handful of Solidity types the PolyEndpoint
+//! contract uses, so calldata can be built without pulling in ethers, and
+//! [`AbiDecoder`] for reading the same types back out of return values,
+//! revert payloads and event data.
+
+use alloc::{format, string::String, vec::Vec};
+use sha3::{Digest, Keccak256};
+
+/// ABI word size in bytes
+pub const WORD_SIZE: usize = 32;
+
+/// A value to ABI-encode with [`AbiEncoder::encode_params`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AbiToken {
+    /// `address`, as `0x`-prefixed (or bare) hex
+    Address(String),
+    /// `uint256`
+    Uint(u64),
+    Bool(bool),
+    /// `bytes32`
+    FixedBytes32([u8; 32]),
+    /// Dynamic `string`
+    String(String),
+    /// Dynamic `bytes`
+    Bytes(Vec<u8>),
+}
+
+impl AbiToken {
+    fn is_dynamic(&self) -> bool {
+        matches!(self, AbiToken::String(_) | AbiToken::Bytes(_))
+    }
+}
+
+/// Keccak-256 hash of `data`
+pub fn keccak256(data: impl AsRef<[u8]>) -> [u8; 32] {
+    Keccak256::digest(data.as_ref()).into()
+}
+
+/// 4-byte function selector for a signature like `addEndpoint(string)`
+pub fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+pub struct AbiEncoder;
+
+impl AbiEncoder {
+    /// Keccak-256 hash of `data`; see [`keccak256`]
+    pub fn keccak256(data: impl AsRef<[u8]>) -> [u8; 32] {
+        keccak256(data)
+    }
+
+    /// 4-byte function selector for a signature; see [`selector`]
+    pub fn function_selector(signature: &str) -> [u8; 4] {
+        selector(signature)
+    }
+
+    /// Encode an unsigned integer as a big-endian 32-byte word
+    pub fn encode_uint256(value: u64) -> [u8; 32] {
+        let mut word = [0u8; WORD_SIZE];
+        word[24..].copy_from_slice(&value.to_be_bytes());
+        word
+    }
+
+    /// Encode a non-negative decimal integer of any size up to `2^256 - 1`
+    /// as a big-endian 32-byte word
+    pub fn encode_uint256_decimal(value: &str) -> Result<[u8; 32], String> {
+        if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("Invalid integer '{}': expected decimal digits", value));
+        }
+
+        let mut word = [0u8; WORD_SIZE];
+        for digit in value.bytes().map(|b| b - b'0') {
+            // word = word * 10 + digit
+            let mut carry = digit as u16;
+            for byte in word.iter_mut().rev() {
+                let v = *byte as u16 * 10 + carry;
+                *byte = v as u8;
+                carry = v >> 8;
+            }
+            if carry != 0 {
+                return Err(format!("Invalid integer '{}': does not fit in uint256", value));
+            }
+        }
+        Ok(word)
+    }
+
+    /// Encode a bool as a 32-byte word
+    pub fn encode_bool(value: bool) -> [u8; 32] {
+        Self::encode_uint256(value as u64)
+    }
+
+    /// Encode a `0x`-prefixed (or bare) 20-byte hex address as a left-padded word
+    pub fn encode_address(address: &str) -> Result<[u8; 32], String> {
+        let hex_str = address.strip_prefix("0x").unwrap_or(address);
+        if hex_str.len() != 40 {
+            return Err(format!("Invalid address '{}': expected 20 bytes", address));
+        }
+        let bytes = hex::decode(hex_str)
+            .map_err(|e| format!("Invalid address '{}': {}", address, e))?;
+
+        let mut word = [0u8; WORD_SIZE];
+        word[12..].copy_from_slice(&bytes);
+        Ok(word)
+    }
+
+    /// Encode a `bytes32` value (already one word)
+    pub fn encode_bytes32(value: &[u8; 32]) -> [u8; 32] {
+        *value
+    }
+
+    /// Encode the tail of a dynamic `string`: its byte length followed by the
+    /// UTF-8 bytes right-padded to a multiple of 32
+    pub fn encode_string(s: &str) -> Vec<u8> {
+        Self::encode_bytes(s.as_bytes())
+    }
+
+    /// Encode the tail of a dynamic `bytes`: its length followed by the data
+    /// right-padded to a multiple of 32
+    pub fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+        let padded_len = bytes.len().div_ceil(WORD_SIZE) * WORD_SIZE;
+
+        let mut encoded = Vec::with_capacity(WORD_SIZE + padded_len);
+        encoded.extend_from_slice(&Self::encode_uint256(bytes.len() as u64));
+        encoded.extend_from_slice(bytes);
+        encoded.resize(WORD_SIZE + padded_len, 0);
+        encoded
+    }
+
+    /// Encode a list of `string` parameters: one offset word per argument,
+    /// followed by each string's tail
+    pub fn encode_string_args(args: &[&str]) -> Vec<u8> {
+        let tails: Vec<Vec<u8>> = args.iter().map(|s| Self::encode_string(s)).collect();
+
+        let mut head = Vec::with_capacity(args.len() * WORD_SIZE);
+        let mut offset = args.len() * WORD_SIZE;
+        for tail in &tails {
+            head.extend_from_slice(&Self::encode_uint256(offset as u64));
+            offset += tail.len();
+        }
+
+        head.extend(tails.into_iter().flatten());
+        head
+    }
+
+    /// Encode a single `string[]` parameter, as for `addEndpoints(string[])`:
+    /// the offset of the array, its length, then the strings laid out like
+    /// [`encode_string_args`](Self::encode_string_args) (an offset table
+    /// relative to the first element's slot, followed by each string's tail)
+    pub fn encode_string_array(items: &[&str]) -> Vec<u8> {
+        let elements = Self::encode_string_args(items);
+
+        let mut encoded = Vec::with_capacity(2 * WORD_SIZE + elements.len());
+        encoded.extend_from_slice(&Self::encode_uint256(WORD_SIZE as u64));
+        encoded.extend_from_slice(&Self::encode_uint256(items.len() as u64));
+        encoded.extend(elements);
+        encoded
+    }
+
+    /// Encode a parameter list: static values inline in the head, dynamic
+    /// values as an offset word pointing at their tail
+    pub fn encode_params(tokens: &[AbiToken]) -> Result<Vec<u8>, String> {
+        let mut head = Vec::with_capacity(tokens.len() * WORD_SIZE);
+        let mut tail = Vec::new();
+        let head_len = tokens.len() * WORD_SIZE;
+
+        for token in tokens {
+            if token.is_dynamic() {
+                head.extend_from_slice(&Self::encode_uint256((head_len + tail.len()) as u64));
+            }
+            match token {
+                AbiToken::Address(address) => head.extend_from_slice(&Self::encode_address(address)?),
+                AbiToken::Uint(value) => head.extend_from_slice(&Self::encode_uint256(*value)),
+                AbiToken::Bool(value) => head.extend_from_slice(&Self::encode_bool(*value)),
+                AbiToken::FixedBytes32(value) => head.extend_from_slice(&Self::encode_bytes32(value)),
+                AbiToken::String(s) => tail.extend(Self::encode_string(s)),
+                AbiToken::Bytes(bytes) => tail.extend(Self::encode_bytes(bytes)),
+            }
+        }
+
+        head.extend(tail);
+        Ok(head)
+    }
+
+    /// Decode a word written by [`encode_uint256`](Self::encode_uint256),
+    /// rejecting short input and values that don't fit in a `u64`
+    pub fn decode_uint256(word: &[u8]) -> Result<u64, String> {
+        let word = word.get(..WORD_SIZE)
+            .ok_or_else(|| format!("Expected a {}-byte word, got {} bytes", WORD_SIZE, word.len()))?;
+        let (high, low) = word.split_at(WORD_SIZE - 8);
+        if high.iter().any(|&b| b != 0) {
+            return Err("Value does not fit in u64".into());
+        }
+        Ok(u64::from_be_bytes(low.try_into().expect("8 bytes")))
+    }
+
+    /// Decode a `string` tail written by [`encode_string`](Self::encode_string):
+    /// a length word followed by that many UTF-8 bytes. Trailing padding is
+    /// ignored.
+    pub fn decode_string(data: &[u8]) -> Result<String, String> {
+        let len = usize::try_from(Self::decode_uint256(data)?)
+            .map_err(|_| String::from("String length does not fit in usize"))?;
+        let bytes = WORD_SIZE.checked_add(len)
+            .and_then(|end| data.get(WORD_SIZE..end))
+            .ok_or_else(|| format!(
+                "String of length {} runs past the end of the {}-byte data",
+                len,
+                data.len()
+            ))?;
+        String::from_utf8(bytes.to_vec()).map_err(|e| format!("String is not UTF-8: {}", e))
+    }
+}
+
+/// Bounds-checked decoders for ABI-encoded data, the counterpart of
+/// [`AbiEncoder`].
+///
+/// Each method reads the value whose head word is at byte `offset` of
+/// `data`. For a static type that word is the value itself; for a dynamic
+/// one it holds the position of the value's tail, relative to the start of
+/// `data`. Offsets and lengths that point outside `data` are errors, never
+/// panics.
+pub struct AbiDecoder;
+
+impl AbiDecoder {
+    /// Decode a `uint256` that must fit in a `u64`
+    pub fn decode_uint256(data: &[u8], offset: usize) -> Result<u64, String> {
+        AbiEncoder::decode_uint256(Self::word(data, offset)?)
+    }
+
+    /// Decode an `address` as `0x`-prefixed lowercase hex, rejecting a word
+    /// whose 12 padding bytes aren't zero
+    pub fn decode_address(data: &[u8], offset: usize) -> Result<String, String> {
+        let (padding, address) = Self::word(data, offset)?.split_at(12);
+        if padding.iter().any(|&b| b != 0) {
+            return Err(format!("Address at offset {} has nonzero padding", offset));
+        }
+        Ok(format!("0x{}", hex::encode(address)))
+    }
+
+    /// Decode a `bool`, rejecting any word other than 0 or 1
+    pub fn decode_bool(data: &[u8], offset: usize) -> Result<bool, String> {
+        match Self::decode_uint256(data, offset) {
+            Ok(0) => Ok(false),
+            Ok(1) => Ok(true),
+            _ => Err(format!("Word at offset {} is not a bool", offset)),
+        }
+    }
+
+    /// Decode a dynamic `string`
+    pub fn decode_string(data: &[u8], offset: usize) -> Result<String, String> {
+        let tail = Self::tail(data, offset)?;
+        AbiEncoder::decode_string(tail)
+    }
+
+    /// Decode a dynamic `string[]`. The offsets of its elements are relative
+    /// to the array's first element slot (the word after its length), not to
+    /// the start of `data`, so the array may sit anywhere in it.
+    pub fn decode_string_array(data: &[u8], offset: usize) -> Result<Vec<String>, String> {
+        let array = Self::tail(data, offset)?;
+        let len = Self::decode_usize(array, 0)?;
+        let elements = &array[WORD_SIZE..];
+        (0..len)
+            .map(|i| {
+                let head = i.checked_mul(WORD_SIZE)
+                    .ok_or_else(|| format!("Array length {} is out of bounds", len))?;
+                Self::decode_string(elements, head).map_err(|e| format!("String {}: {}", i, e))
+            })
+            .collect()
+    }
+
+    /// Decode a length or offset word, rejecting values that don't fit in a `usize`
+    pub fn decode_usize(data: &[u8], offset: usize) -> Result<usize, String> {
+        let value = Self::decode_uint256(data, offset)?;
+        usize::try_from(value).map_err(|_| format!("Length or offset {} does not fit in usize", value))
+    }
+
+    /// The 32-byte word starting at `offset`
+    fn word(data: &[u8], offset: usize) -> Result<&[u8], String> {
+        offset.checked_add(WORD_SIZE)
+            .and_then(|end| data.get(offset..end))
+            .ok_or_else(|| format!("Offset {} points past the end of the {}-byte data", offset, data.len()))
+    }
+
+    /// The data of a dynamic value whose offset word is at `offset`
+    fn tail(data: &[u8], offset: usize) -> Result<&[u8], String> {
+        let start = Self::decode_usize(data, offset)?;
+        data.get(start..)
+            .filter(|tail| tail.len() >= WORD_SIZE)
+            .ok_or_else(|| format!("Offset {} points past the end of the {}-byte data", start, data.len()))
+    }
+}
+
+/// Read the word at byte `pos` as a `usize`, rejecting values that don't fit
+pub fn decode_usize(data: &[u8], pos: usize) -> Option<usize> {
+    AbiDecoder::decode_usize(data, pos).ok()
+}
+
+/// Decode a single `string` encoded as the first (and only) parameter:
+/// an offset word pointing at the length-prefixed UTF-8 bytes
+pub fn decode_string(data: &[u8]) -> Option<String> {
+    decode_string_at(data, 0)
+}
+
+/// Decode the `string` whose offset word is at byte `head_pos` of a
+/// parameter list
+pub fn decode_string_at(data: &[u8], head_pos: usize) -> Option<String> {
+    AbiDecoder::decode_string(data, head_pos).ok()
+}
@@ -0,0 +1,15 @@
+//! General-purpose ABI decoding through ethers (`ethers` feature)
+//!
+//! The decoders in [`contract`](super) are hand-rolled for the handful of
+//! types PolyEndpoint returns and need no dependencies. [`decode`] handles
+//! any ABI type, for calls to other methods or contracts.
+
+pub use ethers::abi::{ParamType, Token};
+
+use crate::ClientError;
+
+/// Decode ABI-encoded `data`, such as an `eth_call` result, into one
+/// [`Token`] per entry of `types`
+pub fn decode(types: &[ParamType], data: &[u8]) -> Result<Vec<Token>, ClientError> {
+    ethers::abi::decode(types, data).map_err(|e| ClientError::Decode(format!("ABI decode: {}", e)))
+}
@@ -1,8 +1,12 @@
 //! Endpoint information types
 
+use alloc::{format, string::String};
+
+#[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct EndpointInfo {
     pub url: String,
     pub description: String,
@@ -23,5 +27,25 @@ impl EndpointInfo {
     pub fn description(&self) -> &str {
         &self.description
     }
+
+    /// Check that `url` is an `http`, `https`, `ws` or `wss` URL
+    pub fn validate(&self) -> Result<(), String> {
+        validate_url(&self.url)
+    }
+}
+
+/// Check that `url` parses as an `http`, `https`, `ws` or `wss` URL with a host
+pub(crate) fn validate_url(url: &str) -> Result<(), String> {
+    let parsed = url::Url::parse(url).map_err(|e| format!("Invalid endpoint URL '{}': {}", url, e))?;
+    if !matches!(parsed.scheme(), "http" | "https" | "ws" | "wss") {
+        return Err(format!(
+            "Invalid endpoint URL '{}': scheme must be http, https, ws or wss",
+            url
+        ));
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err(format!("Invalid endpoint URL '{}': missing host", url));
+    }
+    Ok(())
 }
 
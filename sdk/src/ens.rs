@@ -0,0 +1,69 @@
+//! ENS name resolution
+//!
+//! Resolves `.eth` names through the ENS registry with plain `eth_call`s:
+//! `resolver(namehash)` on the registry, then `addr(namehash)` on the
+//! returned resolver.
+
+use crate::contract::abi::AbiEncoder;
+use crate::simple_client::{eth_call, get_rpc_urls, parse_address, BlockId, ClientError, Rpc};
+
+/// ENS registry, deployed at the same address on mainnet and Sepolia
+pub const ENS_REGISTRY: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+
+/// EIP-137 namehash of a dot-separated name.
+///
+/// Labels are lowercased but otherwise not normalized, which covers plain
+/// ASCII names.
+pub fn namehash(name: &str) -> [u8; 32] {
+    let name = name.to_lowercase();
+    let mut node = [0u8; 32];
+    if name.is_empty() {
+        return node;
+    }
+    for label in name.rsplit('.') {
+        let label_hash = AbiEncoder::keccak256(label);
+        node = AbiEncoder::keccak256([node, label_hash].concat());
+    }
+    node
+}
+
+/// Whether an address argument should be treated as an ENS name
+pub fn is_ens_name(input: &str) -> bool {
+    input.to_lowercase().ends_with(".eth")
+}
+
+/// Resolve an ENS name to its EIP-55 checksummed address.
+///
+/// Fails if the name has no resolver or resolves to the zero address.
+pub async fn resolve_name(network: impl AsRef<str>, name: &str) -> Result<String, ClientError> {
+    let rpc = Rpc::urls(get_rpc_urls(network.as_ref())?);
+    let node = namehash(name);
+
+    let resolver = read_address(eth_call(&rpc, ENS_REGISTRY, &call("resolver(bytes32)", &node), BlockId::Latest).await?)?
+        .ok_or_else(|| ClientError::InvalidAddress(format!("ENS name '{}' has no resolver", name)))?;
+    let address = read_address(eth_call(&rpc, &resolver, &call("addr(bytes32)", &node), BlockId::Latest).await?)?
+        .ok_or_else(|| ClientError::InvalidAddress(format!("ENS name '{}' does not resolve to an address", name)))?;
+    Ok(address)
+}
+
+/// Resolve `input` if it is an ENS name, otherwise validate and checksum it as a hex address
+pub async fn resolve_address(network: impl AsRef<str>, input: &str) -> Result<String, ClientError> {
+    if is_ens_name(input) {
+        resolve_name(network, input).await
+    } else {
+        Ok(ethers::utils::to_checksum(&parse_address(input)?, None))
+    }
+}
+
+fn call(signature: &str, node: &[u8; 32]) -> Vec<u8> {
+    [&AbiEncoder::function_selector(signature)[..], node].concat()
+}
+
+/// Decode an `address` return value; `None` for the zero address
+fn read_address(result: Vec<u8>) -> Result<Option<String>, ClientError> {
+    let word: [u8; 32] = result.get(..32)
+        .and_then(|w| w.try_into().ok())
+        .ok_or_else(|| ClientError::Decode("Short address return value".to_string()))?;
+    let address = ethers::types::Address::from_slice(&word[12..]);
+    Ok((!address.is_zero()).then(|| ethers::utils::to_checksum(&address, None)))
+}
@@ -0,0 +1,121 @@
+//! Ownership and admin change logs
+//!
+//! Endpoint changes are decoded by [`EndpointEvent`](crate::EndpointEvent);
+//! this module covers the contract's other audit-worthy events,
+//! `OwnershipTransferred(address,address)`, `AdminAdded(address)` and
+//! `AdminRemoved(address)`.
+
+use ethers::types::Address;
+use ethers::utils::to_checksum;
+use serde::{Deserialize, Serialize};
+
+use crate::contract::event_topic;
+use crate::simple_client::{BlockId, ClientError, PolyEndpointClient};
+
+/// What an [`AdminEvent`] changed. Addresses are EIP-55 checksummed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AdminEventKind {
+    AdminAdded { admin: String },
+    AdminRemoved { admin: String },
+    /// The contract rejects the zero address as `new_owner`, so ownership
+    /// can be handed on but never renounced
+    OwnershipTransferred { previous_owner: String, new_owner: String },
+}
+
+/// An `AdminAdded`, `AdminRemoved` or `OwnershipTransferred` log
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdminEvent {
+    pub kind: AdminEventKind,
+    pub block: u64,
+    pub transaction_hash: String,
+}
+
+impl AdminEvent {
+    /// Decode one log object from an `eth_getLogs` result
+    pub fn from_log(log: &serde_json::Value) -> Result<Self, ClientError> {
+        let field = |name: &str| {
+            log.get(name)
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| ClientError::Decode(format!("Log is missing '{}'", name)))
+        };
+        let topics: Vec<[u8; 32]> = log.get("topics")
+            .and_then(|t| t.as_array())
+            .ok_or_else(|| ClientError::Decode("Log is missing 'topics'".to_string()))?
+            .iter()
+            .map(|topic| {
+                topic.as_str()
+                    .ok_or_else(|| ClientError::Decode(format!("Topic {} is not a string", topic)))
+                    .and_then(topic_word)
+            })
+            .collect::<Result<_, _>>()?;
+
+        let kind = match topics.as_slice() {
+            [signature, admin] if *signature == event_topic::admin_added() => {
+                AdminEventKind::AdminAdded { admin: topic_address(admin)? }
+            }
+            [signature, admin] if *signature == event_topic::admin_removed() => {
+                AdminEventKind::AdminRemoved { admin: topic_address(admin)? }
+            }
+            [signature, previous_owner, new_owner] if *signature == event_topic::ownership_transferred() => {
+                AdminEventKind::OwnershipTransferred {
+                    previous_owner: topic_address(previous_owner)?,
+                    new_owner: topic_address(new_owner)?,
+                }
+            }
+            [signature, ..] => {
+                return Err(ClientError::Decode(format!("Unexpected event topic 0x{}", hex::encode(signature))))
+            }
+            [] => return Err(ClientError::Decode("Log has no topics".to_string())),
+        };
+
+        let block_hex = field("blockNumber")?;
+        let block = u64::from_str_radix(block_hex.trim_start_matches("0x"), 16)
+            .map_err(|e| ClientError::Decode(format!("Bad block number '{}': {}", block_hex, e)))?;
+
+        Ok(Self {
+            kind,
+            block,
+            transaction_hash: field("transactionHash")?.to_string(),
+        })
+    }
+}
+
+fn topic_word(hex_str: &str) -> Result<[u8; 32], ClientError> {
+    let mut out = [0u8; 32];
+    hex::decode_to_slice(hex_str.trim_start_matches("0x"), &mut out)
+        .map_err(|e| ClientError::Decode(format!("Bad topic '{}': {}", hex_str, e)))?;
+    Ok(out)
+}
+
+/// An indexed `address` topic: 12 zero bytes, then the address
+fn topic_address(word: &[u8; 32]) -> Result<String, ClientError> {
+    let (padding, address) = word.split_at(12);
+    if padding.iter().any(|&b| b != 0) {
+        return Err(ClientError::Decode(format!("Topic 0x{} is not an address", hex::encode(word))));
+    }
+    Ok(to_checksum(&Address::from_slice(address), None))
+}
+
+impl PolyEndpointClient {
+    /// Fetch `OwnershipTransferred`, `AdminAdded` and `AdminRemoved` logs
+    /// between two blocks (inclusive), in the order the node returns them
+    /// (oldest first).
+    ///
+    /// Like [`get_endpoint_events`](Self::get_endpoint_events), block hashes
+    /// are rejected and public RPCs may cap the range size.
+    #[tracing::instrument(level = "debug", skip_all, fields(contract = %self.contract_address(), network = network.as_ref(), ?from_block, ?to_block))]
+    pub async fn get_admin_events(
+        &self,
+        network: impl AsRef<str>,
+        from_block: BlockId,
+        to_block: BlockId,
+    ) -> Result<Vec<AdminEvent>, ClientError> {
+        let topics = [
+            event_topic::ownership_transferred(),
+            event_topic::admin_added(),
+            event_topic::admin_removed(),
+        ];
+        let logs = self.get_logs(network.as_ref(), from_block, to_block, &topics).await?;
+        logs.iter().map(AdminEvent::from_log).collect()
+    }
+}
@@ -1,9 +1,18 @@
 //! Native HTTP implementation using reqwest
 
-use crate::simple_client::ClientError;
+use crate::simple_client::{check_http_status, now, request_error, trace_rpc_request, trace_rpc_response, ClientError};
 use serde_json::Value;
 
+#[allow(dead_code)]
 pub async fn make_rpc_call(url: &str, payload: &Value) -> Result<String, ClientError> {
+    let started = now();
+    trace_rpc_request(url, payload);
+    let result = post(url, payload).await;
+    trace_rpc_response(url, payload, started, &result);
+    result
+}
+
+async fn post(url: &str, payload: &Value) -> Result<String, ClientError> {
     let client = reqwest::Client::new();
     
     let body_str = serde_json::to_string(payload)
@@ -15,13 +24,14 @@ pub async fn make_rpc_call(url: &str, payload: &Value) -> Result<String, ClientE
         .body(body_str)
         .send()
         .await
-        .map_err(|e| ClientError::Network(format!("Request failed: {}", e)))?;
+        .map_err(request_error)?;
     
+    let status = response.status().as_u16();
     let text = response
         .text()
         .await
-        .map_err(|e| ClientError::Network(format!("Failed to read response: {}", e)))?;
+        .map_err(request_error)?;
     
-    Ok(text)
+    check_http_status(status, text)
 }
 
@@ -1,48 +1,124 @@
 //! WASM HTTP implementation using web-sys
 
-use crate::simple_client::ClientError;
+use crate::simple_client::{check_http_status, now, trace_rpc_request, trace_rpc_response, ClientError};
+use core::time::Duration;
 use serde_json::Value;
-use wasm_bindgen::prelude::*;
+use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
 
+#[allow(dead_code)]
 pub async fn make_rpc_call(url: &str, payload: &Value) -> Result<String, ClientError> {
+    let started = now();
+    trace_rpc_request(url, payload);
+    let result = post(url, &[], None, payload).await;
+    trace_rpc_response(url, payload, started, &result);
+    result
+}
+
+/// POST `payload` with `fetch`.
+///
+/// With a `timeout`, the request (including reading the body) is aborted
+/// through an `AbortController` once it elapses and fails with
+/// [`ClientError::Timeout`]. Dropping the returned future aborts the request
+/// too, so a cancelled read doesn't leave a `fetch` running.
+pub(crate) async fn post(
+    url: &str,
+    headers: &[(String, String)],
+    timeout: Option<Duration>,
+    payload: &Value,
+) -> Result<String, ClientError> {
     let window = web_sys::window()
         .ok_or_else(|| ClientError::Network("No window object".to_string()))?;
-    
-    let init = init_request(payload);
+    let abort = AbortGuard::new(&window, timeout)?;
+
+    let init = init_request(headers, payload)?;
+    init.set_signal(Some(&abort.controller.signal()));
     let fetch_promise = window.fetch_with_str_and_init(url, &init);
-    
+
     let resp_value = JsFuture::from(fetch_promise)
         .await
-        .map_err(|e| ClientError::Network(format!("Fetch failed: {:?}", e)))?;
-    
+        .map_err(|e| abort.error(format!("Fetch failed: {:?}", e)))?;
+
     let resp: web_sys::Response = resp_value
         .dyn_into()
         .map_err(|e| ClientError::Network(format!("Not a Response: {:?}", e)))?;
-    
+
     let text_promise = resp.text()
         .map_err(|e| ClientError::Network(format!("Could not get text: {:?}", e)))?;
-    
+
     let text = JsFuture::from(text_promise)
         .await
-        .map_err(|e| ClientError::Network(format!("Text future failed: {:?}", e)))?;
-    
-    Ok(text.as_string().ok_or_else(|| ClientError::Network("No text returned".to_string()))?)
+        .map_err(|e| abort.error(format!("Reading the response failed: {:?}", e)))?;
+
+    let body = text.as_string().ok_or_else(|| ClientError::Network("No text returned".to_string()))?;
+    check_http_status(resp.status(), body)
 }
 
-fn init_request(payload: &Value) -> web_sys::RequestInit {
-    let mut opts = web_sys::RequestInit::new();
+fn init_request(headers: &[(String, String)], payload: &Value) -> Result<web_sys::RequestInit, ClientError> {
+    let opts = web_sys::RequestInit::new();
     opts.set_method("POST");
-    
-    let headers = web_sys::Headers::new().unwrap();
-    headers.set("Content-Type", "application/json").unwrap();
-    opts.set_headers(&headers.into());
-    
-    let body_str = serde_json::to_string(payload).unwrap();
-    let body = wasm_bindgen::JsValue::from_str(&body_str);
-    opts.set_body(&body);
-    
-    opts
+
+    let request_headers = web_sys::Headers::new()
+        .map_err(|e| ClientError::Network(format!("Headers: {:?}", e)))?;
+    request_headers.set("Content-Type", "application/json")
+        .map_err(|e| ClientError::Network(format!("Header Content-Type: {:?}", e)))?;
+    for (name, value) in headers {
+        request_headers.set(name, value)
+            .map_err(|e| ClientError::Network(format!("Header {}: {:?}", name, e)))?;
+    }
+    opts.set_headers(&request_headers.into());
+
+    opts.set_body(&wasm_bindgen::JsValue::from_str(&payload.to_string()));
+    Ok(opts)
+}
+
+/// Aborts a `fetch` when its timer fires, and on drop clears the timer and
+/// aborts whatever is still in flight (a no-op once the body has been read)
+struct AbortGuard {
+    window: web_sys::Window,
+    controller: web_sys::AbortController,
+    /// The `setTimeout` handle, and the callback it must outlive
+    timer: Option<(i32, Closure<dyn FnMut()>)>,
 }
 
+impl AbortGuard {
+    fn new(window: &web_sys::Window, timeout: Option<Duration>) -> Result<Self, ClientError> {
+        let controller = web_sys::AbortController::new()
+            .map_err(|e| ClientError::Network(format!("AbortController: {:?}", e)))?;
+
+        let timer = match timeout {
+            Some(timeout) => {
+                let on_timeout = controller.clone();
+                let callback = Closure::<dyn FnMut()>::new(move || on_timeout.abort());
+                let millis = i32::try_from(timeout.as_millis()).unwrap_or(i32::MAX);
+                let handle = window
+                    .set_timeout_with_callback_and_timeout_and_arguments_0(callback.as_ref().unchecked_ref(), millis)
+                    .map_err(|e| ClientError::Network(format!("setTimeout: {:?}", e)))?;
+                Some((handle, callback))
+            }
+            None => None,
+        };
+
+        Ok(Self { window: window.clone(), controller, timer })
+    }
+
+    /// [`ClientError::Timeout`] if the timer aborted the request, otherwise a
+    /// network error with `message`
+    fn error(&self, message: String) -> ClientError {
+        if self.controller.signal().aborted() {
+            ClientError::Timeout
+        } else {
+            ClientError::Network(message)
+        }
+    }
+}
+
+impl Drop for AbortGuard {
+    fn drop(&mut self) {
+        if let Some((handle, _)) = &self.timer {
+            self.window.clear_timeout_with_handle(*handle);
+        }
+        self.controller.abort();
+    }
+}
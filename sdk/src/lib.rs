@@ -1,10 +1,49 @@
 //! PolyEndpoint SDK
 //! Simple SDK that fetches endpoint lists from PolyEndpoint smart contract
+//!
+//! With the default `std` feature disabled the crate is `no_std` + `alloc`
+//! and only the [`contract`] calldata builders are available.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(all(feature = "rustls", feature = "native-tls"))]
+compile_error!("features `rustls` and `native-tls` are mutually exclusive; disable default features to use `native-tls`");
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
 mod simple_client;
 mod endpoint;
+#[cfg(feature = "std")]
+pub mod ens;
+#[cfg(feature = "std")]
+pub mod networks;
+#[cfg(feature = "std")]
+pub mod events;
+#[cfg(feature = "std")]
 mod http_impl;
+pub mod contract;
+#[cfg(all(target_arch = "wasm32", feature = "std"))]
+mod wasm;
+#[cfg(all(feature = "ws", not(target_arch = "wasm32")))]
+mod ws;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+mod blocking;
+#[cfg(all(feature = "ethers", not(target_arch = "wasm32")))]
+mod middleware;
 
-pub use simple_client::PolyEndpointClient;
+#[cfg(feature = "std")]
+pub use simple_client::{
+    BlockId, ClientBuilder, ClientError, EndpointEvent, EndpointEventKind, EndpointPage, FeeEstimate, GasEstimate,
+    HealthStatus, PolyEndpointClient, STREAM_BATCH_SIZE, TxOutcome, TxStatus, format_rpc_trace,
+};
+#[cfg(feature = "std")]
+pub use networks::NetworkName;
+#[cfg(feature = "std")]
+pub use events::{AdminEvent, AdminEventKind};
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub use blocking::{block_on, BlockingPolyEndpointClient};
 pub use endpoint::EndpointInfo;
-
+pub use contract::abi::{keccak256, selector};
+#[cfg(all(target_arch = "wasm32", feature = "std"))]
+pub use wasm::{PolyEndpointSdk, WasmPolyEndpointClient};
@@ -0,0 +1,100 @@
+//! Reads through an application's own ethers [`Middleware`] stack
+//!
+//! The client builds JSON-RPC requests itself; [`MiddlewareTransport`]
+//! answers them with the matching `Middleware` methods instead of POSTing
+//! them, so gas oracles, retry layers and the like see every call.
+
+use async_trait::async_trait;
+use ethers::providers::{Middleware, MiddlewareError};
+use ethers::types::{Address, BlockId, Filter, TransactionRequest};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::simple_client::{ClientError, PolyEndpointClient, Route};
+
+/// Something that can answer a JSON-RPC request (or batch) with a response body
+#[async_trait]
+pub(crate) trait RpcTransport: Send + Sync {
+    async fn send(&self, request: &Value) -> Result<String, ClientError>;
+}
+
+struct MiddlewareTransport<M>(M);
+
+impl<M: Middleware> MiddlewareTransport<M> {
+    /// Answer one request object with a response object
+    async fn respond(&self, request: &Value) -> Result<Value, ClientError> {
+        let method = request["method"].as_str().unwrap_or_default();
+        let params = &request["params"];
+
+        let result = match method {
+            "eth_call" => {
+                let tx: TransactionRequest = param(params, 0)?;
+                let block: Option<BlockId> = param(params, 1)?;
+                self.0.call(&tx.into(), block).await.map(|bytes| json!(bytes))
+            }
+            "eth_estimateGas" => {
+                let tx: TransactionRequest = param(params, 0)?;
+                self.0.estimate_gas(&tx.into(), None).await.map(|gas| json!(gas))
+            }
+            "eth_blockNumber" => self.0.get_block_number().await.map(|number| json!(number)),
+            "eth_gasPrice" => self.0.get_gas_price().await.map(|price| json!(price)),
+            "eth_getLogs" => {
+                let filter: Filter = param(params, 0)?;
+                self.0.get_logs(&filter).await.map(|logs| json!(logs))
+            }
+            _ => self.0.provider().request::<_, Value>(method, params).await.map_err(M::Error::from_provider_err),
+        };
+
+        let mut response = json!({ "jsonrpc": "2.0", "id": request["id"] });
+        match result {
+            Ok(result) => response["result"] = result,
+            // Node errors keep their JSON-RPC shape so reverts are classified as usual
+            Err(e) => match e.as_error_response() {
+                Some(error) => {
+                    response["error"] = json!({ "code": error.code, "message": error.message, "data": error.data })
+                }
+                None => return Err(ClientError::Network(e.to_string())),
+            },
+        }
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl<M: Middleware + 'static> RpcTransport for MiddlewareTransport<M> {
+    async fn send(&self, request: &Value) -> Result<String, ClientError> {
+        let response = match request {
+            Value::Array(batch) => {
+                let mut responses = Vec::with_capacity(batch.len());
+                for request in batch {
+                    responses.push(self.respond(request).await?);
+                }
+                Value::Array(responses)
+            }
+            request => self.respond(request).await?,
+        };
+        Ok(response.to_string())
+    }
+}
+
+/// The `index`th request parameter, `null` if absent
+fn param<T: DeserializeOwned>(params: &Value, index: usize) -> Result<T, ClientError> {
+    let value = params.get(index).cloned().unwrap_or(Value::Null);
+    serde_json::from_value(value).map_err(|e| ClientError::Parse(format!("Request parameter {}: {}", index, e)))
+}
+
+impl PolyEndpointClient {
+    /// Create a client that sends every request through `middleware`.
+    ///
+    /// `eth_call`, `eth_estimateGas`, `eth_blockNumber`, `eth_gasPrice` and
+    /// `eth_getLogs` go through the matching [`Middleware`] methods; anything
+    /// else is passed to its provider as is. The `network` argument of the
+    /// read methods is ignored, since the middleware decides where requests go.
+    pub fn from_middleware<M: Middleware + 'static>(middleware: M, contract: Address) -> Self {
+        Self::with_transport(
+            ethers::utils::to_checksum(&contract, None),
+            Route::Transport(Arc::new(MiddlewareTransport(middleware))),
+        )
+    }
+}
@@ -0,0 +1,233 @@
+//! Network name → chain ID / RPC URL registry
+//!
+//! The built-in networks can be extended or overridden from a TOML file:
+//!
+//! ```toml
+//! [[network]]
+//! name = "optimism"
+//! chain_id = 10
+//! rpc_urls = ["https://mainnet.optimism.io"]
+//! ```
+
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::simple_client::ClientError;
+
+/// One named chain
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Network {
+    pub name: String,
+    /// Other names that select this network
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub chain_id: u64,
+    /// RPC URLs in order of preference
+    pub rpc_urls: Vec<String>,
+}
+
+impl Network {
+    pub fn new(name: impl Into<String>, chain_id: u64, rpc_url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            aliases: Vec::new(),
+            chain_id,
+            rpc_urls: vec![rpc_url.into()],
+        }
+    }
+
+    pub fn with_alias(mut self, alias: impl Into<String>) -> Self {
+        self.aliases.push(alias.into());
+        self
+    }
+
+    /// Add a fallback RPC URL, tried after the ones already listed
+    pub fn with_rpc_url(mut self, rpc_url: impl Into<String>) -> Self {
+        self.rpc_urls.push(rpc_url.into());
+        self
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name) || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+    }
+}
+
+/// A network name checked when it is parsed, so a typo like `"base-seplia"`
+/// fails up front instead of being sent as an RPC URL.
+///
+/// Reads take any `AsRef<str>`, so a `NetworkName` can be passed wherever a
+/// network name is expected.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NetworkName {
+    Mainnet,
+    Sepolia,
+    Base,
+    BaseSepolia,
+    Polygon,
+    Arbitrum,
+    /// A network added through a [`NetworkRegistry`], or an RPC URL
+    Custom(String),
+}
+
+impl NetworkName {
+    /// The name the built-in registry knows the network by, or the custom name or URL
+    pub fn as_str(&self) -> &str {
+        match self {
+            NetworkName::Mainnet => "mainnet",
+            NetworkName::Sepolia => "sepolia",
+            NetworkName::Base => "base",
+            NetworkName::BaseSepolia => "base-sepolia",
+            NetworkName::Polygon => "polygon",
+            NetworkName::Arbitrum => "arbitrum",
+            NetworkName::Custom(name) => name,
+        }
+    }
+}
+
+impl FromStr for NetworkName {
+    type Err = ClientError;
+
+    /// Parse a built-in name or alias (case-insensitive) or an RPC URL.
+    /// Anything else is [`ClientError::UnknownNetwork`]; names from a
+    /// networks file are built with [`NetworkName::Custom`] instead.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let builtin = NetworkRegistry::builtin();
+        match builtin.get(name).map(|network| network.name.as_str()) {
+            Some("mainnet") => Ok(NetworkName::Mainnet),
+            Some("sepolia") => Ok(NetworkName::Sepolia),
+            Some("base") => Ok(NetworkName::Base),
+            Some("base-sepolia") => Ok(NetworkName::BaseSepolia),
+            Some("polygon") => Ok(NetworkName::Polygon),
+            Some("arbitrum") => Ok(NetworkName::Arbitrum),
+            Some(other) => Ok(NetworkName::Custom(other.to_string())),
+            None if is_rpc_url(name) => Ok(NetworkName::Custom(name.to_string())),
+            None => Err(ClientError::UnknownNetwork(name.to_string())),
+        }
+    }
+}
+
+impl AsRef<str> for NetworkName {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl std::fmt::Display for NetworkName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Whether `network` is a URL rather than a network name
+fn is_rpc_url(network: &str) -> bool {
+    network.contains("://")
+}
+
+#[derive(Deserialize)]
+struct NetworksFile {
+    #[serde(default)]
+    network: Vec<Network>,
+}
+
+/// An ordered set of networks, looked up by name or alias (case-insensitive)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NetworkRegistry {
+    networks: Vec<Network>,
+}
+
+impl NetworkRegistry {
+    pub fn new(networks: Vec<Network>) -> Self {
+        Self { networks }
+    }
+
+    /// The networks the SDK knows about out of the box, each with a few
+    /// keyless public RPCs from different operators so one going down
+    /// doesn't break the default setup
+    pub fn builtin() -> Self {
+        Self::new(vec![
+            Network::new("mainnet", 1, "https://eth.llamarpc.com")
+                .with_rpc_url("https://ethereum-rpc.publicnode.com")
+                .with_rpc_url("https://1rpc.io/eth"),
+            Network::new("sepolia", 11155111, "https://ethereum-sepolia-rpc.publicnode.com")
+                .with_rpc_url("https://rpc.sepolia.org")
+                .with_rpc_url("https://1rpc.io/sepolia"),
+            Network::new("base", 8453, "https://mainnet.base.org")
+                .with_rpc_url("https://base-rpc.publicnode.com")
+                .with_rpc_url("https://1rpc.io/base")
+                .with_alias("base-mainnet"),
+            Network::new("base-sepolia", 84532, "https://sepolia.base.org")
+                .with_rpc_url("https://base-sepolia-rpc.publicnode.com")
+                .with_alias("base-testnet"),
+            Network::new("polygon", 137, "https://polygon-rpc.com")
+                .with_rpc_url("https://polygon-bor-rpc.publicnode.com")
+                .with_rpc_url("https://1rpc.io/matic"),
+            Network::new("arbitrum", 42161, "https://arb1.arbitrum.io/rpc")
+                .with_rpc_url("https://arbitrum-one-rpc.publicnode.com")
+                .with_rpc_url("https://1rpc.io/arb"),
+        ])
+    }
+
+    /// Parse the `[[network]]` entries of a networks file
+    pub fn from_toml(contents: &str) -> Result<Self, ClientError> {
+        let file: NetworksFile = toml::from_str(contents)
+            .map_err(|e| ClientError::Parse(format!("Invalid networks file: {}", e)))?;
+        if let Some(network) = file.network.iter().find(|n| n.rpc_urls.is_empty()) {
+            return Err(ClientError::Parse(format!("Network '{}' has no rpc_urls", network.name)));
+        }
+        Ok(Self::new(file.network))
+    }
+
+    /// The built-in networks, overridden and extended by the file at `path`.
+    ///
+    /// A missing file is not an error; the built-ins are returned as-is.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ClientError> {
+        let path = path.as_ref();
+        let mut registry = Self::builtin();
+        match std::fs::read_to_string(path) {
+            Ok(contents) => registry.merge(Self::from_toml(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(ClientError::Parse(format!("Failed to read {}: {}", path.display(), e))),
+        }
+        Ok(registry)
+    }
+
+    /// Add `other`'s networks, replacing any existing network with the same name
+    pub fn merge(&mut self, other: NetworkRegistry) {
+        for network in other.networks {
+            match self.networks.iter_mut().find(|n| n.name.eq_ignore_ascii_case(&network.name)) {
+                Some(existing) => *existing = network,
+                None => self.networks.push(network),
+            }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Network> {
+        self.networks.iter().find(|n| n.matches(name))
+    }
+
+    pub fn networks(&self) -> &[Network] {
+        &self.networks
+    }
+
+    /// The preferred RPC URL for a network name, or `network` itself if it
+    /// isn't a known name (so a raw RPC URL passes straight through)
+    pub fn rpc_url(&self, network: &str) -> String {
+        self.get(network)
+            .and_then(|n| n.rpc_urls.first())
+            .cloned()
+            .unwrap_or_else(|| network.to_string())
+    }
+
+    /// All RPC URLs for a network name, in the order to try them. Only a URL
+    /// (containing `://`) passes through; an unknown name is
+    /// [`ClientError::UnknownNetwork`].
+    pub fn resolve(&self, network: &str) -> Result<Vec<String>, ClientError> {
+        match self.get(network) {
+            Some(n) if !n.rpc_urls.is_empty() => Ok(n.rpc_urls.clone()),
+            _ if is_rpc_url(network) => Ok(vec![network.to_string()]),
+            _ => Err(ClientError::UnknownNetwork(network.to_string())),
+        }
+    }
+}
@@ -2,17 +2,215 @@
 //! Works without ethers dependency
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use thiserror::Error;
 
+use crate::endpoint::EndpointInfo;
+use crate::networks::NetworkRegistry;
+
 #[derive(Clone)]
 pub struct PolyEndpointClient {
     contract_address: String,
+    networks: NetworkRegistry,
+    cache: Option<EndpointCache>,
+    /// Where requests go regardless of the network name, if set
+    transport: Option<Route>,
+    http: Arc<HttpOptions>,
+    /// Source of JSON-RPC request ids, shared by clones
+    ids: Arc<AtomicU64>,
+    /// Check the contract has code before each read
+    check_code: bool,
+    /// Print every request and response; see [`PolyEndpointClient::with_trace`]
+    trace: bool,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct EndpointInfo {
-    pub url: String,
-    pub description: String,
+/// Settings for the built-in HTTP transport
+#[derive(Clone, Debug, Default)]
+pub(crate) struct HttpOptions {
+    /// Per-request timeout; on wasm32 the `fetch` is aborted when it elapses
+    timeout: Option<Duration>,
+    /// Extra headers sent with every request, e.g. an API key
+    headers: Vec<(String, String)>,
+}
+
+/// Where JSON-RPC requests go, and the counter their ids come from
+#[derive(Clone)]
+pub(crate) struct Rpc {
+    route: Route,
+    ids: Arc<AtomicU64>,
+    /// Print each request and response (see [`print_rpc_trace`])
+    trace: bool,
+}
+
+/// Destination of a JSON-RPC request
+#[derive(Clone)]
+pub(crate) enum Route {
+    /// POSTed to each URL in turn until one answers (see [`send_rpc`])
+    Http { urls: Vec<String>, options: Arc<HttpOptions> },
+    /// Answered by an application-provided transport (see `from_middleware`)
+    #[cfg(all(feature = "ethers", not(target_arch = "wasm32")))]
+    Transport(Arc<dyn crate::middleware::RpcTransport>),
+}
+
+impl Rpc {
+    /// URLs tried in order, with the default HTTP settings
+    pub(crate) fn urls(urls: Vec<String>) -> Self {
+        Rpc {
+            route: Route::Http { urls, options: Arc::default() },
+            ids: Arc::new(AtomicU64::new(1)),
+            trace: false,
+        }
+    }
+
+    /// A fresh id for the next request, so each response in a batch (or
+    /// behind a proxy) can be matched to its request
+    fn next_id(&self) -> u64 {
+        self.ids.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+/// Configures a [`PolyEndpointClient`]; see [`PolyEndpointClient::builder`]
+#[derive(Clone, Debug, Default)]
+pub struct ClientBuilder {
+    contract_address: Option<String>,
+    networks: Option<NetworkRegistry>,
+    rpc_urls: Vec<String>,
+    cache_ttl: Option<Duration>,
+    check_code: bool,
+    trace: bool,
+    http: HttpOptions,
+}
+
+impl ClientBuilder {
+    /// The contract to read from (required)
+    pub fn contract_address(mut self, contract_address: impl Into<String>) -> Self {
+        self.contract_address = Some(contract_address.into());
+        self
+    }
+
+    /// Resolve network names through `networks`; see [`PolyEndpointClient::with_networks`]
+    pub fn networks(mut self, networks: NetworkRegistry) -> Self {
+        self.networks = Some(networks);
+        self
+    }
+
+    /// Send every request to these URLs instead of the network's RPC,
+    /// trying the next one when a URL can't be reached, times out or is
+    /// rate limited. The `network` argument of the read methods is then ignored.
+    pub fn rpc_urls<I, S>(mut self, urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.rpc_urls = urls.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Give up on a request after `timeout` with [`ClientError::Timeout`]. On
+    /// wasm32 the `fetch` is aborted through an `AbortController`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.http.timeout = Some(timeout);
+        self
+    }
+
+    /// Send an extra header with every request, e.g. an API key
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.http.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Cache [`get_endpoints`](PolyEndpointClient::get_endpoints) results; see
+    /// [`PolyEndpointClient::with_cache`]
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Check the contract has code before each read; see
+    /// [`PolyEndpointClient::with_code_check`]
+    pub fn check_code(mut self, check_code: bool) -> Self {
+        self.check_code = check_code;
+        self
+    }
+
+    /// Print every request and raw response; see [`PolyEndpointClient::with_trace`]
+    pub fn trace(mut self, trace: bool) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    /// Build the client, validating and checksumming the address like
+    /// [`PolyEndpointClient::try_new`] and rejecting malformed headers
+    pub fn build(self) -> Result<PolyEndpointClient, ClientError> {
+        let contract_address = self.contract_address
+            .ok_or_else(|| ClientError::InvalidAddress("no contract address given".to_string()))?;
+        for (name, value) in &self.http.headers {
+            if !is_header_name(name) || value.bytes().any(|b| b.is_ascii_control() && b != b'\t') {
+                return Err(ClientError::Config(format!("invalid header '{}: {}'", name, value)));
+            }
+        }
+
+        let http = Arc::new(self.http);
+        let mut client = PolyEndpointClient::try_new(contract_address)?;
+        if let Some(networks) = self.networks {
+            client.networks = networks;
+        }
+        if !self.rpc_urls.is_empty() {
+            client.transport = Some(Route::Http { urls: self.rpc_urls, options: http.clone() });
+        }
+        if let Some(ttl) = self.cache_ttl {
+            client = client.with_cache(ttl);
+        }
+        client.http = http;
+        client.check_code = self.check_code;
+        client.trace = self.trace;
+        Ok(client)
+    }
+}
+
+/// An HTTP header name: a non-empty RFC 7230 token
+fn is_header_name(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// (contract, network) -> (time stored, endpoints)
+type CacheEntries = HashMap<(String, String), (Duration, Vec<EndpointInfo>)>;
+
+/// `get_endpoints` results kept for `ttl`, keyed by (contract, network).
+/// Clones of a client share the same entries.
+#[derive(Clone)]
+struct EndpointCache {
+    ttl: Duration,
+    entries: Arc<Mutex<CacheEntries>>,
+}
+
+impl EndpointCache {
+    fn get(&self, key: &(String, String)) -> Option<Vec<EndpointInfo>> {
+        let entries = self.entries.lock().unwrap();
+        let (stored_at, endpoints) = entries.get(key)?;
+        (now().saturating_sub(*stored_at) < self.ttl).then(|| endpoints.clone())
+    }
+
+    fn insert(&self, key: (String, String), endpoints: Vec<EndpointInfo>) {
+        self.entries.lock().unwrap().insert(key, (now(), endpoints));
+    }
+}
+
+/// Time elapsed on a clock that only needs to be consistent within the process
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn now() -> Duration {
+    static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+    START.get_or_init(std::time::Instant::now).elapsed()
+}
+
+/// `std::time::Instant` panics on wasm32, so use the JS clock
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn now() -> Duration {
+    Duration::from_secs_f64(js_sys::Date::now() / 1000.0)
 }
 
 #[derive(Debug, Error)]
@@ -23,164 +221,1247 @@ pub enum ClientError {
     Parse(String),
     #[error("Decode error: {0}")]
     Decode(String),
+    #[error("Invalid contract address: {0}")]
+    InvalidAddress(String),
+    /// The provider answered HTTP 429 or a rate-limit JSON-RPC error; retry later
+    #[error("Rate limited by the RPC provider")]
+    RateLimited,
+    /// The call reverted; `reason` is the decoded `Error(string)` message, if any
+    #[error("Execution reverted: {}", .reason.as_deref().unwrap_or("no reason given"))]
+    Reverted { reason: Option<String> },
+    #[error("Request timed out")]
+    Timeout,
+    /// Any other JSON-RPC error object
+    #[error("RPC error {code}: {message}")]
+    RpcError { code: i64, message: String },
+    /// The contract address is the zero address, or a call to it returned
+    /// no data, which usually means nothing is deployed there
+    #[error("No contract at {0}")]
+    NoContract(String),
+    /// A network name that is neither in the client's registry nor an RPC URL
+    #[error("Unknown network '{0}': use a known network name or an RPC URL")]
+    UnknownNetwork(String),
+    /// An invalid [`ClientBuilder`] setting
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+}
+
+/// Block to run a read against
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlockId {
+    #[default]
+    Latest,
+    Pending,
+    Earliest,
+    Number(u64),
+    Hash([u8; 32]),
+}
+
+impl BlockId {
+    /// The block parameter as it goes into `eth_call` params.
+    ///
+    /// Hashes use the EIP-1898 `{"blockHash": ...}` form.
+    pub fn to_param(&self) -> serde_json::Value {
+        match self {
+            BlockId::Latest => "latest".into(),
+            BlockId::Pending => "pending".into(),
+            BlockId::Earliest => "earliest".into(),
+            BlockId::Number(n) => format!("0x{:x}", n).into(),
+            BlockId::Hash(hash) => serde_json::json!({ "blockHash": format!("0x{}", hex::encode(hash)) }),
+        }
+    }
+}
+
+impl From<u64> for BlockId {
+    fn from(number: u64) -> Self {
+        BlockId::Number(number)
+    }
+}
+
+impl FromStr for BlockId {
+    type Err = ClientError;
+
+    /// Parse `latest`, `pending`, `earliest`, a decimal or `0x` block number,
+    /// or a 32-byte `0x` block hash
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ClientError::Parse(format!("Invalid block '{}'", s));
+        match s.to_lowercase().as_str() {
+            "latest" => Ok(BlockId::Latest),
+            "pending" => Ok(BlockId::Pending),
+            "earliest" => Ok(BlockId::Earliest),
+            other => match other.strip_prefix("0x") {
+                Some(hex_str) if hex_str.len() == 64 => {
+                    let mut hash = [0u8; 32];
+                    hex::decode_to_slice(hex_str, &mut hash).map_err(|_| invalid())?;
+                    Ok(BlockId::Hash(hash))
+                }
+                Some(hex_str) => u64::from_str_radix(hex_str, 16).map(BlockId::Number).map_err(|_| invalid()),
+                None => other.parse().map(BlockId::Number).map_err(|_| invalid()),
+            },
+        }
+    }
+}
+
+/// One window of endpoints from [`PolyEndpointClient::get_endpoints_paged`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EndpointPage {
+    pub endpoints: Vec<EndpointInfo>,
+    /// Endpoint count at the block the page was read from
+    pub total: u64,
+    /// Whether there are endpoints past this page
+    pub has_more: bool,
+}
+
+/// RPC reachability from [`PolyEndpointClient::health`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HealthStatus {
+    /// Chain ID reported by `eth_chainId`
+    pub chain_id: u64,
+    /// Latest block number from `eth_blockNumber`
+    pub block_number: u64,
+    /// Round-trip time of the `eth_chainId` request
+    pub latency: Duration,
+}
+
+/// Estimated cost of a transaction from [`PolyEndpointClient::estimate_cost`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasEstimate {
+    pub gas: u64,
+    /// Base fee plus priority fee (or the legacy gas price), in wei
+    pub fee_per_gas: u128,
+    /// `gas * fee_per_gas`
+    pub wei: u128,
+    /// `wei` in ETH, e.g. `"0.000042"`
+    pub eth_string: String,
+}
+
+impl GasEstimate {
+    pub fn new(gas: u64, fee_per_gas: u128) -> Self {
+        let wei = (gas as u128).saturating_mul(fee_per_gas);
+        Self { gas, fee_per_gas, wei, eth_string: format_ether(wei) }
+    }
+
+    /// The cost in dollars, e.g. `"$0.08"`, using the ETH/USD price from
+    /// `price_feed`; `None` if the feed has no price
+    pub fn usd_string(&self, price_feed: impl FnOnce() -> Option<f64>) -> Option<String> {
+        let eth_usd = price_feed()?;
+        Some(format!("${:.2}", self.wei as f64 / 1e18 * eth_usd))
+    }
+}
+
+/// Current network fees from [`PolyEndpointClient::suggested_fees`], in wei
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    /// `eth_gasPrice`
+    pub gas_price: u128,
+    /// Base fee of the next block; `None` on chains without EIP-1559
+    pub base_fee: Option<u128>,
+    /// Median tip paid in recent blocks (0 without EIP-1559)
+    pub priority_fee: u128,
+    /// Max fee per gas to send with: twice the base fee plus the tip, so the
+    /// transaction stays valid while the base fee rises. `gas_price` on
+    /// chains without EIP-1559.
+    pub max_fee: u128,
+}
+
+/// Whether a mined transaction succeeded, from its receipt's `status`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TxStatus {
+    Success,
+    Reverted,
+}
+
+/// The result of a mined write transaction, taken from its receipt, with
+/// what a caller needs to log and reconcile it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxOutcome {
+    /// `0x`-prefixed transaction hash
+    pub hash: String,
+    pub block_number: u64,
+    pub gas_used: u64,
+    pub status: TxStatus,
+    /// Wei paid per gas; `None` when the node leaves it out of the receipt
+    pub effective_gas_price: Option<u128>,
+}
+
+impl TxOutcome {
+    /// Read an `eth_getTransactionReceipt` result
+    pub fn from_receipt(receipt: &serde_json::Value) -> Result<Self, ClientError> {
+        let field = |name: &str| {
+            receipt.get(name)
+                .filter(|value| !value.is_null())
+                .ok_or_else(|| ClientError::Parse(format!("Receipt is missing '{}'", name)))
+        };
+        let small = |name: &str| {
+            let value = quantity(field(name)?)?;
+            u64::try_from(value).map_err(|_| ClientError::Parse(format!("Receipt '{}' {} does not fit in u64", name, value)))
+        };
+
+        let hash = field("transactionHash")?.as_str()
+            .ok_or_else(|| ClientError::Parse("Receipt 'transactionHash' is not a string".to_string()))?
+            .to_string();
+        let status = match quantity(field("status")?)? {
+            1 => TxStatus::Success,
+            0 => TxStatus::Reverted,
+            other => return Err(ClientError::Parse(format!("Unknown receipt status {}", other))),
+        };
+        let effective_gas_price = match receipt.get("effectiveGasPrice") {
+            Some(value) if !value.is_null() => Some(quantity(value)?),
+            _ => None,
+        };
+
+        Ok(Self { hash, block_number: small("blockNumber")?, gas_used: small("gasUsed")?, status, effective_gas_price })
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.status == TxStatus::Success
+    }
+
+    /// The fee paid, `gas_used * effective_gas_price`
+    pub fn fee(&self) -> Option<GasEstimate> {
+        self.effective_gas_price.map(|price| GasEstimate::new(self.gas_used, price))
+    }
+}
+
+/// Format a wei amount in ETH without trailing zeros
+fn format_ether(wei: u128) -> String {
+    const WEI_PER_ETH: u128 = 1_000_000_000_000_000_000;
+    let (whole, fraction) = (wei / WEI_PER_ETH, wei % WEI_PER_ETH);
+    if fraction == 0 {
+        return whole.to_string();
+    }
+    format!("{}.{}", whole, format!("{:018}", fraction).trim_end_matches('0'))
+}
+
+/// Which change an [`EndpointEvent`] records
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EndpointEventKind {
+    Added,
+    Removed,
+}
+
+/// An `EndpointAdded` or `EndpointRemoved` log.
+///
+/// The contract declares `url` as an indexed string, so logs only carry its
+/// keccak-256 hash; use [`EndpointEvent::matches_url`] to check a candidate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EndpointEvent {
+    pub kind: EndpointEventKind,
+    pub url_hash: [u8; 32],
+    /// Description given when the endpoint was added; `None` for removals
+    pub description: Option<String>,
+    pub block: u64,
+    pub transaction_hash: String,
+}
+
+impl EndpointEvent {
+    pub fn matches_url(&self, url: &str) -> bool {
+        crate::keccak256(url) == self.url_hash
+    }
+
+    /// Decode one log object from an `eth_getLogs` result
+    pub fn from_log(log: &serde_json::Value) -> Result<Self, ClientError> {
+        let field = |name: &str| {
+            log.get(name)
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| ClientError::Decode(format!("Log is missing '{}'", name)))
+        };
+        let word = |hex_str: &str| -> Result<[u8; 32], ClientError> {
+            let mut out = [0u8; 32];
+            hex::decode_to_slice(hex_str.trim_start_matches("0x"), &mut out)
+                .map_err(|e| ClientError::Decode(format!("Bad topic '{}': {}", hex_str, e)))?;
+            Ok(out)
+        };
+
+        let topics: Vec<&str> = log.get("topics")
+            .and_then(|t| t.as_array())
+            .map(|t| t.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default();
+        let (signature, url_hash) = match topics.as_slice() {
+            [signature, url_hash, ..] => (word(signature)?, word(url_hash)?),
+            _ => return Err(ClientError::Decode("Log has fewer than 2 topics".to_string())),
+        };
+
+        let kind = if signature == crate::contract::event_topic::endpoint_added() {
+            EndpointEventKind::Added
+        } else if signature == crate::contract::event_topic::endpoint_removed() {
+            EndpointEventKind::Removed
+        } else {
+            return Err(ClientError::Decode(format!("Unexpected event topic {}", topics[0])));
+        };
+
+        let description = match kind {
+            EndpointEventKind::Added => {
+                let data = hex::decode(field("data")?.trim_start_matches("0x"))
+                    .map_err(|e| ClientError::Decode(format!("Hex decode: {}", e)))?;
+                let description = crate::contract::abi::decode_string(&data)
+                    .ok_or_else(|| ClientError::Decode("Invalid EndpointAdded data".to_string()))?;
+                Some(description)
+            }
+            EndpointEventKind::Removed => None,
+        };
+
+        let block_hex = field("blockNumber")?;
+        let block = u64::from_str_radix(block_hex.trim_start_matches("0x"), 16)
+            .map_err(|e| ClientError::Decode(format!("Bad block number '{}': {}", block_hex, e)))?;
+
+        Ok(Self {
+            kind,
+            url_hash,
+            description,
+            block,
+            transaction_hash: field("transactionHash")?.to_string(),
+        })
+    }
 }
 
 impl PolyEndpointClient {
+    /// Create a client without validating the address.
+    ///
+    /// A malformed address is only reported when a request is made; prefer
+    /// [`PolyEndpointClient::try_new`] to fail fast.
     pub fn new(contract_address: impl Into<String>) -> Self {
         Self {
             contract_address: contract_address.into(),
+            networks: NetworkRegistry::builtin(),
+            cache: None,
+            transport: None,
+            http: Arc::default(),
+            ids: Arc::new(AtomicU64::new(1)),
+            check_code: false,
+            trace: false,
         }
     }
 
+    /// Configure a client with a timeout, headers, fixed RPC URLs or a cache:
+    ///
+    /// ```no_run
+    /// # use polyendpoint_sdk::PolyEndpointClient;
+    /// # use std::time::Duration;
+    /// let client = PolyEndpointClient::builder()
+    ///     .contract_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed")
+    ///     .timeout(Duration::from_secs(10))
+    ///     .header("Authorization", "Bearer my-key")
+    ///     .cache_ttl(Duration::from_secs(60))
+    ///     .build()?;
+    /// # Ok::<(), polyendpoint_sdk::ClientError>(())
+    /// ```
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// Create a client, rejecting anything that isn't a 20-byte hex address.
+    ///
+    /// The stored address is normalized to its EIP-55 checksummed form.
+    pub fn try_new(contract_address: impl AsRef<str>) -> Result<Self, ClientError> {
+        let address = parse_address(contract_address.as_ref())?;
+        Ok(Self {
+            contract_address: ethers::utils::to_checksum(&address, None),
+            networks: NetworkRegistry::builtin(),
+            cache: None,
+            transport: None,
+            http: Arc::default(),
+            ids: Arc::new(AtomicU64::new(1)),
+            check_code: false,
+            trace: false,
+        })
+    }
+
+    /// A client whose requests all go to `transport`
+    #[cfg(all(feature = "ethers", not(target_arch = "wasm32")))]
+    pub(crate) fn with_transport(contract_address: String, transport: Route) -> Self {
+        Self {
+            contract_address,
+            networks: NetworkRegistry::builtin(),
+            cache: None,
+            transport: Some(transport),
+            http: Arc::default(),
+            ids: Arc::new(AtomicU64::new(1)),
+            check_code: false,
+            trace: false,
+        }
+    }
+
+    /// Resolve network names through `networks` instead of the built-ins,
+    /// e.g. one loaded with [`NetworkRegistry::load`]
+    pub fn with_networks(mut self, networks: NetworkRegistry) -> Self {
+        self.networks = networks;
+        self
+    }
+
+    /// Keep [`get_endpoints`](Self::get_endpoints) results for `ttl`, so
+    /// repeated reads within that window don't make an RPC request
+    pub fn with_cache(mut self, ttl: Duration) -> Self {
+        self.cache = Some(EndpointCache { ttl, entries: Arc::default() });
+        self
+    }
+
+    /// Call [`is_contract`](Self::is_contract) before each read and fail
+    /// with [`ClientError::NoContract`] if there is no code at the address.
+    ///
+    /// Costs an extra `eth_getCode` per read, but tells a wrong address (or
+    /// network) apart from a contract whose reads return nothing.
+    pub fn with_code_check(mut self) -> Self {
+        self.check_code = true;
+        self
+    }
+
+    /// Print every JSON-RPC request and its raw response body to stderr
+    /// (the browser console on wasm32), with the selector of each `eth_call`
+    /// and its result split into 32-byte words by offset.
+    ///
+    /// For debugging only: the output includes full payloads, so leave it
+    /// off otherwise.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = true;
+        self
+    }
+
     pub fn contract_address(&self) -> &str {
         &self.contract_address
     }
 
+    /// Whether the contract address has code on `network`, via
+    /// `eth_getCode` at the latest block. An account without code, such as
+    /// a wallet, gives `false`.
+    #[tracing::instrument(level = "debug", skip_all, fields(contract = %self.contract_address, network = network.as_ref()))]
+    pub async fn is_contract(&self, network: impl AsRef<str>) -> Result<bool, ClientError> {
+        has_code(&self.rpc(network.as_ref())?, &self.contract_address).await
+    }
+
+    /// Fail with [`ClientError::NoContract`] when code checks are enabled
+    /// and there is no code at the contract address
+    async fn ensure_contract(&self, rpc: &Rpc) -> Result<(), ClientError> {
+        if self.check_code && !has_code(rpc, &self.contract_address).await? {
+            return Err(ClientError::NoContract(self.contract_address.clone()));
+        }
+        Ok(())
+    }
+
+    /// Where requests for `network` go. Without a fixed transport the name
+    /// must be in the registry or be an RPC URL.
+    fn rpc(&self, network: &str) -> Result<Rpc, ClientError> {
+        let route = match &self.transport {
+            Some(route) => route.clone(),
+            None => Route::Http {
+                urls: self.networks.resolve(network)?,
+                options: self.http.clone(),
+            },
+        };
+        Ok(Rpc { route, ids: self.ids.clone(), trace: self.trace })
+    }
+
+    /// Fetch the current endpoint list, served from the cache if enabled
+    /// with [`with_cache`](Self::with_cache) and still fresh
+    #[tracing::instrument(level = "debug", skip_all, fields(contract = %self.contract_address, network = network.as_ref()))]
     pub async fn get_endpoints(&self, network: impl AsRef<str>) -> Result<Vec<EndpointInfo>, ClientError> {
-        let network = network.as_ref();
-        let rpc_url = get_rpc_url(network);
-        
-        // Compute method ID for getAllEndpoints() - use sha3 like CLI does
-        let method_id = ethers::utils::keccak256("getAllEndpoints()")[0..4].to_vec();
-        
-        // Make RPC call
+        let key = (self.contract_address.clone(), network.as_ref().to_string());
+        if let Some(endpoints) = self.cache.as_ref().and_then(|cache| cache.get(&key)) {
+            tracing::debug!(count = endpoints.len(), "served endpoints from cache");
+            return Ok(endpoints);
+        }
+        self.refresh(network).await
+    }
+
+    /// Fetch the current endpoint list from the network, replacing any
+    /// cached copy
+    #[tracing::instrument(level = "debug", skip_all, fields(contract = %self.contract_address, network = network.as_ref()))]
+    pub async fn refresh(&self, network: impl AsRef<str>) -> Result<Vec<EndpointInfo>, ClientError> {
+        let endpoints = self.get_endpoints_at(network.as_ref(), BlockId::Latest).await?;
+        if let Some(cache) = &self.cache {
+            cache.insert((self.contract_address.clone(), network.as_ref().to_string()), endpoints.clone());
+        }
+        Ok(endpoints)
+    }
+
+    /// Drop all cached endpoint lists
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.entries.lock().unwrap().clear();
+        }
+    }
+
+    /// Fetch the endpoint list as of `block`
+    #[tracing::instrument(level = "debug", skip_all, fields(contract = %self.contract_address, network = network.as_ref(), ?block))]
+    pub async fn get_endpoints_at(
+        &self,
+        network: impl AsRef<str>,
+        block: BlockId,
+    ) -> Result<Vec<EndpointInfo>, ClientError> {
+        let rpc = self.rpc(network.as_ref())?;
+        self.ensure_contract(&rpc).await?;
+        let method_id = crate::contract::method_id::GET_ALL_ENDPOINTS;
+
+        let result = eth_call(&rpc, &self.contract_address, &method_id, block).await?;
+        decode_endpoints(&result)
+    }
+
+    /// Number of registered endpoints, from `getEndpointCount()`
+    #[tracing::instrument(level = "debug", skip_all, fields(contract = %self.contract_address, network = network.as_ref()))]
+    pub async fn get_endpoint_count(&self, network: impl AsRef<str>) -> Result<u64, ClientError> {
+        let rpc = self.rpc(network.as_ref())?;
+        self.ensure_contract(&rpc).await?;
+        let data = crate::contract::encode_get_endpoint_count().build();
+        decode_u64(&eth_call(&rpc, &self.contract_address, &data, BlockId::Latest).await?)
+    }
+
+    /// Whether `url` is registered, from `hasEndpoint(string)`. A cheap
+    /// `eth_call` to run before `addEndpoint`, which reverts on duplicates.
+    #[tracing::instrument(level = "debug", skip_all, fields(contract = %self.contract_address, network = network.as_ref(), url))]
+    pub async fn has_endpoint(&self, network: impl AsRef<str>, url: &str) -> Result<bool, ClientError> {
+        let rpc = self.rpc(network.as_ref())?;
+        self.ensure_contract(&rpc).await?;
+        let data = crate::contract::encode_has_endpoint(url).build();
+        let result = eth_call(&rpc, &self.contract_address, &data, BlockId::Latest).await?;
+        crate::contract::decode_bool(&result).map_err(ClientError::Decode)
+    }
+
+    /// The contract owner from `owner()`, as `0x`-prefixed lowercase hex
+    #[tracing::instrument(level = "debug", skip_all, fields(contract = %self.contract_address, network = network.as_ref()))]
+    pub async fn owner(&self, network: impl AsRef<str>) -> Result<String, ClientError> {
+        let rpc = self.rpc(network.as_ref())?;
+        self.ensure_contract(&rpc).await?;
+        let data = crate::contract::encode_owner().build();
+        let result = eth_call(&rpc, &self.contract_address, &data, BlockId::Latest).await?;
+        crate::contract::decode_address(&result).map_err(ClientError::Decode)
+    }
+
+    /// Check that the network's RPC answers, and report which chain it is
+    /// on and its latest block
+    #[tracing::instrument(level = "debug", skip_all, fields(network = network.as_ref()))]
+    pub async fn health(&self, network: impl AsRef<str>) -> Result<HealthStatus, ClientError> {
+        let rpc = self.rpc(network.as_ref())?;
+        let started = now();
+        let chain_id = rpc_request(&rpc, "eth_chainId", serde_json::json!([])).await?;
+        let latency = now().saturating_sub(started);
+        let chain_id = u64::try_from(quantity(&chain_id)?)
+            .map_err(|_| ClientError::Parse(format!("Chain ID {} does not fit in u64", chain_id)))?;
+
+        Ok(HealthStatus { chain_id, block_number: block_number(&rpc).await?, latency })
+    }
+
+    /// Fetch up to `limit` endpoints starting at index `offset`.
+    ///
+    /// Reads `getEndpointCount()` and then the requested `getEndpoint(i)`
+    /// entries in one JSON-RPC batch, all pinned to the current block so the
+    /// count and the entries agree. Note that removing an endpoint moves the
+    /// last one into its slot, so indices are only stable between writes.
+    #[tracing::instrument(level = "debug", skip_all, fields(contract = %self.contract_address, network = network.as_ref(), offset, limit))]
+    pub async fn get_endpoints_paged(
+        &self,
+        network: impl AsRef<str>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<EndpointPage, ClientError> {
+        let rpc = self.rpc(network.as_ref())?;
+        self.ensure_contract(&rpc).await?;
+        let block = BlockId::Number(block_number(&rpc).await?);
+
+        let count = crate::contract::encode_get_endpoint_count().build();
+        let total = decode_u64(&eth_call(&rpc, &self.contract_address, &count, block).await?)?;
+        let end = offset.saturating_add(limit).min(total);
+        if offset >= end {
+            return Ok(EndpointPage { endpoints: Vec::new(), total, has_more: offset < total });
+        }
+
+        let endpoints = get_endpoint_range(&rpc, &self.contract_address, block, offset..end).await?;
+        Ok(EndpointPage { endpoints, total, has_more: end < total })
+    }
+
+    /// Stream every endpoint without holding the whole list in memory.
+    ///
+    /// Reads `getEndpointCount()` once, then fetches `getEndpoint(i)` in
+    /// batches of [`STREAM_BATCH_SIZE`] only as the stream is polled, so at
+    /// most one batch is buffered. Like [`get_endpoints_paged`](Self::get_endpoints_paged),
+    /// every read is pinned to the block current when the stream starts.
+    /// The stream ends after the first error.
+    pub fn endpoints_stream(
+        &self,
+        network: impl AsRef<str>,
+    ) -> impl futures_util::Stream<Item = Result<EndpointInfo, ClientError>> {
+        let state = self.rpc(network.as_ref()).map(|rpc| EndpointStream {
+            rpc,
+            contract_address: self.contract_address.clone(),
+            check_code: self.check_code,
+            range: None,
+            next: 0,
+            buffer: std::collections::VecDeque::new(),
+            done: false,
+        });
+
+        // An unknown network is the stream's only item
+        futures_util::stream::unfold(Some(state), |state| async move {
+            let mut state = match state? {
+                Ok(state) => state,
+                Err(e) => return Some((Err(e), None)),
+            };
+            loop {
+                if let Some(endpoint) = state.buffer.pop_front() {
+                    return Some((Ok(endpoint), Some(Ok(state))));
+                }
+                if state.done {
+                    return None;
+                }
+                if let Err(e) = state.fill().await {
+                    state.done = true;
+                    return Some((Err(e), Some(Ok(state))));
+                }
+            }
+        })
+    }
+
+    /// Fetch `EndpointAdded` / `EndpointRemoved` logs between two blocks (inclusive).
+    ///
+    /// `eth_getLogs` ranges take block numbers or tags, so a [`BlockId::Hash`]
+    /// is rejected. Many public RPCs cap the range size; query in chunks if
+    /// the node complains.
+    #[tracing::instrument(level = "debug", skip_all, fields(contract = %self.contract_address, network = network.as_ref(), ?from_block, ?to_block))]
+    pub async fn get_endpoint_events(
+        &self,
+        network: impl AsRef<str>,
+        from_block: BlockId,
+        to_block: BlockId,
+    ) -> Result<Vec<EndpointEvent>, ClientError> {
+        let topics = [crate::contract::event_topic::endpoint_added(), crate::contract::event_topic::endpoint_removed()];
+        let logs = self.get_logs(network.as_ref(), from_block, to_block, &topics).await?;
+        logs.iter().map(EndpointEvent::from_log).collect()
+    }
+
+    /// The contract's logs between two blocks (inclusive) whose first topic
+    /// is one of `signatures`
+    pub(crate) async fn get_logs(
+        &self,
+        network: &str,
+        from_block: BlockId,
+        to_block: BlockId,
+        signatures: &[[u8; 32]],
+    ) -> Result<Vec<serde_json::Value>, ClientError> {
+        if matches!(from_block, BlockId::Hash(_)) || matches!(to_block, BlockId::Hash(_)) {
+            return Err(ClientError::Parse("eth_getLogs ranges take block numbers or tags, not hashes".to_string()));
+        }
+        let rpc = self.rpc(network)?;
+        self.ensure_contract(&rpc).await?;
+
+        let topics: Vec<String> = signatures.iter().map(|hash| format!("0x{}", hex::encode(hash))).collect();
+        let id = rpc.next_id();
         let request = serde_json::json!({
             "jsonrpc": "2.0",
-            "method": "eth_call",
+            "method": "eth_getLogs",
             "params": [{
-                "to": format!("{:#x}", parse_address(&self.contract_address)?),
-                "data": format!("0x{}", hex::encode(&method_id))
-            }, "latest"],
-            "id": 1
+                "address": format!("{:#x}", parse_contract_address(&self.contract_address)?),
+                "fromBlock": from_block.to_param(),
+                "toBlock": to_block.to_param(),
+                "topics": [topics]
+            }],
+            "id": id
         });
 
-        // Call RPC
-        #[cfg(not(target_arch = "wasm32"))]
-        let response = {
-            let client = reqwest::Client::new();
-            let res: serde_json::Value = client
-                .post(rpc_url)
-                .json(&request)
-                .send()
-                .await
-                .map_err(|e| ClientError::Network(format!("Request failed: {}", e)))?
-                .json()
-                .await
-                .map_err(|e| ClientError::Network(format!("Parse failed: {}", e)))?;
-            res.to_string()
-        };
-
-        #[cfg(target_arch = "wasm32")]
-        let response = {
-            use wasm_bindgen::JsCast;
-            use wasm_bindgen_futures::JsFuture;
-            let window = web_sys::window()
-                .ok_or_else(|| ClientError::Network("No window".to_string()))?;
-            
-            let mut opts = web_sys::RequestInit::new();
-            opts.set_method("POST");
-            let headers = web_sys::Headers::new().unwrap();
-            headers.set("Content-Type", "application/json").unwrap();
-            opts.set_headers(&headers.into());
-            
-            let body = wasm_bindgen::JsValue::from_str(&request.to_string());
-            opts.set_body(&body);
-            
-            let fetch_promise = window.fetch_with_str_and_init(rpc_url, &opts);
-            
-            let resp_value = JsFuture::from(fetch_promise).await
-                .map_err(|e| ClientError::Network(format!("Fetch: {:?}", e)))?;
-            
-            let resp: web_sys::Response = resp_value.dyn_into()
-                .map_err(|e| ClientError::Network(format!("Response: {:?}", e)))?;
-            
-            let text_promise = resp.text()
-                .map_err(|e| ClientError::Network(format!("Text: {:?}", e)))?;
-            
-            let text = JsFuture::from(text_promise).await
-                .map_err(|e| ClientError::Network(format!("Text future: {:?}", e)))?;
-            
-            text.as_string().ok_or_else(|| ClientError::Network("No text".to_string()))?
-        };
-        
-        let endpoints = decode_endpoints_response(response)?;
-        Ok(endpoints)
+        let response = send_rpc(&rpc, &request).await?;
+        decode_logs_response(&response, id)
+    }
+
+    /// Estimate what sending `tx` to the contract from `from` would cost.
+    ///
+    /// Multiplies `eth_estimateGas` by the latest block's base fee plus
+    /// `eth_maxPriorityFeePerGas`, or by `eth_gasPrice` on chains without a
+    /// base fee (or nodes without `eth_maxPriorityFeePerGas`). The call is
+    /// simulated with `tx.value`. A call that would revert fails with
+    /// [`ClientError::Reverted`].
+    #[tracing::instrument(level = "debug", skip_all, fields(contract = %self.contract_address, network = network.as_ref(), from))]
+    pub async fn estimate_cost(
+        &self,
+        network: impl AsRef<str>,
+        from: &str,
+        tx: &crate::contract::TransactionData,
+    ) -> Result<GasEstimate, ClientError> {
+        let rpc = self.rpc(network.as_ref())?;
+
+        let mut call = serde_json::json!({
+            "from": format!("{:#x}", parse_address(from)?),
+            "to": format!("{:#x}", parse_contract_address(&self.contract_address)?),
+            "data": tx.to_hex()
+        });
+        if tx.value != 0 {
+            call["value"] = serde_json::json!(format!("{:#x}", tx.value));
+        }
+        let gas = quantity(&rpc_request(&rpc, "eth_estimateGas", serde_json::json!([call])).await?)?;
+        let gas = u64::try_from(gas)
+            .map_err(|_| ClientError::Parse(format!("Gas estimate {} does not fit in u64", gas)))?;
+
+        let block = rpc_request(&rpc, "eth_getBlockByNumber", serde_json::json!(["latest", false])).await?;
+        let eip1559_fee = match block.get("baseFeePerGas") {
+            Some(base_fee) => match rpc_request(&rpc, "eth_maxPriorityFeePerGas", serde_json::json!([])).await {
+                Ok(priority_fee) => Some(quantity(base_fee)?.saturating_add(quantity(&priority_fee)?)),
+                Err(ClientError::RpcError { .. }) => None,
+                Err(e) => return Err(e),
+            },
+            None => None,
+        };
+        let fee_per_gas = match eip1559_fee {
+            Some(fee) => fee,
+            None => quantity(&rpc_request(&rpc, "eth_gasPrice", serde_json::json!([])).await?)?,
+        };
+
+        Ok(GasEstimate::new(gas, fee_per_gas))
+    }
+
+    /// Current fees on `network`, without building a transaction.
+    ///
+    /// Reads `eth_gasPrice` and the last few blocks of `eth_feeHistory`. The
+    /// suggested priority fee is the median of the blocks' median tips. A node
+    /// without `eth_feeHistory` is treated as a chain without EIP-1559.
+    #[tracing::instrument(level = "debug", skip_all, fields(network = network.as_ref()))]
+    pub async fn suggested_fees(&self, network: impl AsRef<str>) -> Result<FeeEstimate, ClientError> {
+        let rpc = self.rpc(network.as_ref())?;
+        let gas_price = quantity(&rpc_request(&rpc, "eth_gasPrice", serde_json::json!([])).await?)?;
+
+        let params = serde_json::json!([format!("{:#x}", FEE_HISTORY_BLOCKS), "latest", [50]]);
+        let history = match rpc_request(&rpc, "eth_feeHistory", params).await {
+            Ok(history) => history,
+            Err(ClientError::RpcError { .. }) => serde_json::Value::Null,
+            Err(e) => return Err(e),
+        };
+        // The last entry is the base fee of the block after the newest one
+        let base_fee = match history["baseFeePerGas"].as_array().and_then(|fees| fees.last()) {
+            Some(fee) => Some(quantity(fee)?),
+            None => None,
+        };
+        let Some(base_fee) = base_fee else {
+            return Ok(FeeEstimate { gas_price, base_fee: None, priority_fee: 0, max_fee: gas_price });
+        };
+
+        let mut tips = history["reward"].as_array().into_iter().flatten()
+            .filter_map(|rewards| rewards.get(0))
+            .map(quantity)
+            .collect::<Result<Vec<_>, _>>()?;
+        tips.sort_unstable();
+        let priority_fee = match tips.len() {
+            0 => gas_price.saturating_sub(base_fee),
+            n => tips[n / 2],
+        };
+
+        Ok(FeeEstimate {
+            gas_price,
+            base_fee: Some(base_fee),
+            priority_fee,
+            max_fee: base_fee.saturating_mul(2).saturating_add(priority_fee),
+        })
     }
 }
 
-fn parse_address(addr: &str) -> Result<ethers::types::Address, ClientError> {
-    addr.parse()
-        .map_err(|e| ClientError::Parse(format!("Invalid address: {}", e)))
+/// Blocks of `eth_feeHistory` [`PolyEndpointClient::suggested_fees`] takes the tip from
+const FEE_HISTORY_BLOCKS: u64 = 10;
+
+/// Number of endpoints [`PolyEndpointClient::endpoints_stream`] fetches per request
+pub const STREAM_BATCH_SIZE: u64 = 16;
+
+/// State behind [`PolyEndpointClient::endpoints_stream`]
+struct EndpointStream {
+    rpc: Rpc,
+    contract_address: String,
+    check_code: bool,
+    /// Pinned block and endpoint count, once read
+    range: Option<(BlockId, u64)>,
+    next: u64,
+    buffer: std::collections::VecDeque<EndpointInfo>,
+    done: bool,
 }
 
-fn get_rpc_url(network: &str) -> &str {
-    match network.to_lowercase().as_str() {
-        "mainnet" => "https://eth.llamarpc.com",
-        "sepolia" => "https://rpc.sepolia.org",
-        "base" | "base-mainnet" => "https://mainnet.base.org",
-        "base-sepolia" | "base-testnet" => "https://sepolia.base.org",
-        "polygon" => "https://polygon-rpc.com",
-        "arbitrum" => "https://arb1.arbitrum.io/rpc",
-        _ => network,
+impl EndpointStream {
+    /// Buffer the next batch, or mark the stream done when none are left
+    async fn fill(&mut self) -> Result<(), ClientError> {
+        let (block, total) = match self.range {
+            Some(range) => range,
+            None => {
+                if self.check_code && !has_code(&self.rpc, &self.contract_address).await? {
+                    return Err(ClientError::NoContract(self.contract_address.clone()));
+                }
+                let block = BlockId::Number(block_number(&self.rpc).await?);
+                let count = crate::contract::encode_get_endpoint_count().build();
+                let total = decode_u64(&eth_call(&self.rpc, &self.contract_address, &count, block).await?)?;
+                *self.range.insert((block, total))
+            }
+        };
+
+        if self.next >= total {
+            self.done = true;
+            return Ok(());
+        }
+        let end = self.next.saturating_add(STREAM_BATCH_SIZE).min(total);
+        let endpoints = get_endpoint_range(&self.rpc, &self.contract_address, block, self.next..end).await?;
+        self.buffer.extend(endpoints);
+        self.next = end;
+        Ok(())
     }
 }
 
-fn decode_endpoints_response(response: String) -> Result<Vec<EndpointInfo>, ClientError> {
-    let json: serde_json::Value = serde_json::from_str(&response)
-        .map_err(|e| ClientError::Parse(format!("Parse error: {}", e)))?;
-    
-    if let Some(error) = json.get("error") {
-        let error_msg = format!("RPC error: {}", error);
-        return Err(ClientError::Network(error_msg));
-    }
-    
-    let result = json.get("result")
-        .and_then(|r| r.as_str())
-        .ok_or_else(|| ClientError::Parse("No result in response".to_string()))?;
-    
-    let result_bytes = hex::decode(result.trim_start_matches("0x"))
-        .map_err(|e| ClientError::Decode(format!("Hex decode: {}", e)))?;
-    
-    // Decode using ethers ABI decoder
-    let tokens = ethers::abi::decode(&[
-        ethers::abi::ParamType::Array(Box::new(ethers::abi::ParamType::String)),
-        ethers::abi::ParamType::Array(Box::new(ethers::abi::ParamType::String))
-    ], result_bytes.as_slice())
-    .map_err(|e| ClientError::Decode(format!("ABI decode: {}", e)))?;
-    
-    if tokens.len() < 2 {
-        return Err(ClientError::Decode("Invalid response format".to_string()));
-    }
-    
-    let urls = if let Some(ethers::abi::Token::Array(arr)) = tokens.first() {
-        arr.iter().filter_map(|token| {
-            if let ethers::abi::Token::String(s) = token {
-                Some(s.clone())
-            } else {
-                None
+/// `getEndpoint(i)` for every `i` in `indices`, as one JSON-RPC batch read at `block`
+async fn get_endpoint_range(
+    rpc: &Rpc,
+    contract_address: &str,
+    block: BlockId,
+    indices: std::ops::Range<u64>,
+) -> Result<Vec<EndpointInfo>, ClientError> {
+    let to = format!("{:#x}", parse_contract_address(contract_address)?);
+    let ids: Vec<(u64, u64)> = indices.map(|index| (index, rpc.next_id())).collect();
+    let batch: Vec<serde_json::Value> = ids.iter()
+        .map(|&(index, id)| serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_call",
+            "params": [{
+                "to": to,
+                "data": crate::contract::encode_get_endpoint(index).to_hex()
+            }, block.to_param()],
+            "id": id
+        }))
+        .collect();
+
+    let response = send_rpc(rpc, &serde_json::Value::Array(batch)).await?;
+    let responses: Vec<serde_json::Value> = serde_json::from_str(&response)
+        .map_err(|e| ClientError::Parse(format!("Batch response: {}", e)))?;
+
+    // Batch responses may come back in any order
+    ids.into_iter()
+        .map(|(index, id)| {
+            let response = responses.iter()
+                .find(|r| r.get("id").and_then(|id| id.as_u64()) == Some(id))
+                .ok_or_else(|| ClientError::Parse(format!("No response for getEndpoint({})", index)))?;
+            decode_endpoint(&contract_result(call_result_bytes(response)?, contract_address)?)
+        })
+        .collect()
+}
+
+/// `eth_call` `data` against `to` and return the raw result bytes
+pub(crate) async fn eth_call(
+    rpc: &Rpc,
+    to: &str,
+    data: &[u8],
+    block: BlockId,
+) -> Result<Vec<u8>, ClientError> {
+    let id = rpc.next_id();
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "eth_call",
+        "params": [{
+            "to": format!("{:#x}", parse_contract_address(to)?),
+            "data": format!("0x{}", hex::encode(data))
+        }, block.to_param()],
+        "id": id
+    });
+
+    let response = send_rpc(rpc, &request).await?;
+    contract_result(decode_call_response(&response, id)?, to)
+}
+
+/// Send a single JSON-RPC request and return its `result`
+async fn rpc_request(
+    rpc: &Rpc,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, ClientError> {
+    let id = rpc.next_id();
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": params,
+        "id": id
+    });
+
+    let response = send_rpc(rpc, &request).await?;
+    let json = parse_single_response(&response, id)?;
+    rpc_result(&json).cloned()
+}
+
+/// Parse a hex `QUANTITY` such as `"0x5208"`
+fn quantity(value: &serde_json::Value) -> Result<u128, ClientError> {
+    let hex_str = value.as_str()
+        .ok_or_else(|| ClientError::Parse(format!("Expected a hex quantity, got {}", value)))?;
+    u128::from_str_radix(hex_str.trim_start_matches("0x"), 16)
+        .map_err(|e| ClientError::Parse(format!("Bad quantity '{}': {}", hex_str, e)))
+}
+
+/// Send a JSON-RPC request and return the raw response body.
+///
+/// Over HTTP the URLs are tried in order, moving on only when one can't be
+/// reached, times out or is rate limited; JSON-RPC errors in the body are
+/// left to the caller.
+async fn send_rpc(rpc: &Rpc, request: &serde_json::Value) -> Result<String, ClientError> {
+    match &rpc.route {
+        Route::Http { urls, options } => {
+            let mut result = Err(ClientError::Network("No RPC URL configured".to_string()));
+            for url in urls {
+                result = traced(url, request, rpc.trace, post_rpc(url, options, request)).await;
+                if !matches!(result, Err(ClientError::Network(_) | ClientError::Timeout | ClientError::RateLimited)) {
+                    break;
+                }
             }
-        }).collect::<Vec<_>>()
-    } else {
-        vec![]
+            result
+        }
+        #[cfg(all(feature = "ethers", not(target_arch = "wasm32")))]
+        Route::Transport(transport) => traced("middleware", request, rpc.trace, transport.send(request)).await,
+    }
+}
+
+/// Run one request attempt, logged with [`trace_rpc_request`] and
+/// [`trace_rpc_response`], and printed with [`print_rpc_trace`] if `print`
+async fn traced(
+    rpc_url: &str,
+    request: &serde_json::Value,
+    print: bool,
+    attempt: impl std::future::Future<Output = Result<String, ClientError>>,
+) -> Result<String, ClientError> {
+    let started = now();
+    trace_rpc_request(rpc_url, request);
+    let result = attempt.await;
+    trace_rpc_response(rpc_url, request, started, &result);
+    if print {
+        print_rpc_trace(rpc_url, request, &result);
+    }
+    result
+}
+
+/// Print a request and its outcome for [`PolyEndpointClient::with_trace`]
+fn print_rpc_trace(rpc_url: &str, request: &serde_json::Value, result: &Result<String, ClientError>) {
+    let text = format_rpc_trace(rpc_url, request, result);
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("{}", text);
+    #[cfg(target_arch = "wasm32")]
+    web_sys::console::log_1(&text.into());
+}
+
+/// The text [`PolyEndpointClient::with_trace`] prints for one request and
+/// its raw response body, for applications tracing requests they send
+/// themselves.
+///
+/// Each `eth_call` is annotated with its selector (and method, if it is one
+/// of the contract's), and a single call's result is listed word by word so
+/// ABI offsets and lengths can be read off directly.
+pub fn format_rpc_trace(rpc_url: &str, request: &serde_json::Value, result: &Result<String, ClientError>) -> String {
+    use std::fmt::Write;
+
+    let mut text = format!("--> {} {}", rpc_url, request);
+    let calls: Vec<&serde_json::Value> = match request {
+        serde_json::Value::Array(requests) => requests.iter().collect(),
+        request => vec![request],
     };
-    
-    let descriptions = if let Some(ethers::abi::Token::Array(arr)) = tokens.get(1) {
-        arr.iter().filter_map(|token| {
-            if let ethers::abi::Token::String(s) = token {
-                Some(s.clone())
-            } else {
-                None
+    for call in calls.iter().filter(|call| call["method"] == "eth_call") {
+        let data = call["params"][0]["data"].as_str().unwrap_or_default();
+        if let Ok(call_data) = crate::contract::TransactionData::from_hex(data) {
+            let _ = write!(
+                text,
+                "\n    selector 0x{} {}",
+                hex::encode(call_data.selector),
+                call_data.selector_name().unwrap_or("(unknown method)")
+            );
+        }
+    }
+
+    match result {
+        Ok(body) => {
+            let _ = write!(text, "\n<-- {}", body);
+            let words = match (calls.as_slice(), serde_json::from_str::<serde_json::Value>(body)) {
+                ([call], Ok(response)) if call["method"] == "eth_call" => response["result"]
+                    .as_str()
+                    .and_then(|result| hex::decode(result.trim_start_matches("0x")).ok()),
+                _ => None,
+            };
+            for (i, word) in words.iter().flat_map(|words| words.chunks(32)).enumerate() {
+                let _ = write!(text, "\n    0x{:04x}: {}", i * 32, hex::encode(word));
+            }
+        }
+        Err(error) => {
+            let _ = write!(text, "\n<-- error: {}", error);
+        }
+    }
+    text
+}
+
+/// The `method` of a JSON-RPC request, or `batch` for a batch
+fn rpc_method(request: &serde_json::Value) -> &str {
+    match request {
+        serde_json::Value::Array(_) => "batch",
+        _ => request["method"].as_str().unwrap_or("unknown"),
+    }
+}
+
+pub(crate) fn trace_rpc_request(rpc_url: &str, request: &serde_json::Value) {
+    tracing::debug!(
+        rpc_url,
+        method = rpc_method(request),
+        request_bytes = request.to_string().len(),
+        "sending JSON-RPC request"
+    );
+}
+
+/// Log the outcome of a request whose sending started at `started` (see [`now`])
+pub(crate) fn trace_rpc_response(
+    rpc_url: &str,
+    request: &serde_json::Value,
+    started: Duration,
+    result: &Result<String, ClientError>,
+) {
+    let elapsed_ms = now().saturating_sub(started).as_millis() as u64;
+    match result {
+        Ok(body) => {
+            tracing::debug!(rpc_url, method = rpc_method(request), response_bytes = body.len(), elapsed_ms, "JSON-RPC response");
+            tracing::trace!(rpc_url, body = body.as_str(), "JSON-RPC response body");
+        }
+        Err(error) => {
+            tracing::debug!(rpc_url, method = rpc_method(request), elapsed_ms, %error, "JSON-RPC request failed");
+        }
+    }
+}
+
+async fn post_rpc(rpc_url: &str, options: &HttpOptions, request: &serde_json::Value) -> Result<String, ClientError> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let response = {
+        let send = async {
+            let mut client = reqwest::Client::builder();
+            if let Some(timeout) = options.timeout {
+                client = client.timeout(timeout);
+            }
+            let client = client.build().map_err(request_error)?;
+            let mut req = client.post(rpc_url).json(request);
+            for (name, value) in &options.headers {
+                req = req.header(name, value);
             }
-        }).collect::<Vec<_>>()
+            let res = req.send().await.map_err(request_error)?;
+            let status = res.status().as_u16();
+            let body = res.text().await.map_err(request_error)?;
+            check_http_status(status, body)
+        };
+        // reqwest needs a tokio reactor: use the caller's if there is one,
+        // otherwise a shared background runtime
+        #[cfg(feature = "any-runtime")]
+        let send = async_compat::Compat::new(send);
+        send.await?
+    };
+
+    #[cfg(target_arch = "wasm32")]
+    let response = crate::http_impl::wasm::post(rpc_url, &options.headers, options.timeout, request).await?;
+
+    Ok(response)
+}
+
+/// Map a reqwest failure to [`ClientError::Timeout`] or [`ClientError::Network`]
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn request_error(e: reqwest::Error) -> ClientError {
+    if e.is_timeout() {
+        ClientError::Timeout
     } else {
-        vec![]
+        ClientError::Network(format!("Request failed: {}", e))
+    }
+}
+
+/// Check the HTTP status of an RPC response and pass its body through.
+///
+/// 429 is [`ClientError::RateLimited`]. Other error statuses become
+/// [`ClientError::Network`] with the status and the start of the body,
+/// unless the body is a JSON-RPC error: many providers send those with a
+/// 4xx/5xx status, and they are classified by [`rpc_result`].
+pub(crate) fn check_http_status(status: u16, body: String) -> Result<String, ClientError> {
+    if status == 429 {
+        return Err(ClientError::RateLimited);
+    }
+    if !(200..300).contains(&status) && !is_json_rpc_error(&body) {
+        return Err(ClientError::Network(format!("HTTP {}: {}", status, body_snippet(&body))));
+    }
+    Ok(body)
+}
+
+/// Whether `body` is a JSON-RPC error response, or a batch of responses with one
+fn is_json_rpc_error(body: &str) -> bool {
+    let is_error = |response: &serde_json::Value| response.get("error").is_some_and(|e| e.is_object());
+    match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(serde_json::Value::Array(responses)) => responses.iter().any(is_error),
+        Ok(response) => is_error(&response),
+        Err(_) => false,
+    }
+}
+
+/// The first line of an error body, cut to a length that fits in a message
+fn body_snippet(body: &str) -> String {
+    const MAX_CHARS: usize = 200;
+    let line = body.trim().lines().next().unwrap_or_default();
+    match line.char_indices().nth(MAX_CHARS) {
+        Some((end, _)) => format!("{}...", &line[..end]),
+        None if body.trim().len() > line.len() => format!("{}...", line),
+        None => line.to_string(),
+    }
+}
+
+pub(crate) fn parse_address(addr: &str) -> Result<ethers::types::Address, ClientError> {
+    let hex_str = addr.strip_prefix("0x").unwrap_or(addr);
+    if hex_str.len() != 40 {
+        return Err(ClientError::InvalidAddress(format!("'{}' is not 20 bytes", addr)));
+    }
+    hex_str.parse()
+        .map_err(|e| ClientError::InvalidAddress(format!("'{}': {}", addr, e)))
+}
+
+/// Parse the address of a contract to call, rejecting the zero address
+/// (usually an unfilled placeholder) with [`ClientError::NoContract`]
+pub(crate) fn parse_contract_address(addr: &str) -> Result<ethers::types::Address, ClientError> {
+    let address = parse_address(addr)?;
+    if address.is_zero() {
+        return Err(ClientError::NoContract(format!("{:#x} (the zero address)", address)));
+    }
+    Ok(address)
+}
+
+/// Pass through the result of a call to `to`. All the methods called return
+/// something, so an empty `0x` result means there is no contract there.
+fn contract_result(result: Vec<u8>, to: &str) -> Result<Vec<u8>, ClientError> {
+    if result.is_empty() {
+        return Err(ClientError::NoContract(to.to_string()));
+    }
+    Ok(result)
+}
+
+/// RPC URLs for a network name from the built-in registry, in the order to
+/// try them, or `network` itself if it is a URL
+pub(crate) fn get_rpc_urls(network: &str) -> Result<Vec<String>, ClientError> {
+    NetworkRegistry::builtin().resolve(network)
+}
+
+/// Pull the `Error(string)` reason out of a JSON-RPC error object.
+///
+/// Nodes put the revert payload in `error.data`, either as a hex string or
+/// nested one level deeper as `{"data": "0x..."}`.
+fn revert_reason(error: &serde_json::Value) -> Option<String> {
+    let data = error.get("data")?;
+    let hex_data = data.as_str().or_else(|| data.get("data")?.as_str())?;
+    let bytes = hex::decode(hex_data.trim_start_matches("0x")).ok()?;
+    crate::contract::decode_revert_reason(&bytes)
+}
+
+fn decode_logs_response(response: &str, id: u64) -> Result<Vec<serde_json::Value>, ClientError> {
+    let json = parse_single_response(response, id)?;
+    rpc_result(&json)?
+        .as_array()
+        .cloned()
+        .ok_or_else(|| ClientError::Parse("Result is not a list of logs".to_string()))
+}
+
+/// Extract the `result` bytes of an `eth_call` response, surfacing RPC errors
+/// and revert reasons
+fn decode_call_response(response: &str, id: u64) -> Result<Vec<u8>, ClientError> {
+    call_result_bytes(&parse_single_response(response, id)?)
+}
+
+/// Parse the response to a single (non-batch) request with id `id`.
+///
+/// Some providers answer a single request with a one-element batch array,
+/// so that shape is unwrapped as well.
+fn parse_single_response(response: &str, id: u64) -> Result<serde_json::Value, ClientError> {
+    let json: serde_json::Value = serde_json::from_str(response)
+        .map_err(|e| ClientError::Parse(format!("Parse error: {}", e)))?;
+
+    let json = match json {
+        serde_json::Value::Array(mut items) if items.len() == 1 => items.remove(0),
+        serde_json::Value::Array(items) => return Err(ClientError::Parse(format!(
+            "Expected one response object, got an array of {}",
+            items.len()
+        ))),
+        json => json,
     };
-    
-    let endpoints = urls
-        .into_iter()
-        .zip(descriptions.into_iter())
-        .map(|(url, description)| EndpointInfo { url, description })
-        .collect();
-    
-    Ok(endpoints)
+    check_response_id(&json, id)?;
+    Ok(json)
+}
+
+/// Check that a response object answers request `id`. An error response
+/// may have a `null` id when the node couldn't read the request's.
+fn check_response_id(json: &serde_json::Value, id: u64) -> Result<(), ClientError> {
+    match json.get("id") {
+        Some(got) if got.as_u64() == Some(id) => Ok(()),
+        None | Some(serde_json::Value::Null) if json.get("error").is_some() => Ok(()),
+        got => Err(ClientError::Parse(format!(
+            "Response id {} does not match request id {}",
+            got.unwrap_or(&serde_json::Value::Null),
+            id
+        ))),
+    }
+}
+
+/// Classify a JSON-RPC `error` object
+fn rpc_error(error: &serde_json::Value) -> ClientError {
+    let code = error.get("code").and_then(|c| c.as_i64());
+    let message = error.get("message").and_then(|m| m.as_str()).unwrap_or_default();
+    let lower = message.to_lowercase();
+
+    if let Some(reason) = revert_reason(error) {
+        return ClientError::Reverted { reason: Some(reason) };
+    }
+    // Geth and most providers use code 3 for reverts without (decodable) data
+    if code == Some(3) || lower.contains("execution reverted") {
+        return ClientError::Reverted { reason: None };
+    }
+    // -32005 is the EIP-1474 "limit exceeded" code; some providers only say so in the message
+    if code == Some(-32005)
+        || code == Some(429)
+        || lower.contains("rate limit")
+        || lower.contains("too many requests")
+    {
+        return ClientError::RateLimited;
+    }
+
+    match code {
+        Some(code) => ClientError::RpcError { code, message: message.to_string() },
+        None => ClientError::Network(format!("RPC error: {}", error)),
+    }
+}
+
+/// The `result` of one JSON-RPC response object, or its error
+fn rpc_result(json: &serde_json::Value) -> Result<&serde_json::Value, ClientError> {
+    if let Some(error) = json.get("error") {
+        return Err(rpc_error(error));
+    }
+
+    json.get("result")
+        .ok_or_else(|| ClientError::Parse(format!("No result or error in response: {}", json)))
+}
+
+/// The hex `result` of one `eth_call` response object as bytes
+fn call_result_bytes(json: &serde_json::Value) -> Result<Vec<u8>, ClientError> {
+    let result = rpc_result(json)?
+        .as_str()
+        .ok_or_else(|| ClientError::Parse("Result is not a hex string".to_string()))?;
+
+    let hex_str = result.strip_prefix("0x")
+        .ok_or_else(|| ClientError::Decode(format!("Result '{}' is not 0x-prefixed hex", result)))?;
+    hex::decode(hex_str)
+        .map_err(|e| ClientError::Decode(format!("Hex decode: {}", e)))
+}
+
+/// Whether `address` has code at the latest block, via `eth_getCode`
+async fn has_code(rpc: &Rpc, address: &str) -> Result<bool, ClientError> {
+    let address = format!("{:#x}", parse_address(address)?);
+    let code = rpc_request(rpc, "eth_getCode", serde_json::json!([address, "latest"])).await?;
+    let code = code.as_str()
+        .ok_or_else(|| ClientError::Parse(format!("Expected hex code, got {}", code)))?;
+    Ok(!code.trim_start_matches("0x").is_empty())
+}
+
+/// Current block number via `eth_blockNumber`
+async fn block_number(rpc: &Rpc) -> Result<u64, ClientError> {
+    let number = rpc_request(rpc, "eth_blockNumber", serde_json::json!([])).await?;
+    u64::try_from(quantity(&number)?)
+        .map_err(|_| ClientError::Parse(format!("Block number {} does not fit in u64", number)))
+}
+
+/// Decode a `uint256` return value that must fit in a `u64`
+fn decode_u64(result: &[u8]) -> Result<u64, ClientError> {
+    crate::contract::abi::AbiEncoder::decode_uint256(result)
+        .map_err(|e| ClientError::Decode(format!("Expected a uint256 that fits in u64: {}", e)))
+}
+
+/// Decode the `(string url, string description)` returned by `getEndpoint`
+fn decode_endpoint(result: &[u8]) -> Result<EndpointInfo, ClientError> {
+    crate::contract::decode_endpoint(result).map_err(ClientError::Decode)
+}
+
+/// Decode the `(string[], string[])` returned by `getAllEndpoints`
+fn decode_endpoints(result_bytes: &[u8]) -> Result<Vec<EndpointInfo>, ClientError> {
+    crate::contract::decode_all_endpoints(result_bytes).map_err(ClientError::Decode)
 }
@@ -0,0 +1,204 @@
+//! JavaScript bindings for the calldata builders and the read client
+//!
+//! Every builder returns the full calldata as a `0x`-prefixed hex string,
+//! ready to hand to a wallet as the transaction `data`. The `build_*`
+//! variants of the write calls return `{ selector, args, calldata }`
+//! instead, so a dapp can show exactly what the user is about to sign.
+
+use wasm_bindgen::prelude::*;
+
+use crate::contract::{self, abi::AbiEncoder, method_id, TransactionData};
+use crate::{ClientError, PolyEndpointClient};
+
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct PolyEndpointSdk;
+
+#[wasm_bindgen]
+impl PolyEndpointSdk {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// `0x`-prefixed 4-byte selector of a signature like `addEndpoint(string)`
+    pub fn method_id(&self, signature: &str) -> String {
+        format!("0x{}", hex::encode(AbiEncoder::function_selector(signature)))
+    }
+
+    pub fn add_endpoint(&self, url: &str) -> String {
+        contract::encode_add_endpoint(url).to_hex()
+    }
+
+    pub fn add_endpoint_with_description(&self, url: &str, description: &str) -> String {
+        contract::encode_add_endpoint_with_description(url, description).to_hex()
+    }
+
+    pub fn remove_endpoint(&self, url: &str) -> String {
+        contract::encode_remove_endpoint(url).to_hex()
+    }
+
+    pub fn has_endpoint(&self, url: &str) -> String {
+        contract::encode_has_endpoint(url).to_hex()
+    }
+
+    pub fn add_admin(&self, admin: &str) -> Result<String, String> {
+        contract::encode_add_admin(admin).map(|tx| tx.to_hex())
+    }
+
+    pub fn remove_admin(&self, admin: &str) -> Result<String, String> {
+        contract::encode_remove_admin(admin).map(|tx| tx.to_hex())
+    }
+
+    pub fn is_admin(&self, account: &str) -> Result<String, String> {
+        contract::encode_is_admin(account).map(|tx| tx.to_hex())
+    }
+
+    pub fn transfer_ownership(&self, new_owner: &str) -> Result<String, String> {
+        contract::encode_transfer_ownership(new_owner).map(|tx| tx.to_hex())
+    }
+
+    pub fn owner(&self) -> String {
+        contract::encode_owner().to_hex()
+    }
+
+    pub fn get_all_endpoints(&self) -> String {
+        contract::encode_get_all_endpoints().to_hex()
+    }
+
+    pub fn get_endpoint_count(&self) -> String {
+        contract::encode_get_endpoint_count().to_hex()
+    }
+
+    /// `getEndpoint(uint256)` for a decimal index.
+    ///
+    /// The index is a string so values beyond JavaScript's safe integer range
+    /// are encoded exactly; non-numeric input is an error.
+    pub fn get_endpoint(&self, index: &str) -> Result<String, String> {
+        let word = AbiEncoder::encode_uint256_decimal(index)?;
+        Ok(TransactionData::new(method_id::get_endpoint(), word.to_vec()).to_hex())
+    }
+
+    /// [`add_endpoint`](Self::add_endpoint) as `{ selector, args, calldata }`
+    pub fn build_add_endpoint(&self, url: &str) -> Result<JsValue, String> {
+        built_call(&contract::encode_add_endpoint(url))
+    }
+
+    pub fn build_add_endpoint_with_description(&self, url: &str, description: &str) -> Result<JsValue, String> {
+        built_call(&contract::encode_add_endpoint_with_description(url, description))
+    }
+
+    pub fn build_remove_endpoint(&self, url: &str) -> Result<JsValue, String> {
+        built_call(&contract::encode_remove_endpoint(url))
+    }
+
+    pub fn build_add_admin(&self, admin: &str) -> Result<JsValue, String> {
+        built_call(&contract::encode_add_admin(admin)?)
+    }
+
+    pub fn build_remove_admin(&self, admin: &str) -> Result<JsValue, String> {
+        built_call(&contract::encode_remove_admin(admin)?)
+    }
+
+    pub fn build_transfer_ownership(&self, new_owner: &str) -> Result<JsValue, String> {
+        built_call(&contract::encode_transfer_ownership(new_owner)?)
+    }
+}
+
+/// A call split into its parts, each as `0x`-prefixed hex
+#[derive(serde::Serialize)]
+struct BuiltCall {
+    selector: String,
+    args: String,
+    calldata: String,
+}
+
+impl From<&TransactionData> for BuiltCall {
+    fn from(tx: &TransactionData) -> Self {
+        Self {
+            selector: format!("0x{}", hex::encode(tx.selector)),
+            args: format!("0x{}", hex::encode(&tx.args)),
+            calldata: tx.to_hex(),
+        }
+    }
+}
+
+fn built_call(tx: &TransactionData) -> Result<JsValue, String> {
+    serde_wasm_bindgen::to_value(&BuiltCall::from(tx)).map_err(|e| e.to_string())
+}
+
+/// [`PolyEndpointClient`] for JavaScript, exported as `PolyEndpointClient`.
+///
+/// The constructor checks the contract address and throws an `Error` right
+/// away if it is malformed. Each read returns a `Promise` that rejects with
+/// an `Error` on failure.
+#[wasm_bindgen(js_name = PolyEndpointClient)]
+pub struct WasmPolyEndpointClient {
+    inner: PolyEndpointClient,
+}
+
+#[wasm_bindgen(js_class = PolyEndpointClient)]
+impl WasmPolyEndpointClient {
+    /// `new PolyEndpointClient(address, timeoutMs?)`; with a timeout, a
+    /// request that takes longer is aborted and its promise rejects
+    #[wasm_bindgen(constructor)]
+    pub fn new(contract_address: &str, timeout_ms: Option<u32>) -> Result<WasmPolyEndpointClient, JsError> {
+        let mut builder = PolyEndpointClient::builder().contract_address(contract_address);
+        if let Some(timeout_ms) = timeout_ms {
+            builder = builder.timeout(core::time::Duration::from_millis(timeout_ms.into()));
+        }
+        let inner = builder.build().map_err(js_error)?;
+        Ok(Self { inner })
+    }
+
+    /// The checksummed contract address
+    #[wasm_bindgen(getter, js_name = contractAddress)]
+    pub fn contract_address(&self) -> String {
+        self.inner.contract_address().to_string()
+    }
+
+    /// Resolves to an array of `{ url, description }` objects
+    #[wasm_bindgen(js_name = getEndpoints)]
+    pub fn get_endpoints(&self, network: String) -> js_sys::Promise {
+        let client = self.inner.clone();
+        wasm_bindgen_futures::future_to_promise(async move {
+            let endpoints = client.get_endpoints(&network).await.map_err(js_error)?;
+            let json = serde_json::to_string(&endpoints).map_err(|e| JsError::new(&e.to_string()))?;
+            js_sys::JSON::parse(&json)
+        })
+    }
+
+    /// Resolves to the number of endpoints
+    #[wasm_bindgen(js_name = getEndpointCount)]
+    pub fn get_endpoint_count(&self, network: String) -> js_sys::Promise {
+        let client = self.inner.clone();
+        wasm_bindgen_futures::future_to_promise(async move {
+            let count = client.get_endpoint_count(&network).await.map_err(js_error)?;
+            Ok(JsValue::from_f64(count as f64))
+        })
+    }
+
+    /// Resolves to the owner's checksummed address
+    pub fn owner(&self, network: String) -> js_sys::Promise {
+        let client = self.inner.clone();
+        wasm_bindgen_futures::future_to_promise(async move {
+            let owner = client.owner(&network).await.map_err(js_error)?;
+            Ok(JsValue::from_str(&owner))
+        })
+    }
+
+    /// Resolves to whether the contract address has code on `network`
+    #[wasm_bindgen(js_name = isContract)]
+    pub fn is_contract(&self, network: String) -> js_sys::Promise {
+        let client = self.inner.clone();
+        wasm_bindgen_futures::future_to_promise(async move {
+            let is_contract = client.is_contract(&network).await.map_err(js_error)?;
+            Ok(JsValue::from_bool(is_contract))
+        })
+    }
+}
+
+/// A JS `Error` carrying the client error's message
+fn js_error(error: ClientError) -> JsError {
+    JsError::new(&error.to_string())
+}
@@ -0,0 +1,91 @@
+//! Live endpoint events over a WebSocket provider
+
+use std::time::Duration;
+
+use ethers::providers::{Middleware, Provider, StreamExt, Ws};
+use ethers::types::{Filter, H256};
+use futures_util::Stream;
+use tokio::sync::mpsc;
+
+use crate::contract::event_topic;
+use crate::simple_client::{parse_address, ClientError, EndpointEvent, PolyEndpointClient};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+impl PolyEndpointClient {
+    /// Subscribe to `EndpointAdded` / `EndpointRemoved` events as they are mined.
+    ///
+    /// The first connection is made before returning so a bad URL fails here.
+    /// After that, dropped connections are retried with exponential backoff
+    /// (1s doubling up to 60s); events emitted while disconnected are not
+    /// replayed, so use [`PolyEndpointClient::get_endpoint_events`] to fill
+    /// gaps if that matters. Logs that fail to decode are yielded as errors.
+    /// The background task stops when the stream is dropped.
+    pub async fn subscribe_endpoints(
+        &self,
+        ws_url: impl Into<String>,
+    ) -> Result<impl Stream<Item = Result<EndpointEvent, ClientError>>, ClientError> {
+        let ws_url = ws_url.into();
+        let filter = Filter::new()
+            .address(parse_address(self.contract_address())?)
+            .topic0(vec![
+                H256(event_topic::endpoint_added()),
+                H256(event_topic::endpoint_removed()),
+            ]);
+
+        let provider = connect(&ws_url).await?;
+        let (tx, rx) = mpsc::channel(64);
+        tokio::spawn(forward_events(provider, ws_url, filter, tx));
+
+        Ok(futures_util::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|event| (event, rx))
+        }))
+    }
+}
+
+async fn connect(ws_url: &str) -> Result<Provider<Ws>, ClientError> {
+    Provider::<Ws>::connect(ws_url)
+        .await
+        .map_err(|e| ClientError::Network(format!("WebSocket connect to {} failed: {}", ws_url, e)))
+}
+
+/// Pump subscription logs into `tx`, reconnecting until the receiver goes away
+async fn forward_events(
+    mut provider: Provider<Ws>,
+    ws_url: String,
+    filter: Filter,
+    tx: mpsc::Sender<Result<EndpointEvent, ClientError>>,
+) {
+    let mut backoff = INITIAL_BACKOFF;
+    loop {
+        if let Ok(mut logs) = provider.subscribe_logs(&filter).await {
+            backoff = INITIAL_BACKOFF;
+            while let Some(log) = logs.next().await {
+                // Logs undone by a reorg are re-sent with `removed: true`
+                if log.removed == Some(true) {
+                    continue;
+                }
+                let event = serde_json::to_value(&log)
+                    .map_err(|e| ClientError::Decode(format!("Log: {}", e)))
+                    .and_then(|log| EndpointEvent::from_log(&log));
+                if tx.send(event).await.is_err() {
+                    return;
+                }
+            }
+        }
+
+        // Subscription ended or failed: back off, then reconnect
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(backoff) => {}
+                _ = tx.closed() => return,
+            }
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+            if let Ok(reconnected) = connect(&ws_url).await {
+                provider = reconnected;
+                break;
+            }
+        }
+    }
+}
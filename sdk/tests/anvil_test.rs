@@ -0,0 +1,103 @@
+//! End-to-end tests against a local anvil node
+//!
+//! Needs `anvil` on the PATH and the compiled contract. Run with:
+//! `npx hardhat compile` (or `forge build`), then
+//! `cargo test --features anvil --test anvil_test`.
+//! Set `POLYENDPOINT_ARTIFACT` to use an artifact from somewhere else.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::path::Path;
+use std::sync::Arc;
+
+use ethers::middleware::SignerMiddleware;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, Bytes, TransactionRequest};
+use ethers::utils::{Anvil, AnvilInstance};
+use polyendpoint_sdk::contract::{self, TransactionData};
+use polyendpoint_sdk::PolyEndpointClient;
+
+type Client = SignerMiddleware<Provider<Http>, LocalWallet>;
+
+/// Hardhat and Foundry artifact locations, relative to the SDK crate
+const ARTIFACTS: [&str; 2] = [
+    "../artifacts/contracts/PolyEndpoint.sol/PolyEndpoint.json",
+    "../out/PolyEndpoint.sol/PolyEndpoint.json",
+];
+
+/// Creation bytecode from a Hardhat (`"bytecode": "0x.."`) or Foundry
+/// (`"bytecode": { "object": "0x.." }`) artifact
+fn bytecode() -> Bytes {
+    let path = std::env::var("POLYENDPOINT_ARTIFACT").ok()
+        .or_else(|| ARTIFACTS.iter().find(|path| Path::new(path).exists()).map(|path| path.to_string()))
+        .expect("no PolyEndpoint artifact found; compile the contract or set POLYENDPOINT_ARTIFACT");
+    let artifact: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let bytecode = &artifact["bytecode"];
+    bytecode.as_str()
+        .or_else(|| bytecode["object"].as_str())
+        .unwrap_or_else(|| panic!("{} has no bytecode", path))
+        .parse()
+        .unwrap()
+}
+
+/// Start anvil and deploy the contract from its first dev account
+async fn deploy() -> (AnvilInstance, Arc<Client>, Address) {
+    let anvil = Anvil::new().spawn();
+    let provider = Provider::<Http>::try_from(anvil.endpoint()).unwrap();
+    let wallet: LocalWallet = anvil.keys()[0].clone().into();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet.with_chain_id(anvil.chain_id())));
+
+    let deploy = TransactionRequest::new().data(bytecode());
+    let receipt = client.send_transaction(deploy, None).await.unwrap().await.unwrap().unwrap();
+    let address = receipt.contract_address.expect("deployment has no contract address");
+    (anvil, client, address)
+}
+
+/// Send calldata built by the SDK and wait for it to be mined
+async fn send(client: &Client, contract: Address, tx: TransactionData) {
+    let request = TransactionRequest::new().to(contract).data(tx.build());
+    let receipt = client.send_transaction(request, None).await.unwrap().await.unwrap().unwrap();
+    assert_eq!(receipt.status, Some(1u64.into()), "{:?} reverted", tx.selector_name());
+}
+
+#[tokio::test]
+async fn test_add_remove_round_trip() {
+    let (anvil, client, contract) = deploy().await;
+    let sdk = PolyEndpointClient::new(format!("{:#x}", contract));
+    let rpc = anvil.endpoint();
+
+    assert!(sdk.get_endpoints(&rpc).await.unwrap().is_empty());
+    assert_eq!(sdk.owner(&rpc).await.unwrap(), format!("{:#x}", client.address()));
+
+    send(&client, contract, contract::encode_add_endpoint_with_description("https://a.example.com", "Primary")).await;
+    send(&client, contract, contract::encode_add_endpoint("https://b.example.com")).await;
+
+    let endpoints = sdk.get_endpoints(&rpc).await.unwrap();
+    assert_eq!(endpoints.len(), 2);
+    assert_eq!(endpoints[0].url, "https://a.example.com");
+    assert_eq!(endpoints[0].description, "Primary");
+    assert_eq!(endpoints[1].url, "https://b.example.com");
+    assert_eq!(sdk.get_endpoint_count(&rpc).await.unwrap(), 2);
+
+    send(&client, contract, contract::encode_remove_endpoint("https://a.example.com")).await;
+    let endpoints = sdk.get_endpoints(&rpc).await.unwrap();
+    assert_eq!(endpoints.len(), 1);
+    assert_eq!(endpoints[0].url, "https://b.example.com");
+}
+
+#[tokio::test]
+async fn test_reverted_write_is_reported() {
+    let (anvil, client, contract) = deploy().await;
+    let sdk = PolyEndpointClient::new(format!("{:#x}", contract));
+
+    // Removing an endpoint that was never added reverts in the simulation
+    let tx = contract::encode_remove_endpoint("https://missing.example.com");
+    let from = format!("{:#x}", client.address());
+    let err = sdk.estimate_cost(anvil.endpoint(), &from, &tx).await.unwrap_err();
+    assert!(
+        matches!(&err, polyendpoint_sdk::ClientError::Reverted { reason: Some(reason) } if reason.contains("does not exist")),
+        "{:?}",
+        err
+    );
+}
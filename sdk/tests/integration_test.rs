@@ -29,3 +29,23 @@ async fn test_get_endpoints_base_sepolia() {
     }
 }
 
+
+/// Paging should return the same endpoints as the fetch-all call
+#[tokio::test]
+async fn test_get_endpoints_paged_base_sepolia() {
+    let client = PolyEndpointClient::new("0xf16e03526d1be6d120cfbf5a24e1ac78a8192663");
+
+    let all = client.get_endpoints("base-sepolia").await.expect("Failed to get endpoints");
+
+    let first = client.get_endpoints_paged("base-sepolia", 0, 1).await.expect("Failed to get page");
+    assert_eq!(first.total, all.len() as u64);
+    assert_eq!(first.endpoints.len(), 1);
+    assert!(first.has_more);
+
+    let rest = client.get_endpoints_paged("base-sepolia", 1, 10).await.expect("Failed to get page");
+    assert!(!rest.has_more);
+
+    let paged: Vec<_> = first.endpoints.iter().chain(&rest.endpoints).map(|e| e.url.clone()).collect();
+    let expected: Vec<_> = all.iter().map(|e| e.url.clone()).collect();
+    assert_eq!(paged, expected);
+}
@@ -1,7 +1,7 @@
 //! Local tests that don't require network access
 //! Can run without --ignored flag
 
-use polyendpoint_sdk::PolyEndpointClient;
+use polyendpoint_sdk::{BlockId, ClientError, PolyEndpointClient};
 
 #[test]
 fn test_client_creation() {
@@ -9,8 +9,37 @@ fn test_client_creation() {
     
     assert_eq!(client.contract_address(), "0x1234567890123456789012345678901234567890");
     
-    let client2 = PolyEndpointClient::new("0xABC");
-    assert_eq!(client2.contract_address(), "0xABC");
+    let client2 = PolyEndpointClient::try_new("0xABC");
+    assert!(matches!(client2, Err(ClientError::InvalidAddress(_))));
+}
+
+#[test]
+fn test_try_new_normalizes_checksum() {
+    let client = PolyEndpointClient::try_new("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap();
+    assert_eq!(client.contract_address(), "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+
+    // Missing 0x prefix is accepted and normalized too
+    let client = PolyEndpointClient::try_new("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap();
+    assert_eq!(client.contract_address(), "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+
+    assert!(PolyEndpointClient::try_new("0xzzaeb6053f3e94c9b9a09f33669435e7ef1beaed").is_err());
+}
+
+#[test]
+fn test_block_id() {
+    assert_eq!(BlockId::default(), BlockId::Latest);
+    assert_eq!("latest".parse::<BlockId>().unwrap(), BlockId::Latest);
+    assert_eq!("pending".parse::<BlockId>().unwrap(), BlockId::Pending);
+    assert_eq!("12345".parse::<BlockId>().unwrap(), BlockId::Number(12345));
+    assert_eq!("0x3039".parse::<BlockId>().unwrap(), BlockId::Number(12345));
+    assert!("soon".parse::<BlockId>().is_err());
+
+    let hash = format!("0x{}", "ab".repeat(32));
+    assert_eq!(hash.parse::<BlockId>().unwrap(), BlockId::Hash([0xab; 32]));
+
+    assert_eq!(BlockId::Latest.to_param(), "latest");
+    assert_eq!(BlockId::from(12345).to_param(), "0x3039");
+    assert_eq!(BlockId::Hash([0xab; 32]).to_param()["blockHash"], hash.as_str());
 }
 
 #[test]
@@ -24,3 +53,1190 @@ fn test_network_urls() {
     // The actual RPC URL conversion is tested in integration tests
 }
 
+
+#[test]
+fn test_function_selectors() {
+    use polyendpoint_sdk::contract::abi::AbiEncoder;
+
+    assert_eq!(AbiEncoder::function_selector("transferOwnership(address)"), [0xf2, 0xfd, 0xe3, 0x8b]);
+    assert_eq!(
+        AbiEncoder::function_selector("getAllEndpoints()"),
+        ethers::utils::keccak256("getAllEndpoints()")[0..4]
+    );
+
+    // The precomputed selectors must match their signatures
+    for (signature, selector) in polyendpoint_sdk::contract::method_id::SIGNATURES {
+        assert_eq!(AbiEncoder::function_selector(signature), selector, "{}", signature);
+    }
+
+    // The crate-root helpers are the same hash
+    assert_eq!(polyendpoint_sdk::selector("removeEndpoint(string)"), ethers::utils::keccak256("removeEndpoint(string)")[0..4]);
+    assert_eq!(polyendpoint_sdk::keccak256(b"getAllEndpoints()"), ethers::utils::keccak256("getAllEndpoints()"));
+}
+
+#[test]
+fn test_encoders_match_ethers() {
+    use ethers::abi::Token;
+    use polyendpoint_sdk::contract;
+
+    let url = "https://rpc.example.com/v1";
+    let data = contract::encode_add_endpoint(url).build();
+    let expected = [
+        &ethers::utils::keccak256("addEndpoint(string)")[0..4],
+        &ethers::abi::encode(&[Token::String(url.to_string())])[..],
+    ].concat();
+    assert_eq!(data, expected);
+
+    let owner = "0x1234567890123456789012345678901234567890";
+    let data = contract::encode_transfer_ownership(owner).unwrap().build();
+    let expected = [
+        &ethers::utils::keccak256("transferOwnership(address)")[0..4],
+        &ethers::abi::encode(&[Token::Address(owner.parse().unwrap())])[..],
+    ].concat();
+    assert_eq!(data, expected);
+
+    // A value doesn't change the calldata
+    let tx = contract::encode_add_endpoint(url).with_value(1_000);
+    assert_eq!(tx.value, 1_000);
+    assert_eq!(tx.build(), contract::encode_add_endpoint(url).build());
+
+    assert!(contract::encode_transfer_ownership("0xABC").is_err());
+
+    let args = ethers::abi::encode(&[Token::String(url.to_string()), Token::String("Primary".to_string())]);
+    let expected = [&ethers::utils::keccak256("addEndpoint(string,string)")[0..4], &args[..]].concat();
+    assert_eq!(contract::encode_add_endpoint_with_description(url, "Primary").build(), expected);
+
+    // Hashed-key deployments take keccak256(url) as a bytes32
+    let hash = contract::endpoint_hash(url);
+    assert_eq!(hash, ethers::utils::keccak256(url));
+    let args = ethers::abi::encode(&[Token::FixedBytes(hash.to_vec())]);
+    let expected = [&ethers::utils::keccak256("addEndpoint(bytes32)")[0..4], &args[..]].concat();
+    assert_eq!(contract::encode_add_endpoint_by_hash(hash).build(), expected);
+    let expected = [&ethers::utils::keccak256("hasEndpoint(bytes32)")[0..4], &args[..]].concat();
+    assert_eq!(contract::encode_has_endpoint_by_hash(hash).build(), expected);
+
+    for items in [&[][..], &[url][..], &[url, "", "https://a-much-longer-url.example.com/with/a/path/over/32/bytes"][..]] {
+        let expected = ethers::abi::encode(&[Token::Array(
+            items.iter().map(|s| Token::String(s.to_string())).collect(),
+        )]);
+        assert_eq!(polyendpoint_sdk::contract::abi::AbiEncoder::encode_string_array(items), expected, "{:?}", items);
+    }
+}
+
+#[test]
+fn test_transaction_data_from_hex() {
+    use polyendpoint_sdk::contract::{self, method_id, TransactionData};
+
+    let tx = contract::encode_add_endpoint_with_description("https://rpc.example.com", "Primary");
+    let parsed = TransactionData::from_hex(&tx.to_hex()).unwrap();
+    assert_eq!(parsed, tx);
+    assert_eq!(parsed.selector_name(), Some("addEndpoint(string,string)"));
+
+    // Bare hex and surrounding whitespace are accepted
+    let parsed = TransactionData::from_hex(&format!(" {} ", &tx.to_hex()[2..])).unwrap();
+    assert_eq!(parsed.selector, method_id::ADD_ENDPOINT_WITH_DESCRIPTION);
+
+    let owner = TransactionData::from_hex("0x8da5cb5b").unwrap();
+    assert!(owner.args.is_empty());
+    assert_eq!(owner.selector_name(), Some("owner()"));
+
+    assert_eq!(TransactionData::from_hex("0xdeadbeef00").unwrap().selector_name(), None);
+    assert!(TransactionData::from_hex("0x8da5cb").is_err());
+    assert!(TransactionData::from_hex("0xzz").is_err());
+    assert!(TransactionData::from_hex("").is_err());
+}
+
+#[test]
+fn test_transaction_data_serde() {
+    use polyendpoint_sdk::contract::{self, TransactionData};
+
+    let batch = vec![
+        contract::encode_add_endpoint_with_description("https://rpc.example.com", "Primary"),
+        contract::encode_owner().with_value(1_000_000_000_000_000_000),
+    ];
+    let json = serde_json::to_string(&batch).unwrap();
+    assert_eq!(serde_json::from_str::<Vec<TransactionData>>(&json).unwrap(), batch);
+
+    let value = serde_json::to_value(&batch[1]).unwrap();
+    assert_eq!(value, serde_json::json!({ "selector": "0x8da5cb5b", "args": "0x", "value": 1_000_000_000_000_000_000u64 }));
+
+    let bad_selector = serde_json::json!({ "selector": "0x8da5cb", "args": "0x", "value": 0 });
+    assert!(serde_json::from_value::<TransactionData>(bad_selector).is_err());
+    let bad_args = serde_json::json!({ "selector": "0x8da5cb5b", "args": "0xzz", "value": 0 });
+    assert!(serde_json::from_value::<TransactionData>(bad_args).is_err());
+}
+
+#[test]
+fn test_decode_revert_reason() {
+    use polyendpoint_sdk::contract::{decode_revert_reason, ERROR_STRING_SELECTOR};
+
+    let mut data = ERROR_STRING_SELECTOR.to_vec();
+    data.extend(ethers::abi::encode(&[ethers::abi::Token::String(
+        "PolyEndpoint: caller is not an admin or owner".to_string(),
+    )]));
+    assert_eq!(
+        decode_revert_reason(&data).as_deref(),
+        Some("PolyEndpoint: caller is not an admin or owner")
+    );
+
+    // Truncated payloads and other selectors are not reasons
+    assert_eq!(decode_revert_reason(&data[..data.len() - 32]), None);
+    assert_eq!(decode_revert_reason(&[0xde, 0xad, 0xbe, 0xef]), None);
+}
+
+#[test]
+fn test_endpoint_event_from_log() {
+    use ethers::utils::{hex, keccak256};
+    use polyendpoint_sdk::{EndpointEvent, EndpointEventKind};
+
+    let url = "https://rpc.example.com";
+    let added = serde_json::json!({
+        "topics": [
+            format!("0x{}", hex::encode(keccak256("EndpointAdded(string,string)"))),
+            format!("0x{}", hex::encode(keccak256(url))),
+        ],
+        "data": format!("0x{}", hex::encode(ethers::abi::encode(&[ethers::abi::Token::String("Primary".into())]))),
+        "blockNumber": "0x10",
+        "transactionHash": "0xabc",
+    });
+    let event = EndpointEvent::from_log(&added).unwrap();
+    assert_eq!(event.kind, EndpointEventKind::Added);
+    assert_eq!(event.description.as_deref(), Some("Primary"));
+    assert_eq!(event.block, 16);
+    assert!(event.matches_url(url));
+    assert!(!event.matches_url("https://other.example.com"));
+
+    let removed = serde_json::json!({
+        "topics": [
+            format!("0x{}", hex::encode(keccak256("EndpointRemoved(string)"))),
+            format!("0x{}", hex::encode(keccak256(url))),
+        ],
+        "data": "0x",
+        "blockNumber": "0x11",
+        "transactionHash": "0xdef",
+    });
+    let event = EndpointEvent::from_log(&removed).unwrap();
+    assert_eq!(event.kind, EndpointEventKind::Removed);
+    assert_eq!(event.description, None);
+
+    let unrelated = serde_json::json!({
+        "topics": [format!("0x{}", hex::encode(keccak256("AdminAdded(address)"))), format!("0x{}", "00".repeat(32))],
+        "data": "0x",
+        "blockNumber": "0x1",
+        "transactionHash": "0x1",
+    });
+    assert!(EndpointEvent::from_log(&unrelated).is_err());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_admin_events() {
+    use ethers::utils::{hex, keccak256};
+    use polyendpoint_sdk::{AdminEvent, AdminEventKind};
+
+    let topic = |signature: &str| format!("0x{}", hex::encode(keccak256(signature)));
+    let address_topic = |address: &str| format!("0x{}{}", "00".repeat(12), address.trim_start_matches("0x"));
+    let owner = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    let admin = "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359";
+    let log = |topics: Vec<String>, block: &str| serde_json::json!({
+        "topics": topics,
+        "data": "0x",
+        "blockNumber": block,
+        "transactionHash": "0xabc",
+    });
+    let logs = vec![
+        log(vec![topic("OwnershipTransferred(address,address)"), address_topic(&"00".repeat(20)), address_topic(owner)], "0x1"),
+        log(vec![topic("AdminAdded(address)"), address_topic(admin)], "0x2"),
+        log(vec![topic("AdminRemoved(address)"), address_topic(&admin.to_lowercase())], "0x3"),
+    ];
+
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    let url = mock_rpc(vec![serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": logs }).to_string()]).await;
+    let events = client.get_admin_events(&url, BlockId::Number(0), BlockId::Latest).await.unwrap();
+    assert_eq!(events.iter().map(|e| e.block).collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(events[0].kind, AdminEventKind::OwnershipTransferred {
+        previous_owner: format!("0x{}", "00".repeat(20)),
+        new_owner: owner.to_string(),
+    });
+    assert_eq!(events[1].kind, AdminEventKind::AdminAdded { admin: admin.to_string() });
+    assert_eq!(events[2].kind, AdminEventKind::AdminRemoved { admin: admin.to_string() });
+    assert_eq!(events[2].transaction_hash, "0xabc");
+
+    // Endpoint logs, malformed address topics and missing topics are rejected
+    let endpoint = log(vec![topic("EndpointRemoved(string)"), topic("https://rpc.example.com")], "0x4");
+    assert!(matches!(AdminEvent::from_log(&endpoint), Err(ClientError::Decode(_))));
+    let bad_address = log(vec![topic("AdminAdded(address)"), format!("0x{}", "ff".repeat(32))], "0x5");
+    assert!(matches!(AdminEvent::from_log(&bad_address), Err(ClientError::Decode(_))));
+    let missing_owner = log(vec![topic("OwnershipTransferred(address,address)"), address_topic(owner)], "0x6");
+    assert!(matches!(AdminEvent::from_log(&missing_owner), Err(ClientError::Decode(_))));
+    assert!(client.get_admin_events(&url, BlockId::Hash([1; 32]), BlockId::Latest).await.is_err());
+}
+
+#[test]
+fn test_encode_call() {
+    use ethers::abi::Token;
+    use polyendpoint_sdk::contract::{self, abi::AbiToken};
+
+    assert_eq!(
+        contract::encode_call("addEndpoint(string)", &[AbiToken::String("x".into())]).unwrap(),
+        contract::encode_add_endpoint("x")
+    );
+
+    // Mixed static and dynamic parameters
+    let owner = "0x1234567890123456789012345678901234567890";
+    let data = contract::encode_call(
+        "f(string,address,bytes,uint256,bool)",
+        &[
+            AbiToken::String("https://rpc.example.com".into()),
+            AbiToken::Address(owner.into()),
+            AbiToken::Bytes(vec![0xde, 0xad]),
+            AbiToken::Uint(7),
+            AbiToken::Bool(true),
+        ],
+    )
+    .unwrap();
+    let expected = ethers::abi::encode(&[
+        Token::String("https://rpc.example.com".into()),
+        Token::Address(owner.parse().unwrap()),
+        Token::Bytes(vec![0xde, 0xad]),
+        Token::Uint(7.into()),
+        Token::Bool(true),
+    ]);
+    assert_eq!(data.args, expected);
+
+    assert!(contract::encode_call("addAdmin(address)", &[AbiToken::Address("0xABC".into())]).is_err());
+}
+
+#[test]
+fn test_encode_uint256_decimal() {
+    use polyendpoint_sdk::contract::abi::AbiEncoder;
+
+    assert_eq!(AbiEncoder::encode_uint256_decimal("0").unwrap(), AbiEncoder::encode_uint256(0));
+    assert_eq!(
+        AbiEncoder::encode_uint256_decimal("18446744073709551615").unwrap(),
+        AbiEncoder::encode_uint256(u64::MAX)
+    );
+
+    let max = ethers::types::U256::MAX;
+    let mut expected = [0u8; 32];
+    max.to_big_endian(&mut expected);
+    assert_eq!(AbiEncoder::encode_uint256_decimal(&max.to_string()).unwrap(), expected);
+
+    // 2^256 overflows; signs, hex and empty strings are rejected
+    let too_big = "115792089237316195423570985008687907853269984665640564039457584007913129639936";
+    assert!(AbiEncoder::encode_uint256_decimal(too_big).is_err());
+    assert!(AbiEncoder::encode_uint256_decimal("-1").is_err());
+    assert!(AbiEncoder::encode_uint256_decimal("0x10").is_err());
+    assert!(AbiEncoder::encode_uint256_decimal("").is_err());
+
+    for value in [0, 1, 0x1234_5678, u64::MAX] {
+        assert_eq!(AbiEncoder::decode_uint256(&AbiEncoder::encode_uint256(value)), Ok(value));
+    }
+    assert!(AbiEncoder::decode_uint256(&expected).is_err());
+    assert!(AbiEncoder::decode_uint256(&[0u8; 31]).is_err());
+}
+
+#[test]
+fn test_encode_string_multibyte() {
+    use ethers::abi::Token;
+    use polyendpoint_sdk::contract::{self, abi::AbiEncoder};
+
+    let a31 = "a".repeat(31);
+    let a32 = "a".repeat(32);
+    let e32 = format!("{}é", "a".repeat(30));
+    let e33 = format!("{}é", "a".repeat(31));
+    let cases = [
+        "",
+        "https://bücher.example/rpc",
+        "Café ☕ endpoint 🚀",
+        "https://例え.テスト",
+        a31.as_str(),
+        a32.as_str(),
+        e32.as_str(),
+        e33.as_str(),
+    ];
+
+    for s in cases {
+        // The length word counts UTF-8 bytes, not characters
+        let encoded = AbiEncoder::encode_string(s);
+        assert_eq!(encoded[..32], AbiEncoder::encode_uint256(s.len() as u64), "{:?}", s);
+        assert_eq!(encoded.len(), 32 + s.len().div_ceil(32) * 32, "{:?}", s);
+        assert_eq!(
+            AbiEncoder::encode_string_args(&[s]),
+            ethers::abi::encode(&[Token::String(s.to_string())]),
+            "{:?}",
+            s
+        );
+        assert_eq!(contract::abi::decode_string(&AbiEncoder::encode_string_args(&[s])).as_deref(), Some(s));
+        assert_eq!(AbiEncoder::decode_string(&encoded).as_deref(), Ok(s));
+    }
+
+    // Truncated data, oversized lengths and invalid UTF-8 are errors, not panics
+    let encoded = AbiEncoder::encode_string("https://rpc.example");
+    assert!(AbiEncoder::decode_string(&encoded[..40]).is_err());
+    assert!(AbiEncoder::decode_string(&encoded[..31]).is_err());
+    assert!(AbiEncoder::decode_string(&AbiEncoder::encode_uint256(u64::MAX)).is_err());
+    assert!(AbiEncoder::decode_string(&AbiEncoder::encode_bytes(&[0xff, 0xfe])).is_err());
+
+    // 30 ASCII bytes + 2-byte 'é' fill exactly one word; one more byte spills into a second
+    assert_eq!(AbiEncoder::encode_string(&e32).len(), 64);
+    assert_eq!(AbiEncoder::encode_string(&e33).len(), 96);
+
+    let args = ethers::abi::encode(&[
+        Token::String("https://ñandú.example".to_string()),
+        Token::String("Nœud principal 🌍".to_string()),
+    ]);
+    let expected = [&ethers::utils::keccak256("addEndpoint(string,string)")[0..4], &args[..]].concat();
+    assert_eq!(
+        contract::encode_add_endpoint_with_description("https://ñandú.example", "Nœud principal 🌍").build(),
+        expected
+    );
+}
+
+#[cfg(feature = "ws")]
+#[tokio::test]
+async fn test_subscribe_endpoints_connect_error() {
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    let result = client.subscribe_endpoints("ws://127.0.0.1:1").await;
+    assert!(matches!(result, Err(ClientError::Network(_))));
+}
+
+#[test]
+fn test_ens_namehash() {
+    use polyendpoint_sdk::ens::{is_ens_name, namehash};
+
+    // Vectors from EIP-137
+    assert_eq!(namehash(""), [0u8; 32]);
+    assert_eq!(
+        hex::encode(namehash("eth")),
+        "93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae"
+    );
+    assert_eq!(
+        hex::encode(namehash("foo.eth")),
+        "de9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f"
+    );
+    assert_eq!(namehash("Foo.ETH"), namehash("foo.eth"));
+
+    assert!(is_ens_name("vitalik.eth"));
+    assert!(!is_ens_name("0x1234567890123456789012345678901234567890"));
+}
+
+/// Serve `bodies` in order, one per connection, as JSON-RPC responses on a
+/// local port and return its URL.
+///
+/// Like a node, the mock answers with the request's ids: a single response
+/// takes the request's id, and the numeric ids in a batch response are
+/// replaced, smallest first, by the request ids in order (so a reordered
+/// batch stays reordered). String ids are sent as is.
+#[cfg(not(target_arch = "wasm32"))]
+async fn mock_rpc(bodies: Vec<String>) -> String {
+    mock_http(bodies.into_iter().map(|body| (200, body)).collect()).await
+}
+
+/// Like [`mock_rpc`], but each response also sets the HTTP status code
+#[cfg(not(target_arch = "wasm32"))]
+async fn mock_http(responses: Vec<(u16, String)>) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        for (status, body) in responses {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            // Read the headers and the Content-Length body before answering
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_lowercase();
+                if let Some(header_end) = text.find("\r\n\r\n") {
+                    let content_length = text.lines()
+                        .find_map(|l| l.strip_prefix("content-length:"))
+                        .and_then(|v| v.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if request.len() >= header_end + 4 + content_length || n == 0 {
+                        break;
+                    }
+                }
+            }
+            let body = echo_ids(&request, body);
+            let response = format!(
+                "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    url
+}
+
+/// Give a canned response the ids of the raw HTTP `request`; see [`mock_rpc`]
+#[cfg(not(target_arch = "wasm32"))]
+fn echo_ids(request: &[u8], body: String) -> String {
+    let request = String::from_utf8_lossy(request);
+    let Some((_, request)) = request.split_once("\r\n\r\n") else { return body };
+    let (Ok(request), Ok(mut response)) = (
+        serde_json::from_str::<serde_json::Value>(request),
+        serde_json::from_str::<serde_json::Value>(&body),
+    ) else {
+        return body;
+    };
+
+    match (&request, &mut response) {
+        (serde_json::Value::Array(requests), serde_json::Value::Array(responses)) => {
+            let mut canned: Vec<u64> = responses.iter().filter_map(|r| r["id"].as_u64()).collect();
+            canned.sort_unstable();
+            for response in responses.iter_mut() {
+                if let Some(position) = response["id"].as_u64().and_then(|id| canned.binary_search(&id).ok()) {
+                    response["id"] = requests[position]["id"].clone();
+                }
+            }
+        }
+        // Some providers answer a single request with a one-element array
+        (request, serde_json::Value::Array(responses)) => {
+            for response in responses.iter_mut().filter(|r| r["id"].is_u64()) {
+                response["id"] = request["id"].clone();
+            }
+        }
+        (request, response) if response["id"].is_u64() => response["id"] = request["id"].clone(),
+        _ => {}
+    }
+    response.to_string()
+}
+
+/// ABI-encoded `getAllEndpoints()` result for one endpoint
+#[cfg(not(target_arch = "wasm32"))]
+fn all_endpoints_result() -> String {
+    use ethers::abi::Token;
+    format!("0x{}", hex::encode(ethers::abi::encode(&[
+        Token::Array(vec![Token::String("https://rpc.example.com".into())]),
+        Token::Array(vec![Token::String("Primary".into())]),
+    ])))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_response_shapes() {
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    let result = all_endpoints_result();
+
+    let object = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": result });
+    let array = serde_json::json!([{ "jsonrpc": "2.0", "id": 1, "result": result }]);
+    let url = mock_rpc(vec![object.to_string(), array.to_string()]).await;
+
+    for _ in 0..2 {
+        let endpoints = client.get_endpoints(&url).await.unwrap();
+        assert_eq!(endpoints.len(), 1);
+        assert_eq!(endpoints[0].url, "https://rpc.example.com");
+        assert_eq!(endpoints[0].description, "Primary");
+    }
+
+    // Neither shape carries a result
+    let url = mock_rpc(vec![
+        serde_json::json!({ "jsonrpc": "2.0", "id": 1 }).to_string(),
+        serde_json::json!([]).to_string(),
+    ]).await;
+    assert!(matches!(client.get_endpoints(&url).await, Err(ClientError::Parse(_))));
+    assert!(matches!(client.get_endpoints(&url).await, Err(ClientError::Parse(_))));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_response_ids() {
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    let count = format!("0x{:064x}", 3);
+
+    // A response for some other request is rejected, not decoded
+    let url = mock_rpc(vec![
+        serde_json::json!({ "jsonrpc": "2.0", "id": "other", "result": count }).to_string(),
+        serde_json::json!({ "jsonrpc": "2.0", "id": null, "error": { "code": -32700, "message": "Parse error" } }).to_string(),
+    ]).await;
+    match client.get_endpoint_count(&url).await {
+        Err(ClientError::Parse(message)) => assert!(message.contains("does not match request id"), "{}", message),
+        other => panic!("expected an id mismatch, got {:?}", other),
+    }
+    // An error the node couldn't tie to a request still comes through
+    assert!(matches!(client.get_endpoint_count(&url).await, Err(ClientError::RpcError { code: -32700, .. })));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_has_endpoint() {
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    let url = mock_rpc(vec![
+        serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": format!("0x{:064x}", 1) }).to_string(),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": format!("0x{:064x}", 0) }).to_string(),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": format!("0x{:064x}", 2) }).to_string(),
+    ]).await;
+
+    assert!(client.has_endpoint(&url, "https://rpc.example.com").await.unwrap());
+    assert!(!client.has_endpoint(&url, "https://rpc.example.com").await.unwrap());
+    assert!(matches!(client.has_endpoint(&url, "https://rpc.example.com").await, Err(ClientError::Decode(_))));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_endpoint_cache() {
+    use std::time::Duration;
+
+    let response = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": all_endpoints_result() }).to_string();
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890")
+        .with_cache(Duration::from_secs(60));
+
+    // The mock answers twice, then refuses connections
+    let url = mock_rpc(vec![response.clone(), response.clone()]).await;
+    assert_eq!(client.get_endpoints(&url).await.unwrap().len(), 1);
+    assert_eq!(client.get_endpoints(&url).await.unwrap().len(), 1);
+    assert_eq!(client.clone().get_endpoints(&url).await.unwrap().len(), 1);
+    assert_eq!(client.refresh(&url).await.unwrap().len(), 1);
+    assert_eq!(client.get_endpoints(&url).await.unwrap().len(), 1);
+
+    client.clear_cache();
+    assert!(matches!(client.get_endpoints(&url).await, Err(ClientError::Network(_))));
+
+    // A zero TTL never serves from the cache
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890").with_cache(Duration::ZERO);
+    let url = mock_rpc(vec![response]).await;
+    assert!(client.get_endpoints(&url).await.is_ok());
+    assert!(client.get_endpoints(&url).await.is_err());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_client_builder() {
+    use std::time::Duration;
+
+    let address = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed";
+    assert!(matches!(PolyEndpointClient::builder().build(), Err(ClientError::InvalidAddress(_))));
+    assert!(matches!(
+        PolyEndpointClient::builder().contract_address("0x1234").build(),
+        Err(ClientError::InvalidAddress(_))
+    ));
+    assert!(matches!(
+        PolyEndpointClient::builder().contract_address(address).header("Bad Name", "x").build(),
+        Err(ClientError::Config(_))
+    ));
+    assert!(matches!(
+        PolyEndpointClient::builder().contract_address(address).header("X-Api-Key", "a\r\nb").build(),
+        Err(ClientError::Config(_))
+    ));
+
+    // A dead URL falls through to the next one, and the result is cached
+    let response = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": all_endpoints_result() }).to_string();
+    let url = mock_rpc(vec![response]).await;
+    let client = PolyEndpointClient::builder()
+        .contract_address(address)
+        .rpc_urls(["http://127.0.0.1:1", url.as_str()])
+        .timeout(Duration::from_secs(5))
+        .header("X-Api-Key", "secret")
+        .cache_ttl(Duration::from_secs(60))
+        .build()
+        .unwrap();
+    assert_eq!(client.contract_address(), "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+    assert_eq!(client.get_endpoints("ignored").await.unwrap().len(), 1);
+    assert_eq!(client.get_endpoints("ignored").await.unwrap().len(), 1);
+
+    // Every URL unreachable reports the last failure
+    client.clear_cache();
+    assert!(matches!(client.get_endpoints("ignored").await, Err(ClientError::Network(_))));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_rpc_trace() {
+    use polyendpoint_sdk::contract::encode_get_endpoint_count;
+    use polyendpoint_sdk::format_rpc_trace;
+
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "eth_call",
+        "params": [{ "to": "0x1234567890123456789012345678901234567890", "data": encode_get_endpoint_count().to_hex() }, "latest"],
+        "id": 1
+    });
+    let body = count_and_owner_responses().remove(0);
+    let text = format_rpc_trace("http://rpc", &request, &Ok(body.clone()));
+    assert!(text.starts_with("--> http://rpc {"), "{}", text);
+    assert!(text.contains(&format!("selector {} getEndpointCount()", encode_get_endpoint_count().to_hex())), "{}", text);
+    assert!(text.contains(&format!("<-- {}", body)), "{}", text);
+    assert!(text.ends_with(&format!("0x0000: {:064x}", 3)), "{}", text);
+
+    let text = format_rpc_trace("http://rpc", &request, &Err(ClientError::Timeout));
+    assert!(text.ends_with("<-- error: Request timed out"), "{}", text);
+
+    // Tracing only prints; reads still succeed
+    let url = mock_rpc(count_and_owner_responses()).await;
+    let client = PolyEndpointClient::builder()
+        .contract_address("0x1234567890123456789012345678901234567890")
+        .rpc_urls([url])
+        .trace(true)
+        .build()
+        .unwrap();
+    assert_eq!(client.get_endpoint_count("ignored").await.unwrap(), 3);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn count_and_owner_responses() -> Vec<String> {
+    let result = |data: String| serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": data }).to_string();
+    vec![
+        result(format!("0x{:064x}", 3)),
+        result(format!("0x{}{}", "00".repeat(12), "5aaeb6053f3e94c9b9a09f33669435e7ef1beaed")),
+    ]
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_count_and_owner() {
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    let url = mock_rpc(count_and_owner_responses()).await;
+    assert_eq!(client.get_endpoint_count(&url).await.unwrap(), 3);
+    assert_eq!(client.owner(&url).await.unwrap(), "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+
+    // A count that doesn't fit in u64 is an error, not truncated
+    let result = |data: String| serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": data }).to_string();
+    let url = mock_rpc(vec![
+        result(format!("0x{:064x}", u64::MAX)),
+        result(format!("0x{:064x}", u128::from(u64::MAX) + 1)),
+    ]).await;
+    assert_eq!(client.get_endpoint_count(&url).await.unwrap(), u64::MAX);
+    assert!(matches!(client.get_endpoint_count(&url).await, Err(ClientError::Decode(_))));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_no_contract() {
+    // The zero address is rejected before any request is made
+    let client = PolyEndpointClient::new("0x0000000000000000000000000000000000000000");
+    assert!(matches!(client.get_endpoints("http://127.0.0.1:1").await, Err(ClientError::NoContract(_))));
+    assert!(matches!(client.get_endpoint_count("http://127.0.0.1:1").await, Err(ClientError::NoContract(_))));
+
+    // An address without code answers `eth_call` with empty data
+    let empty = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x" }).to_string();
+    let url = mock_rpc(vec![empty.clone(), empty]).await;
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    match client.get_endpoints(&url).await {
+        Err(ClientError::NoContract(address)) => assert_eq!(address, "0x1234567890123456789012345678901234567890"),
+        other => panic!("expected NoContract, got {:?}", other),
+    }
+    assert!(matches!(client.owner(&url).await, Err(ClientError::NoContract(_))));
+
+    // Anything else that isn't 0x-prefixed, even-length hex is a decode error
+    let result = |data: &str| serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": data }).to_string();
+    let url = mock_rpc(vec![result(""), result("1"), result("0x1"), result("0xzz")]).await;
+    for _ in 0..4 {
+        assert!(matches!(client.get_endpoint_count(&url).await, Err(ClientError::Decode(_))));
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_is_contract() {
+    let result = |data: &str| serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": data }).to_string();
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    let url = mock_rpc(vec![result("0x"), result("0x6080604052")]).await;
+    assert!(!client.is_contract(&url).await.unwrap());
+    assert!(client.is_contract(&url).await.unwrap());
+
+    // With the check on, a read against an account without code stops at eth_getCode
+    let client = client.with_code_check();
+    let url = mock_rpc(vec![result("0x"), result("0x6080604052"), result(&format!("0x{:064x}", 3))]).await;
+    assert!(matches!(client.get_endpoint_count(&url).await, Err(ClientError::NoContract(_))));
+    assert_eq!(client.get_endpoint_count(&url).await.unwrap(), 3);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_health() {
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    let result = |data: &str| serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": data }).to_string();
+    let url = mock_rpc(vec![result("0x14a34"), result("0x3039")]).await;
+
+    let health = client.health(&url).await.unwrap();
+    assert_eq!(health.chain_id, 84532);
+    assert_eq!(health.block_number, 12345);
+
+    // The mock has no more responses, so the RPC is unreachable
+    assert!(matches!(client.health(&url).await, Err(ClientError::Network(_))));
+}
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+#[test]
+fn test_blocking_client() {
+    // The mock server needs a runtime of its own; the blocking client brings its own
+    let server = tokio::runtime::Runtime::new().unwrap();
+    let url = server.block_on(mock_rpc(count_and_owner_responses()));
+
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890").blocking().unwrap();
+    assert_eq!(client.get_endpoint_count(&url).unwrap(), 3);
+    assert_eq!(client.owner(&url).unwrap(), "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+    assert!(matches!(client.get_endpoints(&url), Err(ClientError::Network(_))));
+}
+
+#[cfg(all(feature = "any-runtime", not(target_arch = "wasm32")))]
+#[test]
+fn test_smol_executor() {
+    let server = tokio::runtime::Runtime::new().unwrap();
+    let url = server.block_on(mock_rpc(count_and_owner_responses()));
+
+    // No tokio runtime on this thread: the client brings its own reactor
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    smol::block_on(async {
+        assert_eq!(client.get_endpoint_count(&url).await.unwrap(), 3);
+        assert_eq!(client.owner(&url).await.unwrap(), "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+    });
+}
+
+#[cfg(all(feature = "ethers", not(target_arch = "wasm32")))]
+#[tokio::test]
+async fn test_from_middleware() {
+    use ethers::providers::{JsonRpcError, MockProvider, MockResponse, Provider};
+    use ethers::types::Address;
+
+    // Served last-pushed first
+    let mock = MockProvider::new();
+    mock.push_response(MockResponse::Error(JsonRpcError {
+        code: 3,
+        message: "execution reverted".to_string(),
+        data: None,
+    }));
+    mock.push::<String, _>(format!("0x{:064x}", 3)).unwrap();
+    mock.push::<String, _>(all_endpoints_result()).unwrap();
+
+    let contract: Address = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".parse().unwrap();
+    let client = PolyEndpointClient::from_middleware(Provider::new(mock), contract);
+    assert_eq!(client.contract_address(), "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+
+    // The network name is ignored; every read goes through the middleware
+    let endpoints = client.get_endpoints("not-a-network").await.unwrap();
+    assert_eq!(endpoints[0].url, "https://rpc.example.com");
+    assert_eq!(client.get_endpoint_count("mainnet").await.unwrap(), 3);
+    assert!(matches!(client.owner("mainnet").await, Err(ClientError::Reverted { reason: None })));
+}
+
+#[cfg(feature = "ethers")]
+#[test]
+fn test_ethers_decode() {
+    use polyendpoint_sdk::contract::{self, ethers_abi::{ParamType, Token}};
+
+    let tokens = vec![Token::Uint(7.into()), Token::Array(vec![Token::String("a".to_string())])];
+    let data = ethers::abi::encode(&tokens);
+    let types = [ParamType::Uint(256), ParamType::Array(Box::new(ParamType::String))];
+    assert_eq!(contract::decode(&types, &data).unwrap(), tokens);
+
+    assert!(matches!(contract::decode(&types, &data[..32]), Err(ClientError::Decode(_))));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_string_array_offsets() {
+    use ethers::abi::Token;
+
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    let strings = |items: &[&str]| Token::Array(items.iter().map(|s| Token::String(s.to_string())).collect());
+    let urls = strings(&["https://a.example.com", "https://b.example.com/a/path/longer/than/thirty-two/bytes"]);
+    let descriptions = strings(&["Primary", ""]);
+
+    // Standard layout from ethers: both heads, then the url array, then the descriptions
+    let standard = ethers::abi::encode(&[urls.clone(), descriptions.clone()]);
+
+    // Same arrays with their tails swapped. Element offsets inside each array
+    // are relative to the array itself, so only the two head offsets change.
+    let tail = |token: Token| ethers::abi::encode(&[token])[32..].to_vec();
+    let (urls_tail, descriptions_tail) = (tail(urls), tail(descriptions));
+    let mut reordered = Vec::new();
+    reordered.extend(ethers::abi::encode(&[Token::Uint((64 + descriptions_tail.len()).into())]));
+    reordered.extend(ethers::abi::encode(&[Token::Uint(64.into())]));
+    reordered.extend(descriptions_tail);
+    reordered.extend(urls_tail);
+
+    let result = |bytes: &[u8]| serde_json::json!({
+        "jsonrpc": "2.0", "id": 1, "result": format!("0x{}", hex::encode(bytes))
+    }).to_string();
+    let url = mock_rpc(vec![result(&standard), result(&reordered)]).await;
+
+    for _ in 0..2 {
+        let endpoints = client.get_endpoints(&url).await.unwrap();
+        assert_eq!(endpoints.len(), 2);
+        assert_eq!(endpoints[0].url, "https://a.example.com");
+        assert_eq!(endpoints[0].description, "Primary");
+        assert_eq!(endpoints[1].url, "https://b.example.com/a/path/longer/than/thirty-two/bytes");
+        assert_eq!(endpoints[1].description, "");
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_mismatched_description_count() {
+    use ethers::abi::Token;
+
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    let strings = |items: &[&str]| Token::Array(items.iter().map(|s| Token::String(s.to_string())).collect());
+    let result = |urls: &[&str], descriptions: &[&str]| serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "result": format!("0x{}", hex::encode(ethers::abi::encode(&[strings(urls), strings(descriptions)])))
+    }).to_string();
+
+    // Pairing positionally would silently drop or misattribute entries
+    let url = mock_rpc(vec![
+        result(&["https://a.example.com", "https://b.example.com"], &["Primary"]),
+        result(&["https://a.example.com"], &["Primary", "Backup"]),
+    ]).await;
+    for expected in ["2 urls but 1 descriptions", "1 urls but 2 descriptions"] {
+        match client.get_endpoints(&url).await {
+            Err(ClientError::Decode(message)) => assert!(message.contains(expected), "{}", message),
+            other => panic!("expected a decode error, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_endpoints_stream() {
+    use ethers::abi::Token;
+    use futures_util::StreamExt;
+    use polyendpoint_sdk::STREAM_BATCH_SIZE;
+
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    let total = STREAM_BATCH_SIZE + 4;
+    let result = |result: String| serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": result }).to_string();
+    let batch = |indices: std::ops::Range<u64>| serde_json::Value::Array(indices.rev()
+        .map(|i| serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": i,
+            "result": format!("0x{}", hex::encode(ethers::abi::encode(&[
+                Token::String(format!("https://rpc{}.example.com", i)),
+                Token::String(format!("Endpoint {}", i)),
+            ])))
+        }))
+        .collect()).to_string();
+
+    let url = mock_rpc(vec![
+        result("0x10".to_string()),
+        result(format!("0x{}", hex::encode(ethers::abi::encode(&[Token::Uint(total.into())])))),
+        batch(0..STREAM_BATCH_SIZE),
+        batch(STREAM_BATCH_SIZE..total),
+    ]).await;
+
+    let endpoints: Vec<_> = client.endpoints_stream(&url).collect().await;
+    assert_eq!(endpoints.len() as u64, total);
+    for (i, endpoint) in endpoints.into_iter().enumerate() {
+        let endpoint = endpoint.unwrap();
+        assert_eq!(endpoint.url, format!("https://rpc{}.example.com", i));
+        assert_eq!(endpoint.description, format!("Endpoint {}", i));
+    }
+
+    // An error ends the stream
+    let url = mock_rpc(vec![serde_json::json!({ "jsonrpc": "2.0", "id": 1 }).to_string()]).await;
+    let results: Vec<_> = client.endpoints_stream(&url).collect().await;
+    assert!(matches!(results.as_slice(), [Err(ClientError::Parse(_))]));
+
+    let results: Vec<_> = client.endpoints_stream("base-seplia").collect().await;
+    assert!(matches!(results.as_slice(), [Err(ClientError::UnknownNetwork(_))]));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_error_classification() {
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    let error = |error: serde_json::Value| serde_json::json!({ "jsonrpc": "2.0", "id": 1, "error": error }).to_string();
+
+    let mut revert_data = polyendpoint_sdk::contract::ERROR_STRING_SELECTOR.to_vec();
+    revert_data.extend(ethers::abi::encode(&[ethers::abi::Token::String("not found".into())]));
+
+    let url = mock_http(vec![
+        (429, "Too Many Requests".to_string()),
+        (200, error(serde_json::json!({ "code": -32005, "message": "limit exceeded" }))),
+        (200, error(serde_json::json!({
+            "code": 3,
+            "message": "execution reverted: not found",
+            "data": format!("0x{}", hex::encode(&revert_data))
+        }))),
+        (200, error(serde_json::json!({ "code": -32000, "message": "execution reverted" }))),
+        (200, error(serde_json::json!({ "code": -32601, "message": "method not found" }))),
+        (502, "Bad Gateway".to_string()),
+        (401, r#"{"message":"invalid API key"}"#.to_string()),
+        (500, "x".repeat(1000)),
+        (500, error(serde_json::json!({ "code": -32000, "message": "execution reverted" }))),
+    ]).await;
+
+    assert!(matches!(client.get_endpoints(&url).await, Err(ClientError::RateLimited)));
+    assert!(matches!(client.get_endpoints(&url).await, Err(ClientError::RateLimited)));
+    assert!(matches!(
+        client.get_endpoints(&url).await,
+        Err(ClientError::Reverted { reason: Some(reason) }) if reason == "not found"
+    ));
+    assert!(matches!(client.get_endpoints(&url).await, Err(ClientError::Reverted { reason: None })));
+    assert!(matches!(
+        client.get_endpoints(&url).await,
+        Err(ClientError::RpcError { code: -32601, message }) if message == "method not found"
+    ));
+    assert!(matches!(client.get_endpoints(&url).await, Err(ClientError::Network(_))));
+
+    // A gateway's JSON error page is reported with its status, not parsed as a response
+    assert!(matches!(
+        client.get_endpoints(&url).await,
+        Err(ClientError::Network(message)) if message == r#"HTTP 401: {"message":"invalid API key"}"#
+    ));
+    assert!(matches!(
+        client.get_endpoints(&url).await,
+        Err(ClientError::Network(message)) if message.starts_with("HTTP 500: xxx") && message.len() < 250
+    ));
+    assert!(matches!(client.get_endpoints(&url).await, Err(ClientError::Reverted { reason: None })));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_estimate_cost() {
+    use polyendpoint_sdk::GasEstimate;
+
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    let tx = polyendpoint_sdk::contract::encode_add_endpoint("https://rpc.example.com");
+    let from = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    let result = |result: serde_json::Value| serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": result }).to_string();
+
+    // EIP-1559: 21000 gas at a 1 gwei base fee + 1 gwei tip
+    let url = mock_rpc(vec![
+        result("0x5208".into()),
+        result(serde_json::json!({ "number": "0x10", "baseFeePerGas": "0x3b9aca00" })),
+        result("0x3b9aca00".into()),
+    ]).await;
+    let estimate = client.estimate_cost(&url, from, &tx).await.unwrap();
+    assert_eq!(estimate, GasEstimate::new(21_000, 2_000_000_000));
+    assert_eq!(estimate.wei, 42_000_000_000_000);
+    assert_eq!(estimate.eth_string, "0.000042");
+    assert_eq!(estimate.usd_string(|| Some(2000.0)).as_deref(), Some("$0.08"));
+    assert_eq!(estimate.usd_string(|| None), None);
+
+    // Legacy chain: no base fee, so eth_gasPrice is used
+    let url = mock_rpc(vec![
+        result("0x5208".into()),
+        result(serde_json::json!({ "number": "0x10" })),
+        result("0x77359400".into()),
+    ]).await;
+    assert_eq!(client.estimate_cost(&url, from, &tx).await.unwrap().wei, 42_000_000_000_000);
+
+    assert_eq!(GasEstimate::new(1, 1_500_000_000_000_000_000).eth_string, "1.5");
+    assert_eq!(GasEstimate::new(0, 1).eth_string, "0");
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_suggested_fees() {
+    use polyendpoint_sdk::FeeEstimate;
+
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    let result = |result: serde_json::Value| serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": result }).to_string();
+    let gwei = |n: u128| format!("{:#x}", n * 1_000_000_000);
+
+    // Tips of 1, 3 and 2 gwei; the next block's base fee is 10 gwei
+    let url = mock_rpc(vec![
+        result(gwei(12).into()),
+        result(serde_json::json!({
+            "oldestBlock": "0x10",
+            "baseFeePerGas": [gwei(9), gwei(9), gwei(9), gwei(10)],
+            "reward": [[gwei(1)], [gwei(3)], [gwei(2)]],
+        })),
+    ]).await;
+    assert_eq!(client.suggested_fees(&url).await.unwrap(), FeeEstimate {
+        gas_price: 12_000_000_000,
+        base_fee: Some(10_000_000_000),
+        priority_fee: 2_000_000_000,
+        max_fee: 22_000_000_000,
+    });
+
+    // No eth_feeHistory: legacy pricing
+    let url = mock_rpc(vec![
+        result(gwei(5).into()),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32601, "message": "method not found" } }).to_string(),
+    ]).await;
+    let fees = client.suggested_fees(&url).await.unwrap();
+    assert_eq!(fees.base_fee, None);
+    assert_eq!(fees.max_fee, 5_000_000_000);
+}
+
+#[test]
+fn test_tx_outcome() {
+    use polyendpoint_sdk::{TxOutcome, TxStatus};
+
+    let hash = format!("0x{}", "ab".repeat(32));
+    let mut receipt = serde_json::json!({
+        "transactionHash": hash,
+        "blockNumber": "0x10",
+        "gasUsed": "0x5208",
+        "status": "0x1",
+        "effectiveGasPrice": "0x77359400",
+    });
+    let outcome = TxOutcome::from_receipt(&receipt).unwrap();
+    assert_eq!(outcome.hash, hash);
+    assert_eq!(outcome.block_number, 16);
+    assert_eq!(outcome.gas_used, 21_000);
+    assert!(outcome.succeeded());
+    assert_eq!(outcome.fee().unwrap().wei, 42_000_000_000_000);
+
+    receipt["status"] = "0x0".into();
+    receipt["effectiveGasPrice"] = serde_json::Value::Null;
+    let outcome = TxOutcome::from_receipt(&receipt).unwrap();
+    assert_eq!(outcome.status, TxStatus::Reverted);
+    assert_eq!(outcome.fee(), None);
+    assert_eq!(serde_json::to_value(&outcome).unwrap()["status"], "reverted");
+
+    receipt.as_object_mut().unwrap().remove("blockNumber");
+    assert!(matches!(TxOutcome::from_receipt(&receipt), Err(ClientError::Parse(_))));
+}
+
+#[test]
+fn test_abi_decoder() {
+    use ethers::abi::Token;
+    use ethers::types::Address;
+    use polyendpoint_sdk::contract::abi::AbiDecoder;
+
+    let owner: Address = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".parse().unwrap();
+    let strings = |items: &[&str]| Token::Array(items.iter().map(|s| Token::String(s.to_string())).collect());
+    let data = ethers::abi::encode(&[
+        Token::Uint(42.into()),
+        Token::Address(owner),
+        Token::Bool(true),
+        Token::String("héllo".into()),
+        strings(&["https://a.example.com", "", "https://b.example.com/a/path/longer/than/thirty-two/bytes"]),
+    ]);
+
+    assert_eq!(AbiDecoder::decode_uint256(&data, 0), Ok(42));
+    assert_eq!(AbiDecoder::decode_address(&data, 32).as_deref(), Ok("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"));
+    assert_eq!(AbiDecoder::decode_bool(&data, 64), Ok(true));
+    assert_eq!(AbiDecoder::decode_string(&data, 96).as_deref(), Ok("héllo"));
+    assert_eq!(
+        AbiDecoder::decode_string_array(&data, 128).unwrap(),
+        ["https://a.example.com", "", "https://b.example.com/a/path/longer/than/thirty-two/bytes"]
+    );
+
+    // Wrong types and out-of-bounds offsets are errors, not panics
+    assert!(AbiDecoder::decode_bool(&data, 0).is_err());
+    assert!(AbiDecoder::decode_address(&[0xffu8; 32], 0).is_err());
+    assert!(AbiDecoder::decode_uint256(&data, data.len() - 16).is_err());
+    assert!(AbiDecoder::decode_uint256(&data, usize::MAX).is_err());
+    assert!(AbiDecoder::decode_string(&data, 0).is_err());
+    assert!(AbiDecoder::decode_string_array(&data[..data.len() - 32], 128).is_err());
+    let huge_length = [ethers::abi::encode(&[Token::Uint(32.into())]), vec![0xff; 32]].concat();
+    assert!(AbiDecoder::decode_string_array(&huge_length, 0).is_err());
+}
+
+#[test]
+fn test_decode_endpoint_round_trip() {
+    use ethers::abi::Token;
+    use polyendpoint_sdk::{contract, EndpointInfo};
+
+    let result = ethers::abi::encode(&[
+        Token::String("https://rpc.example.com".into()),
+        Token::String("Primary RPC with a description longer than one word".into()),
+    ]);
+    assert_eq!(
+        contract::decode_endpoint(&result).unwrap(),
+        EndpointInfo::new("https://rpc.example.com", "Primary RPC with a description longer than one word")
+    );
+
+    assert!(contract::decode_endpoint(&result[..result.len() - 32]).is_err());
+    assert!(contract::decode_endpoint(&[]).is_err());
+}
+
+#[test]
+fn test_decode_address_and_bool() {
+    use ethers::abi::Token;
+    use polyendpoint_sdk::contract::{decode_address, decode_bool};
+
+    let owner: ethers::types::Address = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".parse().unwrap();
+    let result = ethers::abi::encode(&[Token::Address(owner)]);
+    assert_eq!(decode_address(&result).unwrap(), "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+    assert!(decode_address(&result[..31]).is_err());
+    assert!(decode_address(&[0xff; 32]).is_err());
+
+    assert!(decode_bool(&ethers::abi::encode(&[Token::Bool(true)])).unwrap());
+    assert!(!decode_bool(&ethers::abi::encode(&[Token::Bool(false)])).unwrap());
+    assert!(decode_bool(&ethers::abi::encode(&[Token::Uint(2.into())])).is_err());
+    assert!(decode_bool(&[]).is_err());
+}
+
+#[test]
+fn test_validate_endpoint_url() {
+    use polyendpoint_sdk::{contract, EndpointInfo};
+
+    for url in ["https://rpc.example.com", "http://127.0.0.1:8545", "wss://ws.example.com/v1/key"] {
+        assert!(EndpointInfo::new(url, "").validate().is_ok(), "{}", url);
+        assert_eq!(
+            contract::encode_add_endpoint_validated(url).unwrap(),
+            contract::encode_add_endpoint(url)
+        );
+    }
+    for url in ["", "rpc.example.com", "htps://rpc.example.com", "ftp://rpc.example.com", "https://"] {
+        assert!(EndpointInfo::new(url, "").validate().is_err(), "{}", url);
+        assert!(contract::encode_add_endpoint_validated(url).is_err(), "{}", url);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::test]
+async fn test_network_name() {
+    use polyendpoint_sdk::NetworkName;
+
+    assert_eq!("mainnet".parse::<NetworkName>().unwrap(), NetworkName::Mainnet);
+    assert_eq!("Base-Testnet".parse::<NetworkName>().unwrap(), NetworkName::BaseSepolia);
+    assert_eq!("base-mainnet".parse::<NetworkName>().unwrap(), NetworkName::Base);
+    assert_eq!(
+        "https://my.rpc".parse::<NetworkName>().unwrap(),
+        NetworkName::Custom("https://my.rpc".to_string())
+    );
+    assert!(matches!("base-seplia".parse::<NetworkName>(), Err(ClientError::UnknownNetwork(name)) if name == "base-seplia"));
+    assert_eq!(NetworkName::Arbitrum.to_string(), "arbitrum");
+
+    // A typo fails before any request is made
+    let client = PolyEndpointClient::new("0x1234567890123456789012345678901234567890");
+    assert!(matches!(client.get_endpoints("base-seplia").await, Err(ClientError::UnknownNetwork(_))));
+    assert!(matches!(client.get_endpoint_count("localhost:8545").await, Err(ClientError::UnknownNetwork(_))));
+
+    let response = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": all_endpoints_result() }).to_string();
+    let url = mock_rpc(vec![response]).await;
+    let endpoints = client.get_endpoints(NetworkName::Custom(url)).await.unwrap();
+    assert_eq!(endpoints[0].url, "https://rpc.example.com");
+}
+
+#[test]
+fn test_network_registry() {
+    use polyendpoint_sdk::networks::NetworkRegistry;
+
+    let mut registry = NetworkRegistry::builtin();
+    assert_eq!(registry.rpc_url("base-testnet"), "https://sepolia.base.org");
+    // Every built-in network has fallback RPCs
+    for network in registry.networks() {
+        let urls = registry.resolve(&network.name).unwrap();
+        assert!(urls.len() >= 2, "{}", network.name);
+        assert!(urls.iter().all(|url| url.starts_with("https://")));
+    }
+    assert_eq!(registry.get("Base-Sepolia").unwrap().chain_id, 84532);
+    assert_eq!(registry.rpc_url("https://my.rpc"), "https://my.rpc");
+
+    let file = NetworkRegistry::from_toml(r#"
+        [[network]]
+        name = "optimism"
+        chain_id = 10
+        rpc_urls = ["https://mainnet.optimism.io", "https://optimism.example.com"]
+
+        [[network]]
+        name = "base-sepolia"
+        chain_id = 84532
+        rpc_urls = ["https://base-sepolia.example.com"]
+    "#).unwrap();
+    registry.merge(file);
+
+    assert_eq!(registry.rpc_url("optimism"), "https://mainnet.optimism.io");
+    assert_eq!(registry.rpc_url("base-sepolia"), "https://base-sepolia.example.com");
+    // The override replaces the whole entry, aliases included
+    assert_eq!(registry.rpc_url("base-testnet"), "base-testnet");
+    assert!(matches!(registry.resolve("base-testnet"), Err(ClientError::UnknownNetwork(name)) if name == "base-testnet"));
+    assert_eq!(registry.resolve("optimism").unwrap(), ["https://mainnet.optimism.io", "https://optimism.example.com"]);
+    assert_eq!(registry.resolve("http://localhost:8545").unwrap(), ["http://localhost:8545"]);
+
+    assert!(NetworkRegistry::from_toml("[[network]]\nname = \"x\"\nchain_id = 1\nrpc_urls = []").is_err());
+    assert_eq!(NetworkRegistry::load("does-not-exist.toml").unwrap(), NetworkRegistry::builtin());
+}
@@ -0,0 +1,53 @@
+//! The calldata builders with default features off: no `std`, no RPC
+//! client, no reqwest or ethers. Only `contract`, `contract::abi` and
+//! `EndpointInfo` may be used here, so this file also fails to compile if
+//! they start depending on a feature.
+//!
+//! Run with `cargo test --no-default-features --test minimal_test`, or
+//! `scripts/check_minimal.sh`, which also checks the dependency tree.
+
+use polyendpoint_sdk::contract::{
+    self,
+    abi::{AbiDecoder, AbiEncoder},
+    TransactionData,
+};
+use polyendpoint_sdk::{selector, EndpointInfo};
+
+fn word(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+#[test]
+fn test_minimal_encoders() {
+    let data = contract::encode_add_endpoint("abc").build();
+    assert_eq!(data[..4], selector("addEndpoint(string)"));
+
+    let mut padded = [0u8; 32];
+    padded[..3].copy_from_slice(b"abc");
+    assert_eq!(data[4..], [word(0x20), word(3), padded].concat());
+
+    assert_eq!(
+        contract::encode_get_endpoint_count().build(),
+        selector("getEndpointCount()").to_vec()
+    );
+    assert_eq!(contract::encode_has_endpoint("abc").selector_name(), Some("hasEndpoint(string)"));
+    assert!(contract::encode_transfer_ownership("0xABC").is_err());
+
+    let tx = TransactionData::from_hex(&contract::encode_remove_endpoint("abc").to_hex()).unwrap();
+    assert_eq!(tx.selector_name(), Some("removeEndpoint(string)"));
+    assert_eq!(tx.args, AbiEncoder::encode_string_args(&["abc"]));
+}
+
+#[test]
+fn test_minimal_decoders() {
+    let result = AbiEncoder::encode_string_args(&["https://rpc.example.com"]);
+    assert_eq!(AbiDecoder::decode_string(&result, 0).unwrap(), "https://rpc.example.com");
+    assert!(contract::decode_bool(&word(1)).unwrap());
+    assert!(contract::decode_bool(&word(2)).is_err());
+
+    let endpoint = EndpointInfo::new("https://rpc.example.com", "Primary");
+    assert!(endpoint.validate().is_ok());
+    assert!(EndpointInfo::new("ftp://rpc.example.com", "").validate().is_err());
+}
@@ -5,7 +5,7 @@
 #![cfg(target_arch = "wasm32")]
 
 use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
-use polyendpoint_sdk::PolyEndpointClient;
+use polyendpoint_sdk::{PolyEndpointClient, PolyEndpointSdk, WasmPolyEndpointClient};
 
 // Base testnet contract address
 const BASE_SEPOLIA_CONTRACT: &str = "0xf16e03526d1be6d120cfbf5a24e1ac78a8192663";
@@ -61,7 +61,55 @@ fn test_client_creation() {
     
     assert_eq!(client.contract_address(), BASE_SEPOLIA_CONTRACT);
     
-    let client2 = PolyEndpointClient::new(format!("0x{}", "1234567890"));
-    assert_eq!(client2.contract_address(), "0x1234567890");
+    let client2 = PolyEndpointClient::try_new(format!("0x{}", "1234567890"));
+    assert!(client2.is_err());
 }
 
+#[wasm_bindgen_test]
+fn test_js_client_validates_address() {
+    let client = WasmPolyEndpointClient::new("0xf16e03526d1be6d120cfbf5a24e1ac78a8192663", None).unwrap();
+    assert_eq!(client.contract_address(), "0xf16e03526d1BE6d120cFBF5a24e1AC78a8192663");
+
+    assert!(WasmPolyEndpointClient::new("0x1234567890", Some(5_000)).is_err());
+    assert!(WasmPolyEndpointClient::new("not an address", None).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_sdk_get_endpoint_index() {
+    let sdk = PolyEndpointSdk::new();
+
+    let calldata = sdk.get_endpoint("5").unwrap();
+    assert_eq!(calldata, format!("{}{:064x}", sdk.method_id("getEndpoint(uint256)"), 5));
+
+    // Indices past u32 / JS safe integers are encoded exactly
+    let calldata = sdk.get_endpoint("18446744073709551616").unwrap();
+    assert!(calldata.ends_with(&format!("{:064x}", 1u128 << 64)));
+
+    assert!(sdk.get_endpoint("abc").is_err());
+    assert!(sdk.get_endpoint("-1").is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_sdk_build_returns_parts() {
+    use std::collections::HashMap;
+
+    let sdk = PolyEndpointSdk::new();
+    let built: HashMap<String, String> =
+        serde_wasm_bindgen::from_value(sdk.build_add_endpoint("https://rpc.example.com").unwrap()).unwrap();
+    assert_eq!(built["selector"], sdk.method_id("addEndpoint(string)"));
+    assert_eq!(built["calldata"], sdk.add_endpoint("https://rpc.example.com"));
+    assert_eq!(format!("{}{}", built["selector"], &built["args"][2..]), built["calldata"]);
+
+    let built: HashMap<String, String> =
+        serde_wasm_bindgen::from_value(sdk.build_add_admin("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap()).unwrap();
+    assert_eq!(built["args"], format!("0x{:0>64}", "5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"));
+    assert!(sdk.build_add_admin("0xABC").is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_sdk_endpoint_description_builders() {
+    let sdk = PolyEndpointSdk::new();
+
+    let calldata = sdk.add_endpoint_with_description("https://rpc.example.com", "Primary");
+    assert!(calldata.starts_with(&sdk.method_id("addEndpoint(string,string)")));
+}